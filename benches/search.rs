@@ -0,0 +1,21 @@
+use std::time::Instant;
+
+use chess::board::turn::GameState;
+use chess::bench;
+
+// Runs a benchmark function a number of times and prints the average time taken
+fn run<T>(name: &str, iterations: u32, mut f: impl FnMut() -> T) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(f());
+    }
+    println!("{}: {:?} per iteration", name, start.elapsed() / iterations);
+}
+
+fn main() {
+    let game_state = GameState::new();
+
+    run("gen_all_moves (start position)", 1000, || bench::bench_gen_all_moves(game_state.board_info));
+    run("perft depth 3 (start position)", 3, || bench::bench_perft(3));
+    run("best_move depth 3 (start position)", 3, || bench::bench_best_move(game_state, 3));
+}
@@ -91,29 +91,1548 @@ pub const PIECE_HEATMAPS: [[[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]; IDS.len()] = [
     */
 ];
 
+// Module containing positional evaluation terms used at the end of a search
+// Evaluations are from the perspective of the team whose turn it is
+pub mod eval {
+    use super::*;
+    use crate::board::turn::new_turn;
+    use crate::board::turn::GameState;
+    use crate::piece::moves::Move;
+
+    // Returns the penalty for a teams pieces that are attacked by the enemy more times than they are defended
+    // Each hanging piece is penalised by half of its value
+    fn hanging_penalty(
+    team_attackers: [[u8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+    enemy_attackers: [[u8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+    white: bool,
+    game_state: GameState)
+    -> i8 {
+        use crate::piece_white;
+
+        let board_info = game_state.board_info;
+        let mut penalty = 0;
+
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let id = board_info.board[x][y];
+
+                if id != 0 && piece_white(id) == white && enemy_attackers[x][y] > team_attackers[x][y] {
+                    penalty += board_info.pieces[usize::try_from(id.abs() - 1).unwrap()].value / 2;
+                }
+            }
+        }
+        penalty
+    }
+
+    // Returns the hanging piece penalties of the team to move and the enemy
+    fn hanging_penalties(game_state: GameState) -> [i8; 2] {
+        use crate::piece::moves::attacker_count;
+        use crate::piece::moves::enemy_attacker_count;
+
+        let white = game_state.whites_turn;
+        let team_attackers = attacker_count(white, game_state.board_info);
+        let enemy_attackers = enemy_attacker_count(white, game_state.board_info);
+
+        let team_penalty = hanging_penalty(team_attackers, enemy_attackers, white, game_state);
+        let enemy_penalty = hanging_penalty(enemy_attackers, team_attackers, !white, game_state);
+        [team_penalty, enemy_penalty]
+    }
+
+    // Returns the enemies hanging piece penalty minus the hanging piece penalty of the team to move
+    pub fn hanging_score(game_state: GameState) -> i8 {
+        let penalties = hanging_penalties(game_state);
+        penalties[1] - penalties[0]
+    }
+
+    // An evaluation with an explicit perspective
+    // Stored from whites perspective, so converting it to a teams perspective can only negate it once
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Score(i32);
+
+    impl Score {
+        pub fn from_white_perspective(value: i32) -> Self {
+            Score(value)
+        }
+
+        // Creates a score from a value relative to a team, positive values are good for the team given by white
+        pub fn from_perspective(value: i32, white: bool) -> Self {
+            match white {
+                true => Score(value),
+                false => Score(-value),
+            }
+        }
+
+        pub fn white_perspective(self) -> i32 {
+            self.0
+        }
+
+        // Returns the score relative to the team given by white, positive values are good for that team
+        pub fn relative_to(self, white: bool) -> i32 {
+            match white {
+                true => self.0,
+                false => -self.0,
+            }
+        }
+    }
+
+    // Summary of the pieces each team has, arrays are indexed by team (0 for white, 1 for black)
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Imbalance {
+        pub knights: [u8; 2],
+        pub bishops: [u8; 2],
+        pub rooks: [u8; 2],
+        pub queens: [u8; 2],
+        pub minor_pieces: [u8; 2], // Knights and bishops
+        pub major_pieces: [u8; 2], // Rooks and queens
+        pub bishop_pair: [bool; 2], // Bishops on both square colours
+        pub rook_pair: [bool; 2], // Two or more rooks, which overlap in what they do
+        pub knight_vs_bishop: Option<bool>, // Some(true) when white has a lone knight against a lone bishop as minor pieces, Some(false) for the reverse
+        pub opposite_bishops: bool, // Each team has a single bishop, on different square colours
+    }
+
+    // Returns the material imbalance features of a board, the board can be from either perspective
+    pub fn imbalance(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> Imbalance {
+        let mut imbalance = Imbalance {
+            knights: [0; 2],
+            bishops: [0; 2],
+            rooks: [0; 2],
+            queens: [0; 2],
+            minor_pieces: [0; 2],
+            major_pieces: [0; 2],
+            bishop_pair: [false; 2],
+            rook_pair: [false; 2],
+            knight_vs_bishop: None,
+            opposite_bishops: false,
+        };
+
+        // Square colours the bishops of each team are on
+        let mut bishop_colours = [[false; 2]; 2];
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let id = board[x][y];
+                let team = match id {
+                    0 => continue,
+                    id if id > 0 => 0,
+                    _ => 1,
+                };
+
+                match id.abs() {
+                    id if id == IDS[2] => imbalance.knights[team] += 1,
+                    id if id == IDS[3] => {
+                        imbalance.bishops[team] += 1;
+                        bishop_colours[team][(x + y) % 2] = true;
+                    },
+                    id if id == IDS[1] => imbalance.rooks[team] += 1,
+                    id if id == IDS[4] => imbalance.queens[team] += 1,
+                    _ => (),
+                }
+            }
+        }
+
+        for (team, colours) in bishop_colours.iter().enumerate() {
+            imbalance.minor_pieces[team] = imbalance.knights[team] + imbalance.bishops[team];
+            imbalance.major_pieces[team] = imbalance.rooks[team] + imbalance.queens[team];
+            imbalance.bishop_pair[team] = colours[0] && colours[1];
+            imbalance.rook_pair[team] = imbalance.rooks[team] >= 2;
+        }
+
+        let lone_knight = |team: usize| imbalance.knights[team] == 1 && imbalance.bishops[team] == 0;
+        let lone_bishop = |team: usize| imbalance.bishops[team] == 1 && imbalance.knights[team] == 0;
+        if lone_knight(0) && lone_bishop(1) {
+            imbalance.knight_vs_bishop = Some(true);
+        } else if lone_bishop(0) && lone_knight(1) {
+            imbalance.knight_vs_bishop = Some(false);
+        }
+
+        imbalance.opposite_bishops = imbalance.bishops == [1, 1] && bishop_colours[0] != bishop_colours[1];
+        imbalance
+    }
+
+    pub const UNSTOPPABLE_PAWN_BONUS: i8 = 4; // Bonus for a passed pawn the enemy king can't catch when the enemy only has pawns left
+
+    // Returns true if no enemy pawn is in front of the pawn on square, on its own or an adjacent column
+    // board has to be from the perspective of the pawns team
+    pub fn passed_pawn(square: [i8; 2], board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> bool {
+        let enemy_pawn_id = -IDS[0] * crate::get_board(square, board).signum();
+
+        for x in square[0] - 1..square[0] + 2 {
+            for y in square[1] + 1..i8::try_from(BOARD_SIZE[1]).unwrap() {
+                if crate::fits_in_board([x, y]) && crate::get_board([x, y], board) == enemy_pawn_id {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    // Returns true if a teams king is on its back row with every square in front of it taken by its own pieces (no luft)
+    // and no rook or queen on the back row to defend it, so a rook or queen reaching the back row mates
+    // board has to be from whites perspective
+    pub fn back_rank_weak(white: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> bool {
+        use crate::piece_white;
+
+        let mut king_id = IDS[5];
+        let mut back_y = 0;
+        let mut forward = 1;
+        if !white {
+            king_id *= -1;
+            back_y = i8::try_from(BOARD_SIZE[1] - 1).unwrap();
+            forward = -1;
+        }
+
+        let king = match crate::find_id_in_board(king_id, board) {
+            Some(king) if king[1] == back_y => king,
+            _ => return false,
+        };
+
+        for x in king[0] - 1..king[0] + 2 {
+            let square = [x, back_y + forward];
+            if !crate::fits_in_board(square) {
+                continue;
+            }
+
+            let id = crate::get_board(square, board);
+            if id == 0 || piece_white(id) != white {
+                return false;
+            }
+        }
+
+        // A rook or queen on the back row can block or capture a checking piece
+        let back_row = usize::try_from(back_y).unwrap();
+        !(0..BOARD_SIZE[0]).any(|x| {
+            let id = board[x][back_row];
+            id != 0 && piece_white(id) == white && (id.abs() == IDS[1] || id.abs() == IDS[4])
+        })
+    }
+
+    // Returns the passed pawn bonus of a team, board has to be from the perspective of the team
+    // Passed pawns get a bonus for each two rows they have advanced, and UNSTOPPABLE_PAWN_BONUS when they are outside the square of the enemy king
+    fn passed_pawn_bonus(white: bool, to_move: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> i8 {
+        use crate::piece_white;
+
+        let y_max = i8::try_from(BOARD_SIZE[1] - 1).unwrap();
+
+        // Only enemy pawns and the enemy king can be left for the square of the king to apply
+        let enemy_pieces = board.iter().flatten().any(|id| *id != 0 && piece_white(*id) != white && id.abs() != IDS[0] && id.abs() != IDS[5]);
+        let mut enemy_king_id = IDS[5];
+        if white {
+            enemy_king_id *= -1;
+        }
+        let enemy_king = crate::find_id_in_board(enemy_king_id, board);
+
+        let mut bonus = 0;
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let coordinates = crate::coordinates_from_usize([x, y]);
+                let id = board[x][y];
+                if id.abs() != IDS[0] || piece_white(id) != white || !passed_pawn(coordinates, board) {
+                    continue;
+                }
+
+                bonus += (coordinates[1] - 1) / 2;
+
+                let enemy_king = match enemy_king {
+                    Some(enemy_king) if !enemy_pieces => enemy_king,
+                    _ => continue,
+                };
+
+                // Pawns on their starting row can move two squares
+                let promotion_distance = y_max - std::cmp::max(coordinates[1], 2);
+
+                // The enemy king gets one square closer when it is the enemies move
+                let promotion_square = [coordinates[0], y_max];
+                let mut king_distance = std::cmp::max((enemy_king[0] - promotion_square[0]).abs(), (enemy_king[1] - promotion_square[1]).abs());
+                if !to_move {
+                    king_distance -= 1;
+                }
+
+                if king_distance > promotion_distance {
+                    bonus += UNSTOPPABLE_PAWN_BONUS;
+                }
+            }
+        }
+        bonus
+    }
+
+    // Returns the passed pawn bonus of the team to move minus the enemies bonus
+    // Passed pawns matter in endgames, so the score is scaled by the game phase and is 0 while all pieces are on the board
+    pub fn passed_pawn_score(game_state: GameState) -> i8 {
+        use crate::board::phase_value;
+        use crate::board::PHASE_MAX;
+
+        let board = game_state.board_info.board;
+        let white = game_state.whites_turn;
+
+        let team_bonus = i32::from(passed_pawn_bonus(white, true, board));
+        let enemy_bonus = i32::from(passed_pawn_bonus(!white, false, crate::flip_board(board)));
+
+        let score = (team_bonus - enemy_bonus) * phase_value(board) / PHASE_MAX;
+        i8::try_from(score).unwrap()
+    }
+
+    // Returns the mop up bonus of a team against a bare enemy king, 0 if the enemy has more than a king or the team has no pieces to mate with
+    // The enemy king is pushed to the edge of the board and the teams king is brought closer, so the mate can be found within the search depth
+    fn mop_up_bonus(white: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> i8 {
+        use crate::piece_white;
+
+        let enemy_pieces = board.iter().flatten().any(|id| *id != 0 && piece_white(*id) != white && id.abs() != IDS[5]);
+        let mating_pieces = board.iter().flatten().any(|id| *id != 0 && piece_white(*id) == white && id.abs() != IDS[0] && id.abs() != IDS[5]);
+        if enemy_pieces || !mating_pieces {
+            return 0;
+        }
+
+        let mut king_id = IDS[5];
+        if !white {
+            king_id *= -1;
+        }
+        let (king, enemy_king) = match (crate::find_id_in_board(king_id, board), crate::find_id_in_board(-king_id, board)) {
+            (Some(king), Some(enemy_king)) => (king, enemy_king),
+            _ => return 0,
+        };
+
+        // Distance of the enemy king from the four center squares (0 - 6), and how close the kings are (0 - 6)
+        let edge_distance = |coordinate: i8| std::cmp::max(3 - coordinate, coordinate - 4);
+        let edge = edge_distance(enemy_king[0]) + edge_distance(enemy_king[1]);
+        let closeness = {14 - (king[0] - enemy_king[0]).abs() - (king[1] - enemy_king[1]).abs()} / 2;
+
+        edge + closeness
+    }
+
+    // Returns the mop up bonus of the team to move minus the enemies bonus
+    pub fn mop_up_score(game_state: GameState) -> i8 {
+        let board = game_state.board_info.board;
+        mop_up_bonus(game_state.whites_turn, board) - mop_up_bonus(!game_state.whites_turn, board)
+    }
+
+    // Returns the king activity of a team in half points, board has to be from the perspective of the team
+    // In the endgame the king is rewarded for being central and close to passed pawns (its own to escort them, the enemies to stop them)
+    // Earlier on it is rewarded for staying away from the center, the two are blended by the game phase
+    fn king_activity(white: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> i32 {
+        use crate::board::phase_value;
+        use crate::board::PHASE_MAX;
+        use crate::piece_white;
+
+        let mut king_id = IDS[5];
+        if !white {
+            king_id *= -1;
+        }
+        let king = match crate::find_id_in_board(king_id, board) {
+            Some(king) => king,
+            None => return 0,
+        };
+
+        // Distance of the king from the four center squares (0 - 6)
+        let edge_distance = |coordinate: i8| i32::from(std::cmp::max(3 - coordinate, coordinate - 4));
+        let center_distance = edge_distance(king[0]) + edge_distance(king[1]);
+
+        // How close the king is to the nearest passed pawn of either team (0 - 6)
+        let mut pawn_closeness = 0;
+        let flipped_board = crate::flip_board(board);
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let id = board[x][y];
+                if id.abs() != IDS[0] {
+                    continue;
+                }
+
+                let coordinates = crate::coordinates_from_usize([x, y]);
+                let passed = match piece_white(id) == white {
+                    true => passed_pawn(coordinates, board),
+                    false => passed_pawn(crate::flip_coordinates(coordinates), flipped_board),
+                };
+                if passed {
+                    let distance = std::cmp::max((king[0] - coordinates[0]).abs(), (king[1] - coordinates[1]).abs());
+                    pawn_closeness = std::cmp::max(pawn_closeness, 7 - i32::from(distance));
+                }
+            }
+        }
+
+        let endgame = 6 - center_distance + pawn_closeness / 2;
+        let safety = center_distance;
+
+        let phase = phase_value(board);
+        let activity = endgame * phase + safety * {PHASE_MAX - phase};
+        activity / PHASE_MAX
+    }
+
+    // Returns the king activity of the team to move minus the enemies king activity
+    // Kings should be active in the endgame and kept safe before it
+    pub fn king_activity_score(game_state: GameState) -> i8 {
+        let board = game_state.board_info.board;
+        let white = game_state.whites_turn;
+
+        let score = {king_activity(white, board) - king_activity(!white, crate::flip_board(board))} / 2;
+        i8::try_from(score).unwrap()
+    }
+
+    // Returns the rook on the seventh bonus of a team in half points, board has to be from the perspective of the team
+    // Each rook on the row before the enemies back row gets a bonus, with more when the enemy king is stuck on its back row or there are enemy pawns on the row to attack
+    fn rook_seventh_bonus(white: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> i32 {
+        use crate::piece_white;
+
+        let seventh = BOARD_SIZE[1] - 2;
+        let back = BOARD_SIZE[1] - 1;
+
+        let enemy_king_back = (0..BOARD_SIZE[0]).any(|x| {
+            let id = board[x][back];
+            id.abs() == IDS[5] && piece_white(id) != white
+        });
+        let enemy_pawns = (0..BOARD_SIZE[0]).any(|x| {
+            let id = board[x][seventh];
+            id.abs() == IDS[0] && piece_white(id) != white
+        });
+
+        let mut bonus = 0;
+        for x in 0..BOARD_SIZE[0] {
+            let id = board[x][seventh];
+            if id.abs() != IDS[1] || piece_white(id) != white {
+                continue;
+            }
+
+            bonus += 1;
+            if enemy_king_back {
+                bonus += 1;
+            }
+            if enemy_pawns {
+                bonus += 1;
+            }
+        }
+        bonus
+    }
+
+    // Returns the rook on the seventh bonus of the team to move minus the enemies bonus, doubled rooks get a bonus each
+    pub fn rook_seventh_score(game_state: GameState) -> i8 {
+        let board = game_state.board_info.board;
+        let white = game_state.whites_turn;
+
+        let score = {rook_seventh_bonus(white, board) - rook_seventh_bonus(!white, crate::flip_board(board))} / 2;
+        i8::try_from(score).unwrap()
+    }
+
+    pub const OPPOSITE_BISHOPS_MAX_PAWNS: usize = 4; // Most pawns a team can have for an opposite coloured bishop ending to be scaled towards a draw
+    pub const OPPOSITE_BISHOPS_DIVISOR: i32 = 4; // Opposite coloured bishop endings keep 1 / OPPOSITE_BISHOPS_DIVISOR of the material advantage
+
+    // Returns the adjustment that scales the material advantage of a team towards a draw in an opposite coloured bishop ending, 0 for the team that is behind
+    // Only applies when the bishops are the only pieces besides kings and pawns, and neither team has many pawns
+    fn opposite_bishops_adjustment(white: bool, game_state: GameState) -> i32 {
+        let board = game_state.board_info.board;
+        let imbalance = imbalance(board);
+        if !imbalance.opposite_bishops || imbalance.knights != [0, 0] || imbalance.major_pieces != [0, 0] {
+            return 0;
+        }
+
+        for pawn_id in [IDS[0], -IDS[0]] {
+            if board.iter().flatten().filter(|id| **id == pawn_id).count() > OPPOSITE_BISHOPS_MAX_PAWNS {
+                return 0;
+            }
+        }
+
+        let advantage = game_state.board_info.material(white) - game_state.board_info.material(!white);
+        if advantage <= 0 {
+            return 0;
+        }
+        advantage / OPPOSITE_BISHOPS_DIVISOR - advantage
+    }
+
+    // Returns the opposite coloured bishop adjustment of the team to move minus the enemies adjustment, otherwise 0
+    // The team that is ahead has its material advantage scaled towards a draw
+    pub fn opposite_bishops_score(game_state: GameState) -> i8 {
+        let white = game_state.whites_turn;
+        i8::try_from(opposite_bishops_adjustment(white, game_state) - opposite_bishops_adjustment(!white, game_state)).unwrap()
+    }
+
+    // Opening development terms in half points
+    const DEVELOPED_MINOR_BONUS: i32 = 1; // Knight or bishop off its starting square
+    const CASTLED_BONUS: i32 = 2; // King on a castled square
+    const EARLY_QUEEN_PENALTY: i32 = 2; // Queen moved while at least two minor pieces are on their starting squares
+    const MOVED_TWICE_PENALTY: i32 = 1; // Minor piece or queen that has moved more than once
+
+    // Returns the development of a team in half points, boards have to be from whites perspective
+    fn development(white: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], turns_board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> i32 {
+        let mut team = 1;
+        let mut home_y = 0;
+        if !white {
+            team = -1;
+            home_y = BOARD_SIZE[1] - 1;
+        }
+
+        let minor_homes = [(1, IDS[2]), (6, IDS[2]), (2, IDS[3]), (5, IDS[3])];
+        let undeveloped = minor_homes.iter().filter(|(x, id)| board[*x][home_y] == id * team).count();
+
+        let mut development = DEVELOPED_MINOR_BONUS * {4 - i32::try_from(undeveloped).unwrap()};
+
+        if board[6][home_y] == IDS[5] * team || board[2][home_y] == IDS[5] * team {
+            development += CASTLED_BONUS;
+        }
+
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let id = board[x][y];
+                if id != IDS[2] * team && id != IDS[3] * team && id != IDS[4] * team {
+                    continue;
+                }
+
+                // from_fen counts every piece off the pawn rows as having moved once
+                if turns_board[x][y] > 2 {
+                    development -= MOVED_TWICE_PENALTY;
+                }
+                if id == IDS[4] * team && (x, y) != (3, home_y) && undeveloped >= 2 {
+                    development -= EARLY_QUEEN_PENALTY;
+                }
+            }
+        }
+        development
+    }
+
+    // Returns the development of white and black in half points, only counted in the opening
+    fn development_bonuses(game_state: GameState) -> [i32; 2] {
+        use crate::board::game_phase;
+        use crate::board::GamePhase;
+
+        let mut board = game_state.board_info.board;
+        let mut turns_board = game_state.board_info.turns_board;
+        if game_phase(board) != GamePhase::Opening {
+            return [0, 0];
+        }
+
+        if !game_state.whites_turn {
+            board = crate::flip_board(board);
+            turns_board = crate::flip_board(turns_board);
+        }
+        [development(true, board, turns_board), development(false, board, turns_board)]
+    }
+
+    // Returns the development of the team to move minus the enemies development, only counted in the opening
+    // Rewards developed minor pieces and castling, penalises early queen moves and moving the same piece twice
+    pub fn development_score(game_state: GameState) -> i8 {
+        let bonuses = development_bonuses(game_state);
+        let score = match game_state.whites_turn {
+            true => bonuses[0] - bonuses[1],
+            false => bonuses[1] - bonuses[0],
+        } / 2;
+        i8::try_from(score).unwrap()
+    }
+
+    // Returns the number of legal moves of the team to move and of the enemy, as if the team to move passed its turn
+    fn mobility(game_state: GameState) -> [i32; 2] {
+        use crate::piece::moves::legal_move_count;
+
+        let mut enemy_state = game_state;
+        enemy_state.board_info = crate::flip_board_info(game_state.board_info);
+        enemy_state.board_info.last_turn_coordinates = [0, 0];
+        enemy_state.board_info.en_passant = None;
+        enemy_state.whites_turn = !game_state.whites_turn;
+
+        [game_state, enemy_state].map(|state| i32::try_from(legal_move_count(state)).unwrap())
+    }
+
+    // Returns the number of legal moves of the team to move minus the number of legal moves of the enemy
+    pub fn mobility_score(game_state: GameState) -> i32 {
+        let mobility = mobility(game_state);
+        mobility[0] - mobility[1]
+    }
+
+    // Returns the material of the team to move minus the material of the enemy, from the running material in board_info
+    // Kings aren't counted, each team has one so they would cancel out
+    pub fn material(game_state: GameState) -> i32 {
+        let board_info = game_state.board_info;
+        board_info.material(game_state.whites_turn) - board_info.material(!game_state.whites_turn)
+    }
+
+    pub const PAWN: i32 = 100; // Evaluations are in centipawns
+
+    // Weight of each evaluation term, in centipawns for each point the term counts
+    // Development, king activity, and rooks on the seventh are counted in half points, so their weights are halved
+    // Mobility is counted in legal moves instead of points
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct EvalWeights {
+        pub material: i32,
+        pub hanging: i32,
+        pub passed_pawns: i32,
+        pub development: i32,
+        pub king_activity: i32,
+        pub rook_seventh: i32,
+        pub mop_up: i32,
+        pub opposite_bishops: i32,
+        pub mobility: i32, // Off by default, counting the legal moves of both teams in every evaluation slows the search down
+        pub tempo: i32, // Bonus for having the move, in centipawns, a fraction of a pawn
+    }
+
+    impl Default for EvalWeights {
+        fn default() -> Self {
+            EvalWeights::new()
+        }
+    }
+
+    impl EvalWeights {
+        pub fn new() -> Self {
+            EvalWeights {
+                material: PAWN,
+                hanging: PAWN,
+                passed_pawns: PAWN,
+                development: PAWN / 2,
+                king_activity: PAWN / 2,
+                rook_seventh: PAWN / 2,
+                mop_up: PAWN,
+                opposite_bishops: PAWN,
+                mobility: 0,
+                tempo: PAWN / 10,
+            }
+        }
+    }
+
+    // Contribution of each evaluation term in centipawns, arrays are indexed by team (0 for white, 1 for black)
+    // Terms are bonuses for the team they belong to, penalties are negative
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct EvalBreakdown {
+        pub material: [i32; 2],
+        pub hanging: [i32; 2],
+        pub passed_pawns: [i32; 2],
+        pub development: [i32; 2],
+        pub king_activity: [i32; 2],
+        pub rook_seventh: [i32; 2],
+        pub mop_up: [i32; 2],
+        pub opposite_bishops: [i32; 2],
+        pub mobility: [i32; 2],
+        pub tempo: [i32; 2], // Only the team to move gets the tempo
+    }
+
+    impl EvalBreakdown {
+        // Terms that make up the positional evaluation, every term but material and the tempo
+        pub fn positional_terms(&self) -> [[i32; 2]; 8] {
+            [self.hanging, self.passed_pawns, self.development, self.king_activity, self.rook_seventh, self.mop_up, self.opposite_bishops, self.mobility]
+        }
+
+        // Sum of the positional terms of white minus the terms of black
+        pub fn positional(&self) -> Score {
+            Score::from_white_perspective(self.positional_terms().iter().map(|term| term[0] - term[1]).sum())
+        }
+
+        // Sum of every term of white minus every term of black
+        pub fn total(&self) -> Score {
+            let others: i32 = [self.material, self.tempo].iter().map(|term| term[0] - term[1]).sum();
+            Score::from_white_perspective(self.positional().white_perspective() + others)
+        }
+    }
+
+    // Returns the contribution of each evaluation term for both teams, used to see why a position is scored the way it is
+    // evaluate and positional add up the same breakdown, so they always agree with it
+    pub fn explain(game_state: GameState, weights: &EvalWeights) -> EvalBreakdown {
+        use crate::board::phase_value;
+        use crate::board::PHASE_MAX;
+
+        let board = game_state.board_info.board;
+        let flipped_board = crate::flip_board(board);
+        let white = game_state.whites_turn;
+
+        // Puts terms of the team to move and the enemy in team order, multiplied by their weight
+        let by_team = |team: i32, enemy: i32, weight: i32| -> [i32; 2] {
+            match white {
+                true => [team * weight, enemy * weight],
+                false => [enemy * weight, team * weight],
+            }
+        };
+
+        let hanging = hanging_penalties(game_state);
+        let development = development_bonuses(game_state);
+        let phase = phase_value(board);
+        let passed_pawns = by_team(i32::from(passed_pawn_bonus(white, true, board)), i32::from(passed_pawn_bonus(!white, false, flipped_board)), weights.passed_pawns);
+
+        // Counting the legal moves of both teams is the slowest term, so it is skipped when it's weighted by 0
+        let mobility = match weights.mobility {
+            0 => [0, 0],
+            _ => mobility(game_state),
+        };
+
+        EvalBreakdown {
+            material: [game_state.board_info.material(true) * weights.material, game_state.board_info.material(false) * weights.material],
+            hanging: by_team(-i32::from(hanging[0]), -i32::from(hanging[1]), weights.hanging),
+            passed_pawns: passed_pawns.map(|bonus| bonus * phase / PHASE_MAX), // Passed pawns matter in endgames, so they are scaled by the game phase
+            development: [development[0] * weights.development, development[1] * weights.development],
+            king_activity: by_team(king_activity(white, board), king_activity(!white, flipped_board), weights.king_activity),
+            rook_seventh: by_team(rook_seventh_bonus(white, board), rook_seventh_bonus(!white, flipped_board), weights.rook_seventh),
+            mop_up: [i32::from(mop_up_bonus(true, board)) * weights.mop_up, i32::from(mop_up_bonus(false, board)) * weights.mop_up],
+            opposite_bishops: [opposite_bishops_adjustment(true, game_state) * weights.opposite_bishops, opposite_bishops_adjustment(false, game_state) * weights.opposite_bishops],
+            mobility: by_team(mobility[0], mobility[1], weights.mobility),
+            tempo: by_team(weights.tempo, 0, 1),
+        }
+    }
+
+    // Default positional evaluation used by the search, hanging pieces, passed pawns, development, king activity, rooks on the seventh, mating a bare king, drawish opposite coloured bishops, and mobility
+    pub fn positional(game_state: GameState, weights: &EvalWeights) -> Score {
+        explain(game_state, weights).positional()
+    }
+
+    // Returns the static evaluation of a position, the positional evaluation with material and the tempo added
+    pub fn evaluate(game_state: GameState, weights: &EvalWeights) -> Score {
+        explain(game_state, weights).total()
+    }
+
+    // Static exchange evaluation
+    // Returns the material won by the team to move if it makes chess_move and both teams keep recapturing on the move square with their least valuable piece
+    // Either team can stop recapturing when it would lose material
+    pub fn see(chess_move: Move, game_state: GameState) -> i8 {
+        match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, game_state.default_promotion(), game_state) {
+            Ok(game_state_new) => game_state_new.points_delta - see_square(crate::flip_coordinates(chess_move.move_coordinates), game_state_new),
+            Err(_) => 0,
+        }
+    }
+
+    // Returns the material the team to move can win by capturing on square with its least valuable piece first
+    fn see_square(square: [i8; 2], game_state: GameState) -> i8 {
+        use crate::piece::info::piece_weight;
+        use crate::piece::moves::legal_moves;
+
+        let pieces = game_state.board_info.pieces;
+
+        // Find the least valuable piece that can capture on square, kings are captured with last
+        let mut capture: Option<(i32, GameState)> = None;
+        for chess_move in legal_moves(game_state) {
+            if chess_move.move_coordinates != square {
+                continue;
+            }
+
+            let id = crate::get_board(chess_move.piece_coordinates, game_state.board_info.board);
+            let value = piece_weight(id, pieces);
+
+            if let Some((capture_value, _)) = capture {
+                if value >= capture_value {
+                    continue;
+                }
+            }
+
+            // Captures that end the game are not part of an exchange
+            if let Ok(game_state_new) = new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, game_state.default_promotion(), game_state) {
+                capture = Some((value, game_state_new));
+            }
+        }
+
+        match capture {
+            Some((_, game_state_new)) => {
+                let gain = game_state_new.points_delta - see_square(crate::flip_coordinates(square), game_state_new);
+                std::cmp::max(gain, 0)
+            },
+            None => 0,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::fen;
+
+        #[test]
+        fn hanging_score_test() { // Test a hanging knight scoring worse than a defended knight
+            let mut game_state = GameState::new();
+            game_state.board_info.board = fen::decode("4k3/8/8/3p4/4N3/8/8/4K3");
+            let hanging = hanging_score(game_state);
+
+            game_state.board_info.board = fen::decode("4k3/8/8/3p4/4N3/5P2/8/4K3");
+            let defended = hanging_score(game_state);
+
+            assert_eq!(hanging, -1);
+            assert_eq!(defended, 0);
+        }
+
+        #[test]
+        fn score_test() { // Test converting a score between perspectives negating it exactly once
+            let score = Score::from_white_perspective(3);
+            assert_eq!(score.relative_to(true), 3);
+            assert_eq!(score.relative_to(false), -3);
+            assert_eq!(Score::from_perspective(score.relative_to(false), false), score);
+            assert_eq!(Score::from_perspective(3, false).white_perspective(), -3);
+
+            // The same position with the other team to move differs from whites perspective only by the tempo, which goes to the team to move
+            let weights = EvalWeights::new();
+            let white = evaluate(GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap(), &weights);
+            let black = evaluate(GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap(), &weights);
+            assert_eq!(white.white_perspective() - weights.tempo, black.white_perspective() + weights.tempo);
+            assert!(black.white_perspective() > 0);
+        }
+
+        #[test]
+        fn development_score_test() { // Test developing and castling scoring better than pawn moves and early queen moves
+            use crate::uci::apply_moves;
+
+            let score = |moves: &[&str]| -> i8 {
+                let game_state = apply_moves(GameState::new(), moves).unwrap();
+                development_score(game_state)
+            };
+
+            // Scores are from whites perspective after an even number of moves
+            assert_eq!(score(&[]), 0);
+            assert!(score(&["g1f3", "a7a6", "b1c3", "h7h6"]) > score(&["a2a3", "a7a6", "h2h3", "h7h6"]));
+            assert!(score(&["g1f3", "a7a6", "e2e4", "h7h6", "f1c4", "b7b6", "e1g1", "b6b5"]) > score(&["g1f3", "a7a6", "e2e4", "h7h6", "f1c4", "b7b6", "h1g1", "b6b5"]));
+            assert!(score(&["e2e4", "a7a6", "d1h5", "b7b6"]) < score(&["e2e4", "a7a6", "h2h3", "b7b6"]));
+
+            // The engine develops from the start position
+            let mut search = crate::algorithm::minimax::Search::builder().depth(2).build();
+            let result = search.go(GameState::new()).unwrap();
+            let moved_id = crate::get_board(result.piece_coordinates, GameState::new().board_info.board);
+            assert!(moved_id == IDS[2] || moved_id == IDS[3]);
+        }
+
+        #[test]
+        fn imbalance_test() { // Test two bishops against a bishop and knight, two bishops on one colour, and a knight against a bishop
+            use crate::fen::decode;
+
+            let board = decode("2b1kn2/8/8/8/8/8/8/2B1KB1R");
+            let result = imbalance(board);
+            assert_eq!(result.bishop_pair, [true, false]);
+            assert_eq!(result.minor_pieces, [2, 2]);
+            assert_eq!(result.major_pieces, [1, 0]);
+            assert_eq!(result.knight_vs_bishop, None);
+            assert_eq!(imbalance(crate::flip_board(board)), result);
+
+            let result = imbalance(decode("4k3/8/8/8/8/8/8/R1B1KB1R"));
+            assert_eq!(result.bishop_pair, [true, false]);
+            assert_eq!(result.rook_pair, [true, false]);
+
+            // A promoted bishop on the same colour is not a bishop pair
+            assert!(!imbalance(decode("4k3/8/8/8/8/8/8/B1B1K3")).bishop_pair[0]);
+
+            assert_eq!(imbalance(decode("4kb2/8/8/8/8/8/8/4KN2")).knight_vs_bishop, Some(true));
+            assert_eq!(imbalance(decode("4kn2/8/8/8/8/8/8/4KB2")).knight_vs_bishop, Some(false));
+
+            assert!(imbalance(decode("4kb2/8/8/8/8/8/8/4KB2")).opposite_bishops);
+            assert!(!imbalance(decode("4k1b1/8/8/8/8/8/8/4KB2")).opposite_bishops);
+        }
+
+        #[test]
+        fn back_rank_weak_test() { // Test a castled king behind unmoved pawns, luft, a defending rook, and a king off the back row
+            use crate::fen::decode;
+
+            assert!(back_rank_weak(true, decode("6k1/5ppp/8/8/8/8/5PPP/6K1")));
+            assert!(back_rank_weak(false, decode("6k1/5ppp/8/8/8/8/5PPP/6K1")));
+            assert!(back_rank_weak(true, decode("6k1/8/8/8/8/8/6PP/7K")));
+
+            assert!(!back_rank_weak(true, decode("6k1/5ppp/8/8/8/7P/5PP1/6K1")));
+            assert!(!back_rank_weak(true, decode("6k1/5ppp/8/8/8/8/5PPP/R5K1")));
+            assert!(!back_rank_weak(true, decode("6k1/5ppp/8/8/8/6K1/5PPP/8")));
+            assert!(!back_rank_weak(true, decode("6k1/5ppp/8/8/8/8/5PpP/6K1")));
+        }
+
+        #[test]
+        fn passed_pawn_score_test() { // Test passed pawns only scoring in endgames, and a pawn outside the square of the king
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+            assert!(passed_pawn([1, 3], crate::fen::decode("8/8/8/8/1P6/8/8/8")));
+            assert!(!passed_pawn([1, 3], crate::fen::decode("8/2p5/8/8/1P6/8/8/8")));
+            assert_eq!(passed_pawn_score(game_state), 0);
+
+            // The black king catches the pawn if it's blacks move
+            let game_state = GameState::from_fen("8/8/8/8/1P4k1/8/8/K7 w - - 0 1").unwrap();
+            assert_eq!(passed_pawn_score(game_state), 1 + UNSTOPPABLE_PAWN_BONUS);
+            let game_state = GameState::from_fen("8/8/8/8/1P4k1/8/8/K7 b - - 0 1").unwrap();
+            assert_eq!(passed_pawn_score(game_state), -1);
+
+            // Passed pawns don't count with all the pieces on the board
+            let game_state = GameState::from_fen("rnbqkbnr/8/8/8/1P6/8/8/RNBQKBNR w - - 0 1").unwrap();
+            assert_eq!(passed_pawn_score(game_state), 0);
+        }
+
+        #[test]
+        fn best_move_passed_pawn_test() { // Test pushing a passed pawn out of reach of the enemy king instead of moving the king
+            let game_state = GameState::from_fen("8/8/8/8/1P4k1/8/8/K7 w - - 0 1").unwrap();
+
+            // Any king move lets Kf5 catch the pawn
+            let mut search = crate::algorithm::minimax::Search::builder().depth(2).build();
+            let result = search.go(game_state).unwrap();
+            assert_eq!(result.piece_coordinates, [1, 3]);
+            assert_eq!(result.move_coordinates, [1, 4]);
+        }
+
+        #[test]
+        fn king_activity_score_test() { // Test a central king scoring better in a pawn endgame and worse with all pieces on the board
+            let back_rank = GameState::from_fen("4k3/p7/8/8/8/8/P7/4K3 w - - 0 1").unwrap();
+            let central = GameState::from_fen("4k3/p7/8/8/3K4/8/P7/8 w - - 0 1").unwrap();
+            assert!(king_activity_score(central) > king_activity_score(back_rank));
+            assert_eq!(king_activity_score(crate::board::mirror(back_rank)), king_activity_score(back_rank));
+
+            let castled = GameState::from_fen("rnbq1rk1/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1RK1 w - - 0 1").unwrap();
+            let central = GameState::from_fen("rnbq1rk1/pppppppp/8/8/3K4/8/PPPPPPPP/RNBQ1R2 w - - 0 1").unwrap();
+            assert!(king_activity_score(central) < king_activity_score(castled));
+
+            // The engine brings its king towards the center instead of leaving it in the corner
+            let game_state = GameState::from_fen("k7/p7/8/8/8/8/P7/7K w - - 0 1").unwrap();
+            let result = crate::algorithm::minimax::Search::builder().depth(2).build().go(game_state).unwrap();
+            assert_eq!(result.piece_coordinates, [7, 0]);
+            assert_eq!(result.move_coordinates, [6, 1]);
+        }
+
+        #[test]
+        fn rook_seventh_score_test() { // Test rooks on the seventh scoring for either team, doubled rooks, and the engine invading the seventh
+            let seventh = GameState::from_fen("6k1/pp1R1pp1/7p/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+            assert_eq!(rook_seventh_score(seventh), 1);
+            assert_eq!(rook_seventh_score(crate::board::mirror(seventh)), 1);
+
+            // Black's seventh is white's second
+            let black_seventh = GameState::from_fen("6k1/5ppp/8/8/8/8/PP1r1PPP/6K1 w - - 0 1").unwrap();
+            assert_eq!(rook_seventh_score(black_seventh), -1);
+
+            let doubled = GameState::from_fen("6k1/pp1RRpp1/7p/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+            assert_eq!(rook_seventh_score(doubled), 3);
+
+            let game_state = GameState::from_fen("6k1/pp3pp1/7p/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+            let result = crate::algorithm::minimax::Search::builder().depth(2).build().go(game_state).unwrap();
+            assert_eq!(result.piece_coordinates, [3, 0]);
+            assert_eq!(result.move_coordinates, [3, 6]);
+        }
+
+        #[test]
+        fn opposite_bishops_score_test() { // Test a pawn up opposite coloured bishop ending scoring closer to a draw than the same ending with same coloured bishops
+            let opposite = GameState::from_fen("4k3/4bpp1/8/8/8/3B4/5PPP/4K3 w - - 0 1").unwrap();
+            let same = GameState::from_fen("4k3/3b1pp1/8/8/8/3B4/5PPP/4K3 w - - 0 1").unwrap();
+            assert_eq!(material(opposite), 1);
+            assert_eq!(opposite_bishops_score(opposite), -1);
+            assert_eq!(opposite_bishops_score(same), 0);
+            let weights = EvalWeights::new();
+            assert_eq!(evaluate(opposite, &weights).white_perspective(), weights.tempo);
+            assert_eq!(evaluate(same, &weights).white_perspective(), PAWN + weights.tempo);
+
+            // The team that is behind is scaled up instead
+            assert_eq!(opposite_bishops_score(GameState::from_fen("4k3/4bpp1/8/8/8/3B4/5PPP/4K3 b - - 0 1").unwrap()), 1);
+
+            // Other pieces and lots of pawns make the ending winnable
+            assert_eq!(opposite_bishops_score(GameState::from_fen("r3k3/4bpp1/8/8/8/3B4/5PPP/R5K1 w - - 0 1").unwrap()), 0);
+            assert_eq!(opposite_bishops_score(GameState::from_fen("4k3/4bppp/p7/8/8/P2B4/1P3PPP/6K1 w - - 0 1").unwrap()), 0);
+        }
+
+        #[test]
+        fn mobility_score_test() { // Test counting the legal moves of both teams, and the mobility term only counting them when it's weighted
+            use crate::piece::moves::legal_moves;
+
+            assert_eq!(mobility_score(GameState::new()), 0);
+
+            // After 1. e4 black has 20 moves, and white would have 30 if it could move again
+            let game_state = GameState::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+            assert_eq!(mobility(game_state), [20, 30]);
+            assert_eq!(mobility(game_state)[0], i32::try_from(legal_moves(game_state).len()).unwrap());
+            assert_eq!(mobility_score(game_state), -10);
+
+            let weights = EvalWeights::new();
+            assert_eq!(explain(game_state, &weights).mobility, [0, 0]);
+
+            let weights = EvalWeights {
+                mobility: 2,
+                ..weights
+            };
+            let breakdown = explain(game_state, &weights);
+            assert_eq!(breakdown.mobility, [60, 40]);
+            assert_eq!(breakdown.positional(), positional(game_state, &weights));
+            assert_eq!(positional(game_state, &weights).relative_to(false), positional(game_state, &EvalWeights::new()).relative_to(false) - 20);
+        }
+
+        #[test]
+        fn tempo_test() { // Test the start position scoring the tempo for whichever team is to move
+            let white = GameState::new();
+            let black = GameState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+            let weights = EvalWeights::new();
+            assert_eq!(evaluate(white, &weights).relative_to(true), weights.tempo);
+            assert_eq!(evaluate(black, &weights).relative_to(false), weights.tempo);
+            assert_eq!(evaluate(white, &weights).white_perspective(), weights.tempo);
+            assert_eq!(evaluate(black, &weights).white_perspective(), -weights.tempo);
+
+            // The tempo is tunable
+            let weights = EvalWeights {
+                tempo: 0,
+                ..EvalWeights::new()
+            };
+            assert_eq!(evaluate(white, &weights).white_perspective(), 0);
+        }
+
+        #[test]
+        fn explain_test() { // Test the breakdown adding up to evaluate and positional, and agreeing with the term scores, in an opening, a middlegame, and endgames using each term
+            let fens = [
+                crate::fen::START_FEN,
+                "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+                "6k1/pp1R1pp1/7p/8/8/8/5PPP/6K1 b - - 0 1",
+                "4k3/4bpp1/8/8/8/3B4/5PPP/4K3 w - - 0 1",
+                "8/8/8/3k4/8/8/8/4KQ2 w - - 0 1",
+            ];
+            let weights = EvalWeights::new();
+            for fen in fens {
+                let game_state = GameState::from_fen(fen).unwrap();
+                let breakdown = explain(game_state, &weights);
+                assert_eq!(breakdown.total(), evaluate(game_state, &weights));
+                assert_eq!(breakdown.positional(), positional(game_state, &weights));
+
+                // Terms from the perspective of the team to move, in points
+                let white = game_state.whites_turn;
+                let score = |term: [i32; 2]| -> i32 {
+                    Score::from_white_perspective(term[0] - term[1]).relative_to(white) / PAWN
+                };
+                assert_eq!(score(breakdown.material), material(game_state));
+                assert_eq!(score(breakdown.hanging), i32::from(hanging_score(game_state)));
+                assert_eq!(score(breakdown.development), i32::from(development_score(game_state)));
+                assert_eq!(score(breakdown.king_activity), i32::from(king_activity_score(game_state)));
+                assert_eq!(score(breakdown.rook_seventh), i32::from(rook_seventh_score(game_state)));
+                assert_eq!(score(breakdown.mop_up), i32::from(mop_up_score(game_state)));
+                assert_eq!(score(breakdown.opposite_bishops), i32::from(opposite_bishops_score(game_state)));
+                assert_eq!(score(breakdown.tempo), weights.tempo / PAWN);
+            }
+
+            // Each team gets its own contributions, white is a pawn up, so its advantage is scaled towards a draw
+            let breakdown = explain(GameState::from_fen("4k3/4bpp1/8/8/8/3B4/5PPP/4K3 w - - 0 1").unwrap(), &weights);
+            assert_eq!(breakdown.material, [6 * PAWN, 5 * PAWN]);
+            assert_eq!(breakdown.opposite_bishops, [-PAWN, 0]);
+            assert_eq!(breakdown.tempo, [weights.tempo, 0]);
+
+            // Black has a rook on the seventh against a king on its back row, worth two half points with the default weights
+            let breakdown = explain(GameState::from_fen("6k1/8/8/8/8/8/r7/6K1 w - - 0 1").unwrap(), &weights);
+            assert_eq!(breakdown.rook_seventh, [0, PAWN]);
+            assert_eq!(breakdown.tempo, [weights.tempo, 0]);
+
+            // A weight of 0 leaves a term out
+            let no_material = EvalWeights {
+                material: 0,
+                ..weights
+            };
+            assert_eq!(explain(GameState::new(), &no_material).material, [0, 0]);
+        }
+
+        #[test]
+        fn material_test() { // Test material being symmetric between teams, and following the running material through a capture
+            assert_eq!(material(GameState::new()), 0);
+
+            let white = GameState::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+            let black = GameState::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+            assert_eq!(material(white), 9);
+            assert_eq!(material(black), -9);
+
+            // Qxd8 leaves black to move a queen and a rook down
+            let game_state = GameState::from_fen("3rk3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+            assert_eq!(material(game_state), 4);
+            let game_state = crate::board::turn::new_turn([3, 0], [3, 7], 0, game_state).unwrap();
+            assert_eq!(material(game_state), -9);
+        }
+
+        #[test]
+        fn see_test() { // Test exchanges on a defended and an undefended pawn
+            let knight_takes_pawn = Move {
+                piece_coordinates: [2, 2],
+                move_coordinates: [3, 4],
+            };
+
+            let game_state = GameState::from_fen("4k3/8/8/3p4/8/2N5/8/4K3 w - - 0 1").unwrap();
+            assert_eq!(see(knight_takes_pawn, game_state), 1);
+
+            // The pawn on c6 recaptures the knight
+            let game_state = GameState::from_fen("4k3/8/2p5/3p4/8/2N5/8/4K3 w - - 0 1").unwrap();
+            assert_eq!(see(knight_takes_pawn, game_state), -2);
+        }
+    }
+}
+
 pub mod minimax {
     use super::*;
     use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use crate::board::turn::new_turn;
+    use crate::board::turn::GameResult;
+    use crate::board::turn::GameState;
+    use crate::board::BOARD_SIZE;
+    use crate::piece::moves::Move;
+    use crate::piece::moves::MoveInfo;
+    use super::eval::EvalWeights;
+    use super::eval::Score;
+    use super::eval::PAWN;
+
+    // Maximum number of checks the team to move will give when looking for a perpetual check
+    pub const PERPETUAL_CHECK_MOVES: usize = 3;
+
+    // Largest change from the current material a search result can have and still be treated as quiet by the fortress heuristic
+    pub const FORTRESS_MARGIN: i32 = PAWN;
+
+    // Deepest search best_move will do, deeper depths given to the search builder (or iter_deepening) are clamped to this so the stack can't overflow
+    // Depth indexed tables used by the search can be sized with it
+    pub const MAX_DEPTH: usize = 32;
+
+    // Value of a checkmate made by a root move, each ply (move of either team) deeper a checkmate is found it is worth one less
+    // so the search prefers the shortest checkmate, and the longest defence against one
+    pub const MATE: i32 = crate::piece::info::CHECKMATE_VALUE as i32 * PAWN;
+
+    // Returns how many plies after the root move a search value checkmates, None if the value isn't a checkmate
+    // Positive values are checkmates by the master team, negative values checkmates of the master team
+    pub fn mate_plies(value: i32) -> Option<usize> {
+        let plies = usize::try_from(MATE - value.abs()).ok()?;
+        match plies <= MAX_DEPTH {
+            true => Some(plies),
+            false => None,
+        }
+    }
+
+    // Search depth Search::warm_start searches each position of a previous game to, to find its move hints
+    pub const WARM_START_DEPTH: usize = 2;
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct BranchValue {
+        pub piece_coordinates: [i8; 2],
+        pub move_coordinates: [i8; 2],
+        pub value: i32, // Search values are in centipawns from the perspective of the master team, move ordering values are in points
+        pub heatmap_value: i8,
+        pub terminal: Option<GameResult>, // How the game ends at the end of the searched line, None if it doesn't end within the search
+    }
+
+    // Struct assigned to board keys in the transposition table
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct TranspositionInfo {
+        max: BranchValue,
+        min: BranchValue,
+        search_depth: usize,
+        current_depth: usize,
+        null_window_bound: Option<i32>, // Bound of the null window search that stored the entry, None for a full search
+    }
+
+    // What a MoveOrderer knows about the position it is ordering moves for
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct OrderContext {
+        pub game_state: GameState,
+        pub countermove: Option<Move>, // Countermove to the enemies previous move, if one has been stored
+        pub current_depth: usize,
+    }
+
+    // A move ordering strategy for best_move, moves that are likely to be best should be ordered first so more of the search is pruned
+    // Reordering only changes how many nodes are searched, not the best move
+    // An orderer which drops or adds moves changes which moves are searched, so it can change the best move (dropped moves are never searched, added moves are rejected by new_turn when illegal)
+    pub trait MoveOrderer: std::fmt::Debug {
+        fn order(&self, moves: &mut Vec<MoveInfo>, context: &OrderContext);
+    }
+
+    // The built in ordering, captures by static exchange evaluation, then the countermove, then quiet moves, then losing captures
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct DefaultOrderer;
+
+    impl MoveOrderer for DefaultOrderer {
+        fn order(&self, moves: &mut Vec<MoveInfo>, context: &OrderContext) {
+            let ordered: Vec<Move> = order_moves(context.countermove, context.game_state).iter()
+                .flatten()
+                .map(|branch_value| Move {
+                    piece_coordinates: branch_value.piece_coordinates,
+                    move_coordinates: branch_value.move_coordinates,
+                })
+                .collect();
+            moves.sort_by_key(|move_info| crate::unwrap_def(ordered.iter().position(|chess_move| *chess_move == move_info.chess_move), ordered.len()));
+        }
+    }
+
+    // Orders like DefaultOrderer, but moves which stalemate the enemy go first when the team to move is down material and last when it is up material
+    // Stalemating is a drawing resource when losing and throws away the win when winning
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct StalemateOrderer;
+
+    impl MoveOrderer for StalemateOrderer {
+        fn order(&self, moves: &mut Vec<MoveInfo>, context: &OrderContext) {
+            DefaultOrderer.order(moves, context);
+
+            // Antichess has no stalemate
+            let game_state = context.game_state;
+            let mut material = game_state.material_delta();
+            if !game_state.whites_turn {
+                material *= -1;
+            }
+            if material == 0 || game_state.variant == crate::board::Variant::Antichess {
+                return;
+            }
+
+            // The sort is stable, so the default order is kept among the stalemating and the other moves
+            let winning = material > 0;
+            moves.sort_by_cached_key(|move_info| crate::piece::moves::stalemates(move_info.chess_move, game_state) == winning);
+        }
+    }
+
+    // Options which change how best_move values positions
+    #[derive(Debug, Clone)]
+    pub struct SearchOptions {
+        pub contempt: i8, // How much the master team dislikes draws, in points
+        pub eval: fn(GameState, &EvalWeights) -> Score, // Positional evaluation used at the end of a search. Cached by the position and turns board, so it shouldn't read the game history or add the tempo
+        pub weights: EvalWeights, // Weights passed to eval, the search adds weights.tempo for the team to move at the end of a search
+        pub node_limit: Option<u64>, // Stop searching once this many nodes have been searched, the best move from the last completed root move is returned
+        pub claim_repetition: bool, // The enemy is assumed to force a draw with a perpetual check in its replies to the root moves, instead of only when the repetition is inside the search
+        pub pvs: bool, // Principal variation search, moves after the first are searched with a null window and only searched fully if they could be better
+        pub fortress: bool, // A quiet search result in a locked position the master team is down material in is moved towards a draw
+        pub tie_break: bool, // Root moves with equal values are picked by tie_break_value (captures, then checks, then development) instead of the heatmap
+        pub orderer: Option<Arc<dyn MoveOrderer>>, // Orders the moves of each position, the built in ordering (see DefaultOrderer) is used when None
+    }
+
+    impl Default for SearchOptions {
+        fn default() -> Self {
+            SearchOptions::new()
+        }
+    }
+
+    impl SearchOptions {
+        pub fn new() -> Self {
+            SearchOptions {
+                contempt: 0,
+                eval: super::eval::positional,
+                weights: EvalWeights::new(),
+                node_limit: None,
+                claim_repetition: false,
+                pvs: false,
+                fortress: false,
+                tie_break: false,
+                orderer: None,
+            }
+        }
+
+        // Returns true if the search has used up its node limit
+        fn node_limit_reached(&self, stats: &SearchStats) -> bool {
+            match self.node_limit {
+                Some(limit) => stats.nodes >= limit,
+                None => false,
+            }
+        }
+    }
+
+    // Counters collected while searching
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct SearchStats {
+        pub nodes: u64, // Number of positions best_move was called on
+        pub eval_cache_hits: u64, // Number of positional evaluations found in the evaluation cache
+        pub transposition_hits: u64, // Number of search results found in the transposition table
+    }
+
+    impl Default for SearchStats {
+        fn default() -> Self {
+            SearchStats::new()
+        }
+    }
+
+    impl SearchStats {
+        pub fn new() -> Self {
+            SearchStats {
+                nodes: 0,
+                eval_cache_hits: 0,
+                transposition_hits: 0,
+            }
+        }
+    }
+
+    // Result of one depth of Search::iter_deepening
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Evaluation {
+        pub best_move: BranchValue, // Best move and its value, the same as Search::go at this depth
+        pub pv: Vec<Move>, // Principal variation, the best move followed by the expected replies read from the transposition table. Every move is from the perspective of the team to move at the root
+        pub depth: usize,
+    }
 
-    use crate::board::turn::new_turn;
-    use crate::board::turn::GameState;
-    use crate::board::BOARD_SIZE;
+    // Owns everything needed to search for the best move, so callers don't have to pass the recursion parameters of best_move
+    pub struct Search {
+        pub depth: usize,
+        pub options: SearchOptions,
+        pub bitstrings_board: [[HashMap<i8, u64>; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+        pub transposition_table: HashMap<u64, TranspositionInfo>,
+        pub countermove_table: HashMap<Move, Move>,
+        pub eval_cache: HashMap<u64, Score>, // Positional evaluations of positions reached by the last search, cleared when a search starts so it can't grow between calls
+        pub move_hints: HashMap<u64, Move>, // Best moves found by warm_start, searched first when their position is reached. Moves don't depend on the position a search started from, so unlike the transposition table they are kept between calls
+        pub stats: SearchStats, // Stats of the last search
+    }
 
-    #[derive(Debug, Copy, Clone, PartialEq)]
-    pub struct BranchValue {
-        pub piece_coordinates: [i8; 2],
-        pub move_coordinates: [i8; 2],
-        pub value: i8,
-        pub heatmap_value: i8,
+    // Builds a Search, only exposing the settings users should change
+    pub struct SearchBuilder {
+        depth: usize,
+        options: SearchOptions,
     }
 
-    // Struct assigned to board keys in the transposition table
-    #[derive(Debug, Copy, Clone, PartialEq)]
-    pub struct TranspositionInfo {
-        max: BranchValue,
-        min: BranchValue,
-        search_depth: usize,
-        current_depth: usize,
+    impl Search {
+        pub fn builder() -> SearchBuilder {
+            SearchBuilder {
+                depth: 4,
+                options: SearchOptions::new(),
+            }
+        }
+
+        // Returns the best move for the team whose turn it is in game_state
+        // The transposition table is cleared first, its values are relative to the position the search that stored them started from
+        // Returns None for an illegal position (the team that just moved left its king in check)
+        pub fn go(&mut self, game_state: GameState) -> Option<BranchValue> {
+            use crate::piece::moves::position_is_legal;
+
+            if !position_is_legal(game_state) {
+                return None;
+            }
+            self.transposition_table.clear();
+            reset_countermoves(&mut self.countermove_table);
+            self.stats = SearchStats::new();
+            Some(self.search_root(self.depth, None, game_state))
+        }
+
+        // Same as go, but only the moves in restrict_to are searched from game_state (like uci go searchmoves)
+        // The value is the honest value of the best restricted move, even if an unrestricted move is better
+        // Returns None when none of the moves in restrict_to are legal, or the position is illegal
+        pub fn go_restricted(&mut self, game_state: GameState, restrict_to: &[Move]) -> Option<BranchValue> {
+            use crate::piece::moves::is_legal;
+            use crate::piece::moves::position_is_legal;
+
+            if !position_is_legal(game_state) || !restrict_to.iter().any(|chess_move| is_legal(*chess_move, game_state)) {
+                return None;
+            }
+            self.transposition_table.clear();
+            reset_countermoves(&mut self.countermove_table);
+            self.stats = SearchStats::new();
+            Some(self.search_root(self.depth, Some(restrict_to), game_state))
+        }
+
+        // Searches game_state to search_depth with the tables of this search, the stats of the search are added to self.stats
+        fn search_root(&mut self, search_depth: usize, restrict_to: Option<&[Move]>, game_state: GameState) -> BranchValue {
+            self.eval_cache.clear();
+            let mut context = SearchContext {
+                restrict_to,
+                null_window: false,
+                deepening_move: None,
+                bitstrings_board: &self.bitstrings_board,
+                transposition_table: &mut self.transposition_table,
+                countermove_table: &mut self.countermove_table,
+                eval_cache: &mut self.eval_cache,
+                move_hints: &self.move_hints,
+                stats: &mut self.stats,
+                options: &self.options,
+            };
+
+            // Iterative deepening, each depth searches the best move from the last depth first
+            // The depths are searched in a loop instead of by best_move recursing into the depth below, so the stack only holds one search
+            let mut result = best_move(&mut context, 0, std::cmp::min(search_depth, 1), 0, None, None, game_state);
+            for depth in 2..=search_depth {
+
+                // The node limit ran out in a lower level search, so no deeper search is started
+                if context.options.node_limit_reached(context.stats) {
+                    break;
+                }
+                context.deepening_move = Some(result);
+                result = best_move(&mut context, 0, depth, 0, None, None, game_state);
+            }
+            result
+        }
+
+        // Searches for the best move and explains why it was picked, None for an illegal position like go
+        pub fn go_explained(&mut self, game_state: GameState) -> Option<(BranchValue, String)> {
+            let best_move = self.go(game_state)?;
+            let chess_move = Move {
+                piece_coordinates: best_move.piece_coordinates,
+                move_coordinates: best_move.move_coordinates,
+            };
+            Some((best_move, explain_move(chess_move, game_state)))
+        }
+
+        // Returns an iterator which searches game_state one depth deeper each time it is advanced, from a depth of 1 up to max_depth (clamped to MAX_DEPTH)
+        // Each depth reuses the transposition table of the depths before it, which is cleared before the first depth, the stats are of the last depth searched
+        // Nothing is yielded for an illegal position
+        pub fn iter_deepening(&mut self, game_state: GameState, max_depth: usize) -> IterDeepening<'_> {
+            self.transposition_table.clear();
+            IterDeepening {
+                search: self,
+                game_state,
+                depth: 0,
+                max_depth: std::cmp::min(max_depth, MAX_DEPTH),
+            }
+        }
+
+        // Follows the best moves stored in the transposition table from game_state, starting with first_move
+        // Stops at depth moves, or when a stored move isn't legal (E.g. an entry was replaced by another position)
+        // The board flips every turn, so replies are flipped back to the perspective of the team to move in game_state
+        fn principal_variation(&self, first_move: Move, mut game_state: GameState, depth: usize) -> Vec<Move> {
+            use crate::flip_coordinates;
+            use crate::piece::moves::is_legal;
+
+            let mut pv = Vec::new();
+            let mut chess_move = first_move;
+            for ply in 0..depth {
+                if !is_legal(chess_move, game_state) {
+                    break;
+                }
+                match ply % 2 {
+                    0 => pv.push(chess_move),
+                    _ => pv.push(Move {
+                        piece_coordinates: flip_coordinates(chess_move.piece_coordinates),
+                        move_coordinates: flip_coordinates(chess_move.move_coordinates),
+                    }),
+                }
+
+                game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, game_state.default_promotion(), game_state) {
+                    Ok(game_state_new) => game_state_new,
+                    Err(_) => break,
+                };
+
+                // The team that searched the root takes the max value, its enemy the min value
+                let board_hash = crate::gen_zobrist_board_hash(game_state.whites_turn, game_state.board_info, &self.bitstrings_board);
+                let branch_value = match self.transposition_table.get(&board_hash) {
+                    Some(transposition_info) if transposition_info.null_window_bound.is_some() => break, // Null window entries only bound the value
+                    Some(transposition_info) if ply % 2 == 0 => transposition_info.min,
+                    Some(transposition_info) => transposition_info.max,
+                    None => break,
+                };
+                chess_move = Move {
+                    piece_coordinates: branch_value.piece_coordinates,
+                    move_coordinates: branch_value.move_coordinates,
+                };
+            }
+            pv
+        }
+
+        // Adds move hints for positions from a previous game, so searching them again starts with good move ordering
+        // Each legal position is searched to WARM_START_DEPTH, and the best move of every position the search stored is kept as a hint
+        // Only moves are kept, the values in the transposition table are relative to each position of the game, the stats of the last search are kept
+        pub fn warm_start(&mut self, history: &[GameState]) {
+            let stats = std::mem::take(&mut self.stats);
+            for game_state in history.iter().filter(|game_state| crate::piece::moves::position_is_legal(**game_state)) {
+                self.transposition_table.clear();
+                reset_countermoves(&mut self.countermove_table);
+                self.search_root(WARM_START_DEPTH, None, *game_state);
+
+                // The team to move at even depths took the max value
+                for (board_hash, transposition_info) in &self.transposition_table {
+                    let best = match transposition_info.current_depth % 2 {
+                        0 => transposition_info.max,
+                        _ => transposition_info.min,
+                    };
+                    if best.piece_coordinates != best.move_coordinates {
+                        self.move_hints.insert(*board_hash, Move {
+                            piece_coordinates: best.piece_coordinates,
+                            move_coordinates: best.move_coordinates,
+                        });
+                    }
+                }
+            }
+            self.transposition_table.clear();
+            self.stats = stats;
+        }
+    }
+
+    impl SearchBuilder {
+        pub fn depth(mut self, depth: usize) -> Self {
+            self.depth = std::cmp::min(depth, MAX_DEPTH);
+            self
+        }
+
+        pub fn contempt(mut self, contempt: i8) -> Self {
+            self.options.contempt = contempt;
+            self
+        }
+
+        pub fn eval(mut self, eval: fn(GameState, &EvalWeights) -> Score) -> Self {
+            self.options.eval = eval;
+            self
+        }
+
+        pub fn weights(mut self, weights: EvalWeights) -> Self {
+            self.options.weights = weights;
+            self
+        }
+
+        pub fn node_limit(mut self, node_limit: u64) -> Self {
+            self.options.node_limit = Some(node_limit);
+            self
+        }
+
+        pub fn claim_repetition(mut self, claim_repetition: bool) -> Self {
+            self.options.claim_repetition = claim_repetition;
+            self
+        }
+
+        pub fn pvs(mut self, pvs: bool) -> Self {
+            self.options.pvs = pvs;
+            self
+        }
+
+        pub fn fortress(mut self, fortress: bool) -> Self {
+            self.options.fortress = fortress;
+            self
+        }
+
+        pub fn tie_break(mut self, tie_break: bool) -> Self {
+            self.options.tie_break = tie_break;
+            self
+        }
+
+        pub fn orderer<O: MoveOrderer + 'static>(mut self, orderer: O) -> Self {
+            self.options.orderer = Some(Arc::new(orderer));
+            self
+        }
+
+        pub fn build(self) -> Search {
+            Search {
+                depth: self.depth,
+                options: self.options,
+                bitstrings_board: crate::gen_bistrings_board(),
+                transposition_table: HashMap::new(),
+                countermove_table: HashMap::new(),
+                eval_cache: HashMap::new(),
+                move_hints: HashMap::new(),
+                stats: SearchStats::new(),
+            }
+        }
+    }
+
+    // Iterator returned by Search::iter_deepening, yields an Evaluation after each completed depth
+    pub struct IterDeepening<'a> {
+        search: &'a mut Search,
+        game_state: GameState,
+        depth: usize, // Depth of the last completed search
+        max_depth: usize,
+    }
+
+    impl Iterator for IterDeepening<'_> {
+        type Item = Evaluation;
+
+        fn next(&mut self) -> Option<Evaluation> {
+            if self.depth >= self.max_depth || !crate::piece::moves::position_is_legal(self.game_state) {
+                return None;
+            }
+            self.depth += 1;
+
+            let search = &mut *self.search;
+            reset_countermoves(&mut search.countermove_table);
+            search.stats = SearchStats::new();
+            let best_move = search.search_root(self.depth, None, self.game_state);
+
+            let first_move = Move {
+                piece_coordinates: best_move.piece_coordinates,
+                move_coordinates: best_move.move_coordinates,
+            };
+            Some(Evaluation {
+                best_move,
+                pv: search.principal_variation(first_move, self.game_state, self.depth),
+                depth: self.depth,
+            })
+        }
+    }
+
+    // Plays the engine against itself until the game ends, or max_turns turns have been played
+    // Returns the final position and how the game ended (None if max_turns was reached first)
+    pub fn self_play(mut game_state: GameState, search_depth: usize, max_turns: usize) -> (GameState, Option<GameResult>) {
+        use crate::board::errors;
+
+        let mut search = Search::builder().depth(search_depth).build();
+
+        for _ in 0..max_turns {
+            if let Some(result) = game_state.status() {
+                return (game_state, Some(result));
+            }
+            let best_move = match search.go(game_state) {
+                Some(best_move) => best_move,
+                None => return (game_state, None),
+            };
+
+            game_state = match new_turn(best_move.piece_coordinates, best_move.move_coordinates, game_state.default_promotion(), game_state) {
+                Ok(game_state_new) => game_state_new,
+                Err(error) => {
+                    let result = match error.error_code {
+                        errors::CHECKMATE_ERROR => GameResult::Checkmate(game_state.whites_turn),
+                        errors::STALEMATE_ERROR => GameResult::Stalemate,
+                        _ => return (game_state, None),
+                    };
+                    return (game_state, Some(result));
+                },
+            };
+        }
+        (game_state, game_state.status())
+    }
+
+    // Clears countermoves recorded by a previous search
+    // Should be called before searching a new position
+    pub fn reset_countermoves(countermove_table: &mut HashMap<Move, Move>) {
+        countermove_table.clear();
     }
 
     impl BranchValue {
@@ -123,18 +1642,32 @@ pub mod minimax {
                 move_coordinates: [0, 0],
                 value: 0,
                 heatmap_value: 0,
+                terminal: None,
             }
         }
     }
 
-    pub fn best_move(
-        master_team: bool,
-        init_val: i8,
+    // State shared by every node of a search from the root
+    struct SearchContext<'a> {
+        restrict_to: Option<&'a [Move]>, // Only these root moves are searched (like uci searchmoves), the search below the root is not restricted
+        null_window: bool, // Set while a null window search runs, only whether values pass parent_value is wanted, values are bounds so they aren't stored in the transposition table
+        deepening_move: Option<BranchValue>, // Best move from the last depth of the deepening loop in search_root, searched first at the root
+        bitstrings_board: &'a [[HashMap<i8, u64>; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+        transposition_table: &'a mut HashMap<u64, TranspositionInfo>,
+        countermove_table: &'a mut HashMap<Move, Move>, // Stores the move which last caused a cutoff in response to an enemy move
+        eval_cache: &'a mut HashMap<u64, Score>, // Stores the positional evaluation of positions by eval_key
+        move_hints: &'a HashMap<u64, Move>,
+        stats: &'a mut SearchStats,
+        options: &'a SearchOptions,
+    }
+
+    fn best_move(
+        context: &mut SearchContext,
+        init_val: i32,
         search_depth: usize,
         current_depth: usize,
-        parent_value: Option<i8>,
-        bitstrings_board: &[[HashMap<i8, u64>; BOARD_SIZE[0]]; BOARD_SIZE[1]],
-        transposition_table: &mut HashMap<u64, TranspositionInfo>,
+        parent_value: Option<i32>,
+        previous_move: Option<Move>, // Move the enemy team made to reach game_state
         game_state: GameState)
         -> BranchValue {
         use crate::coordinates_from_usize;
@@ -142,34 +1675,109 @@ pub mod minimax {
         use crate::board::errors;
         use crate::gen_zobrist_board_hash;
 
+        // The team to move at the root is the master team, so the teams alternate with each depth
+        let master_team = current_depth.is_multiple_of(2);
+        let restrict_to = match current_depth {
+            0 => context.restrict_to,
+            _ => None,
+        };
+        let null_window = context.null_window;
+        let options = context.options;
+
+        context.stats.nodes += 1;
+
+        // Values from new_turn and the evaluation are relative to the team to move, search values are relative to the master team
+        let master_white = master_team == game_state.whites_turn;
+        // Material values are in points, search values in centipawns
+        let master_value = |value: i8| -> i32 {
+            Score::from_perspective(i32::from(value) * PAWN, game_state.whites_turn).relative_to(master_white)
+        };
+        let contempt = i32::from(options.contempt) * PAWN;
+
+        // A position repeated inside the search (or from the game history) is a draw
+        // The draw value cancels out the master teams material balance
+        if current_depth > 0 && game_state.history.count(game_state.position_hash()) > 0 {
+            return BranchValue {
+                piece_coordinates: [0, 0],
+                move_coordinates: [0, 0],
+                value: init_val - material_balance(master_white, game_state) - contempt,
+                heatmap_value: 0,
+                terminal: Some(GameResult::Repetition),
+            };
+        }
+
         // Stop searching moves once the last branch is reached
         if current_depth == search_depth {
+
+            // Add positional evaluation from the perspective of the master team
+            // Positions reached by transposition are only evaluated once
+            let board_hash = eval_key(gen_zobrist_board_hash(game_state.whites_turn, game_state.board_info, context.bitstrings_board), game_state);
+            let eval_val = match context.eval_cache.get(&board_hash) {
+                Some(eval_val) => {
+                    context.stats.eval_cache_hits += 1;
+                    *eval_val
+                },
+                None => {
+                    let eval_val = (options.eval)(game_state, &options.weights);
+                    context.eval_cache.insert(board_hash, eval_val);
+                    eval_val
+                },
+            };
+
+            // The team to move at the end of the search gets the tempo
+            let tempo = match master_team {
+                true => options.weights.tempo,
+                false => -options.weights.tempo,
+            };
+            let eval_val = eval_val.relative_to(master_white) + tempo;
+
             return BranchValue {
                 piece_coordinates: [0, 0],
                 move_coordinates: [0, 0],
-                value: init_val,
+                value: add_values(init_val, eval_val),
                 heatmap_value: 0,
+                terminal: None,
             };
         }
 
-        // Generate moves
-        let mut moves = order_moves(game_state);
+        // Get the countermove to the enemies previous move
+        let countermove = match previous_move {
+            Some(previous_move) => context.countermove_table.get(&previous_move).copied(),
+            None => None,
+        };
 
-        let board_hash = gen_zobrist_board_hash(game_state.whites_turn, game_state.board_info, &bitstrings_board);
-        let transposition_value = transposition_table.get(&board_hash).copied();
+        // Generate moves
+        let mut moves = ordered_moves(options.orderer.as_deref(), countermove, current_depth, game_state);
+        if let Some(restrict_to) = restrict_to {
+            restrict_moves(&mut moves, restrict_to);
+        }
 
-        match transposition_value {
-            Some(transposition_info) => {
+        let board_hash = gen_zobrist_board_hash(game_state.whites_turn, game_state.board_info, context.bitstrings_board);
+        if let Some(hint) = context.move_hints.get(&board_hash) {
+            hint_first(&mut moves, *hint);
+        }
 
-                // If this position has allready been searched at the current depth return its results
-                if transposition_info.search_depth >= search_depth && transposition_info.current_depth >= current_depth {
-                    if master_team {
-                        return transposition_info.max;
-                    }
-                    return transposition_info.min
+        if let Some(transposition_info) = context.transposition_table.get(&board_hash).copied() {
+            // If this position has allready been searched at the current depth return its results
+            // The stored best move may not be one of the restricted moves, and a null window result is only valid for the same bound
+            let bound_valid = transposition_info.null_window_bound.is_none_or(|bound| null_window && parent_value == Some(bound));
+            if restrict_to.is_none() && bound_valid && transposition_info.search_depth >= search_depth && transposition_info.current_depth >= current_depth {
+                context.stats.transposition_hits += 1;
+                if master_team {
+                    return transposition_info.max;
                 }
-            },
-            None => (),
+                return transposition_info.min
+            }
+
+            // Otherwise the best move the stored search found is searched first
+            let best = match master_team {
+                true => transposition_info.max,
+                false => transposition_info.min,
+            };
+            hint_first(&mut moves, Move {
+                piece_coordinates: best.piece_coordinates,
+                move_coordinates: best.move_coordinates,
+            });
         }
 
         let mut max = BranchValue::new();
@@ -177,14 +1785,16 @@ pub mod minimax {
 
         let mut init_min_max = true;
 
-        let mut min_max_val: Option<i8> = None;
+        let mut min_max_val: Option<i32> = None;
 
         // Use best move from lower level search as the first move to search at this depth
         let mut deepening_val = max;
         let mut use_deepening_val = false;
-        if current_depth == 0 && search_depth > 1 {
-            deepening_val = best_move(master_team, init_val, search_depth - 1, current_depth, parent_value, bitstrings_board, transposition_table, game_state);
-            use_deepening_val = true;
+        if current_depth == 0 {
+            if let Some(deepening_move) = context.deepening_move {
+                deepening_val = deepening_move;
+                use_deepening_val = true;
+            }
         }
         moves.rotate_right(1);
         moves[0] = Some(deepening_val);
@@ -196,69 +1806,118 @@ pub mod minimax {
             None => 0,
         };
 
+        let mut stopped = false;
         for i in 0..moves.len() {
             let move_info = match moves[i] {
                 Some(move_info) => move_info,
                 None => break,
             };
 
+            // Out of nodes, the root checks the limit after each of its moves instead
+            if current_depth > 0 && options.node_limit_reached(context.stats) {
+                break;
+            }
+
             let piece_coordinates = move_info.piece_coordinates;
             let move_coordinates = move_info.move_coordinates;
 
             let mut move_error = false;
-            let mut valid_move = true;
+            let mut stalemate = false;
 
             // Get the material value of moving from piece_coordinates to move_coordinates
-            let game_state_new = new_turn(piece_coordinates, move_coordinates, crate::piece::info::IDS[4], game_state); // The ai will only try to promote pawns to queens
-            let mut move_val = match game_state_new {
+            let game_state_new = new_turn(piece_coordinates, move_coordinates, game_state.default_promotion(), game_state); // The ai will only try to promote pawns to the default promotion
+            let move_val = match game_state_new {
                 Ok(game_state) => game_state.points_delta,
                 Err(error) => {
                     move_error = true;
 
                     // If the error was not a checkmate, or stalemate then the error was related to an invalid move
-                    if error.error_code != errors::CHECKMATE_ERROR && error.error_code != errors::STALEMATE_ERROR {
-                        valid_move = false;
-                    } else { // If the error was a checkmate or stalemate return error.value
-                        let mut error_val = error.value;
-
-                        if !master_team {
-                            error_val *= -1;
-                        }
-                        
+                    if error.error_code == errors::CHECKMATE_ERROR { // Nothing beats a checkmate, return the checkmate value less the plies it took
                         return BranchValue {
                             piece_coordinates: piece_coordinates,
                             move_coordinates: move_coordinates,
-                            value: error_val,
+                            value: master_value(error.value).signum() * (MATE - i32::try_from(current_depth).unwrap()),
                             heatmap_value: 0,
+                            terminal: Some(GameResult::Checkmate(game_state.whites_turn)),
                         };
+                    } else if error.error_code == errors::STALEMATE_ERROR { // A stalemate is compared with the other moves, so a won position isn't thrown away
+                        stalemate = true;
+                        0
+                    } else {
+                        error.value
                     }
-                    
-
-                    error.value
                 },
             };
 
             // If the current branch is not the master team then it's move values are negative (because they negatively impact the master team)
-            if !master_team {
-                move_val *= -1
-            }
+            let move_val = master_value(move_val);
 
-            let branch_val = init_val + move_val;
+            let branch_val = add_values(init_val, move_val);
 
             let piece_id = get_board(piece_coordinates, game_state.board_info.board).abs();
             let mut heatmap_val: i8 = 0;
 
             // Use heatmaps to encourage pieces to move to advantageous sqaurs
-            // Only use heatmaps early in the game (when white + black points are < 18)
-            if piece_id != 0 && game_state.white_points_info.points_total + game_state.black_points_info.points_total < 18 {
+            // Only use heatmaps early in the game (when white + black points are < 18), custom pieces have no heatmap
+            if piece_id != 0 && piece_id <= IDS.len().try_into().unwrap() && game_state.white_points_info.points_total + game_state.black_points_info.points_total < 18 {
                 let init_val = get_board(piece_coordinates, PIECE_HEATMAPS[usize::try_from(piece_id - 1).unwrap()]);
                 let move_val = get_board(move_coordinates, PIECE_HEATMAPS[usize::try_from(piece_id - 1).unwrap()]);
                 heatmap_val = move_val - init_val; // Get heatmap delta so worse positions aren't moved to from a good position
             }
 
-            if !move_error { // Do not check child branches inscase of a move errorpoints_delta: i8,
-                let child_min_max = best_move(!master_team, branch_val, search_depth, current_depth + 1, min_max_val, bitstrings_board, transposition_table, game_state_new.unwrap()); // Get min/max value of child branch
-                
+            // The tie break value replaces the heatmap value for root moves, so it decides between moves with equal values
+            if options.tie_break && current_depth == 0 {
+                heatmap_val = tie_break_value(Move {
+                    piece_coordinates,
+                    move_coordinates,
+                }, game_state_new.as_ref().ok(), &game_state);
+            }
+
+            if !move_error || stalemate { // Do not check child branches inscase of a move errorpoints_delta: i8,
+                let current_move = Move {
+                    piece_coordinates,
+                    move_coordinates,
+                };
+
+                let child_min_max = if stalemate {
+                    // The stalemate draw cancels out the master teams material balance, like a repetition
+                    BranchValue {
+                        piece_coordinates: [0, 0],
+                        move_coordinates: [0, 0],
+                        value: init_val - material_balance(master_white, game_state) - contempt,
+                        heatmap_value: 0,
+                        terminal: Some(GameResult::Stalemate),
+                    }
+                } else if null_window {
+                    // Every node below a null window search uses the same bound
+                    best_move(context, branch_val, search_depth, current_depth + 1, Some(parent_value), Some(current_move), game_state_new.unwrap())
+                } else if options.pvs && !init_min_max {
+                    // Check if the move could be better than the best move so far with a null window search, and only search it fully if it could
+                    // Equal values are searched fully when the heatmap value would break the tie
+                    context.null_window = true;
+                    let scout = best_move(context, branch_val, search_depth, current_depth + 1, min_max_val, Some(current_move), game_state_new.unwrap());
+                    context.null_window = false;
+                    let better = match master_team {
+                        true => scout.value > max.value || {scout.value == max.value && heatmap_val > max.heatmap_value},
+                        false => scout.value < min.value || {scout.value == min.value && heatmap_val < min.heatmap_value},
+                    };
+                    if better {
+                        best_move(context, branch_val, search_depth, current_depth + 1, min_max_val, Some(current_move), game_state_new.unwrap())
+                    } else {
+                        scout
+                    }
+                } else {
+                    best_move(context, branch_val, search_depth, current_depth + 1, min_max_val, Some(current_move), game_state_new.unwrap()) // Get min/max value of child branch
+                };
+
+                // A root move whose search ran out of nodes is incomplete, so it's only used when there is nothing else to return
+                if current_depth == 0 && options.node_limit_reached(context.stats) {
+                    stopped = true;
+                    if !init_min_max || use_deepening_val {
+                        break;
+                    }
+                }
+
                 // Update min and max with child value
                 if init_min_max { // Initialize max and min value
                     max = BranchValue {
@@ -266,6 +1925,7 @@ pub mod minimax {
                         move_coordinates: move_coordinates,
                         value: child_min_max.value,
                         heatmap_value: heatmap_val,
+                        terminal: child_min_max.terminal,
                     };
 
                     min = BranchValue {
@@ -273,6 +1933,7 @@ pub mod minimax {
                         move_coordinates: move_coordinates,
                         value: child_min_max.value,
                         heatmap_value: heatmap_val,
+                        terminal: child_min_max.terminal,
                     };
 
                     if master_team {
@@ -288,6 +1949,7 @@ pub mod minimax {
                         move_coordinates: move_coordinates,
                         value: child_min_max.value,
                         heatmap_value: heatmap_val,
+                        terminal: child_min_max.terminal,
                     };
                     if master_team {
                         min_max_val = Some(max.value);
@@ -298,49 +1960,348 @@ pub mod minimax {
                         move_coordinates: move_coordinates,
                         value: child_min_max.value,
                         heatmap_value: heatmap_val,
+                        terminal: child_min_max.terminal,
                     };
                     if !master_team {
                         min_max_val = Some(min.value);
                     }
                 }
 
-                // Alpha beta pruning
-                if use_parent_value {
-                    if master_team {
-                        if max.value > parent_value {
-                            break;
-                        }
-                    } else if min.value < parent_value {
-                        break;
-                    }
-                }
+                // Alpha beta pruning
+                let mut cutoff = false;
+                if use_parent_value {
+                    if master_team {
+                        if max.value > parent_value {
+                            cutoff = true;
+                        }
+                    } else if min.value < parent_value {
+                        cutoff = true;
+                    }
+                }
+
+                if cutoff {
+
+                    // Remember quiet moves which caused a cutoff as the countermove to the enemies previous move
+                    if move_val == 0 {
+                        if let Some(previous_move) = previous_move {
+                            context.countermove_table.insert(previous_move, current_move);
+                        }
+                    }
+                    break;
+                }
+
+                if stopped {
+                    break;
+                }
+            }
+        }
+
+        // Return the best move of the last completed iteration when no root move was completed
+        if stopped && init_min_max {
+            return deepening_val;
+        }
+
+        // The enemy can take a draw instead of its best searched move when it has a perpetual check
+        // A perpetual check is only looked for in replies to the root moves, when the draw is better for the enemy than the search result
+        if options.claim_repetition && current_depth == 1 && !master_team && !init_min_max && !options.node_limit_reached(context.stats) {
+            let draw_val = init_val - material_balance(master_white, game_state) - contempt;
+            if draw_val < min.value {
+                if let Some(perpetual_move) = perpetual_check(PERPETUAL_CHECK_MOVES, context.bitstrings_board, game_state) {
+                    min = BranchValue {
+                        piece_coordinates: perpetual_move.piece_coordinates,
+                        move_coordinates: perpetual_move.move_coordinates,
+                        value: draw_val,
+                        heatmap_value: 0,
+                        terminal: Some(GameResult::Repetition),
+                    };
+                }
+            }
+        }
+
+        // Add board to transposition table, unless the search was cut short by the node limit, or only searched some of the moves
+        // A null window result doesn't replace the result of a full search of the same depth
+        let replace = !null_window || context.transposition_table.get(&board_hash).is_none_or(|transposition_info| {
+            transposition_info.null_window_bound.is_some() || transposition_info.search_depth < search_depth
+        });
+        if replace && !options.node_limit_reached(context.stats) && restrict_to.is_none() {
+            context.transposition_table.insert(board_hash, TranspositionInfo {
+                max: max,
+                min: min,
+                search_depth: search_depth,
+                current_depth: current_depth,
+                null_window_bound: match null_window {
+                    true => Some(parent_value),
+                    false => None,
+                },
+            });
+        }
+
+        // When the master team is still losing after the search, a perpetual check is used to force a draw
+        if master_team && current_depth == 0 {
+            let material_balance = material_balance(game_state.whites_turn, game_state);
+
+            // Checkmate values are not relative to the current material, so they are not added to the material balance
+            let mut search_balance = add_values(material_balance, max.value);
+            if mate_plies(max.value).is_some() {
+                search_balance = max.value;
+            }
+
+            // A draw is only wanted when the search result is worse than the contempt for a draw
+            if material_balance < 0 && search_balance < -contempt {
+                let perpetual_move = perpetual_check(PERPETUAL_CHECK_MOVES, context.bitstrings_board, game_state)
+                    .filter(|perpetual_move| restrict_to.is_none_or(|restrict_to| restrict_to.contains(perpetual_move)));
+                if let Some(perpetual_move) = perpetual_move {
+                    return BranchValue {
+                        piece_coordinates: perpetual_move.piece_coordinates,
+                        move_coordinates: perpetual_move.move_coordinates,
+                        value: -material_balance - contempt, // A draw cancels out the material balance
+                        heatmap_value: 0,
+                        terminal: Some(GameResult::Repetition),
+                    };
+                }
+            }
+
+            // The search can't make progress in a fortress, so a losing quiet result is moved halfway to a draw
+            // Results where the search found a capture, checkmate, or other game end are left alone
+            if options.fortress && material_balance < 0 && max.terminal.is_none() && max.value.abs() <= FORTRESS_MARGIN && fortress(game_state) {
+                max.value = add_values(max.value, -material_balance) / 2;
+            }
+        }
+
+        if master_team { // Return max values for master team
+            return max;
+        }
+
+        min
+    }
+
+    // Key of a position in the evaluation cache, the zobrist hash of the position mixed with the turns board
+    // The evaluation reads how many times pieces have moved (see eval::development_score), which the zobrist hash leaves out
+    // Everything else the evaluation reads (allowed promotions, custom pieces) is the same for a whole search
+    fn eval_key(board_hash: u64, game_state: GameState) -> u64 {
+        use std::hash::Hash;
+        use std::hash::Hasher;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        board_hash.hash(&mut hasher);
+        game_state.board_info.turns_board.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Adds two search values, clamping to +-i8::MAX points so the result can always be negated
+    fn add_values(a: i32, b: i32) -> i32 {
+        let max = i32::from(i8::MAX) * PAWN;
+        {a + b}.clamp(-max, max)
+    }
+
+    // Returns the points of the given team minus the points of the enemy team, in centipawns
+    fn material_balance(white: bool, game_state: GameState) -> i32 {
+        let balance = i32::from(game_state.white_points_info.points_total - game_state.black_points_info.points_total) * PAWN;
+        if white {
+            return balance;
+        }
+        -balance
+    }
+
+    // Returns the tie break value of a move, captures are preferred over checks, checks over developing moves, and developing moves over anything else
+    // Development is the heatmap delta of the moved piece, game_state_new is None when the move ends the game
+    pub fn tie_break_value(chess_move: Move, game_state_new: Option<&GameState>, game_state: &GameState) -> i8 {
+        use crate::get_board;
+        use crate::piece::moves::is_capture;
+
+        const CAPTURE_VALUE: i8 = 64;
+        const CHECK_VALUE: i8 = 32;
+        const MAX_DEVELOPMENT: i8 = 15;
+
+        let mut value = 0;
+        if is_capture(chess_move, *game_state) {
+            value += CAPTURE_VALUE;
+        }
+        if let Some(game_state_new) = game_state_new {
+            if game_state_new.gives_check {
+                value += CHECK_VALUE;
+            }
+        }
+
+        let piece_id = get_board(chess_move.piece_coordinates, game_state.board_info.board).abs();
+        if piece_id != 0 && piece_id <= IDS.len().try_into().unwrap() {
+            let heatmap = PIECE_HEATMAPS[usize::try_from(piece_id - 1).unwrap()];
+            let development = get_board(chess_move.move_coordinates, heatmap) - get_board(chess_move.piece_coordinates, heatmap);
+            value += development.clamp(-MAX_DEVELOPMENT, MAX_DEVELOPMENT);
+        }
+        value
+    }
+
+    // Returns true if the position is locked, neither team can capture a piece or move a pawn, so the material can't change soon
+    pub fn fortress(game_state: GameState) -> bool {
+        use crate::piece::moves::is_capture;
+        use crate::piece::moves::legal_moves;
+
+        // The enemies moves, as if the team to move passed its turn
+        let mut enemy_state = game_state;
+        enemy_state.board_info = crate::flip_board_info(game_state.board_info);
+        enemy_state.board_info.last_turn_coordinates = [0, 0];
+        enemy_state.board_info.en_passant = None;
+        enemy_state.whites_turn = !game_state.whites_turn;
+
+        [game_state, enemy_state].iter().all(|state| {
+            legal_moves(*state).iter().all(|legal_move| {
+                !is_capture(*legal_move, *state) && crate::get_board(legal_move.piece_coordinates, state.board_info.board).abs() != IDS[0]
+            })
+        })
+    }
+
+    // Searches for a checking move which lets the team to move force a repetition by checking the enemy king every move
+    // Returns the first move of the perpetual check if there is one
+    // This is a separate search of its own, best_move only runs it at the root for the master team, and for enemy replies to the root moves when claim_repetition is set
+    pub fn perpetual_check(
+        max_checks: usize,
+        bitstrings_board: &[[HashMap<i8, u64>; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+        game_state: GameState)
+        -> Option<Move> {
+        use crate::gen_zobrist_board_hash;
+        use crate::piece::moves::checking_moves;
+
+        if max_checks == 0 {
+            return None;
+        }
+
+        // Positions that have occurred since the first check, a perpetual check is found once the enemy is forced back into one of them
+        let mut repetition_table: HashMap<u64, u8> = HashMap::new();
+        repetition_table.insert(gen_zobrist_board_hash(game_state.whites_turn, game_state.board_info, bitstrings_board), 1);
+
+        checking_moves(game_state)
+            .into_iter()
+            .find(|&checking_move| check_forces_repetition(checking_move, max_checks - 1, bitstrings_board, &mut repetition_table, game_state))
+    }
+
+    // Returns true if every enemy reply to the checking move leads to a repeated position, or to a position where another check forces a repetition
+    fn check_forces_repetition(
+        checking_move: Move,
+        remaining_checks: usize,
+        bitstrings_board: &[[HashMap<i8, u64>; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+        repetition_table: &mut HashMap<u64, u8>,
+        game_state: GameState)
+        -> bool {
+        use crate::gen_zobrist_board_hash;
+        use crate::piece::moves::checking_moves;
+        use crate::piece::moves::legal_moves;
+
+        // An error after a checking move can only be a checkmate, which is even better than a draw
+        let game_state_check = match new_turn(checking_move.piece_coordinates, checking_move.move_coordinates, game_state.default_promotion(), game_state) {
+            Ok(game_state) => game_state,
+            Err(_) => return true,
+        };
+
+        for reply in legal_moves(game_state_check) {
+            let game_state_reply = match new_turn(reply.piece_coordinates, reply.move_coordinates, game_state_check.default_promotion(), game_state_check) {
+                Ok(game_state) => game_state,
+                Err(_) => return false, // The enemy ended the game
+            };
+
+            let board_hash = gen_zobrist_board_hash(game_state_reply.whites_turn, game_state_reply.board_info, bitstrings_board);
+            if repetition_table.contains_key(&board_hash) {
+                continue;
+            }
+
+            if remaining_checks == 0 {
+                return false;
+            }
+
+            // Another check has to force a repetition from this position
+            repetition_table.insert(board_hash, 1);
+            let mut forced = false;
+            for next_check in checking_moves(game_state_reply) {
+                if check_forces_repetition(next_check, remaining_checks - 1, bitstrings_board, repetition_table, game_state_reply) {
+                    forced = true;
+                    break;
+                }
+            }
+            repetition_table.remove(&board_hash);
+
+            if !forced {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Orders the moves of a GameState with the orderer, or with order_moves if there is none
+    // Pawns only promote to queens in the search, so other promotions are left out before the orderer sees the moves
+    #[inline(never)]
+    fn ordered_moves(orderer: Option<&dyn MoveOrderer>, countermove: Option<Move>, current_depth: usize, game_state: GameState) -> [Option<BranchValue>; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)] {
+        let orderer = match orderer {
+            Some(orderer) => orderer,
+            None => return order_moves(countermove, game_state),
+        };
+
+        let mut moves: Vec<MoveInfo> = crate::piece::moves::legal_move_infos(game_state).into_iter()
+            .filter(|move_info| move_info.promotion.is_none() || move_info.promotion == Some(game_state.default_promotion()))
+            .collect();
+        orderer.order(&mut moves, &OrderContext {
+            game_state,
+            countermove,
+            current_depth,
+        });
+
+        let mut moves_array: [Option<BranchValue>; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)] = [None; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)];
+        for (i, move_info) in moves.iter().enumerate() {
+            moves_array[i] = Some(BranchValue {
+                piece_coordinates: move_info.chess_move.piece_coordinates,
+                move_coordinates: move_info.chess_move.move_coordinates,
+                value: 0,
+                heatmap_value: 0,
+                terminal: None,
+            });
+        }
+        moves_array
+    }
+
+    // Removes the moves which aren't in restrict_to from an ordered moves array, keeping the order of the rest
+    fn restrict_moves(moves: &mut [Option<BranchValue>; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)], restrict_to: &[Move]) {
+        let mut kept = 0;
+        for i in 0..moves.len() {
+            let branch_value = match moves[i] {
+                Some(branch_value) => branch_value,
+                None => break,
+            };
+            moves[i] = None;
+
+            let chess_move = Move {
+                piece_coordinates: branch_value.piece_coordinates,
+                move_coordinates: branch_value.move_coordinates,
+            };
+            if restrict_to.contains(&chess_move) {
+                moves[kept] = Some(branch_value);
+                kept += 1;
             }
         }
+    }
 
-        // Add board to transposition table
-        transposition_table.insert(board_hash, TranspositionInfo {
-            max: max,
-            min: min,
-            search_depth: search_depth,
-            current_depth: current_depth,
+    // Moves the hinted move to the front of an ordered moves array, keeping the order of the rest
+    // Nothing changes when the hint isn't one of the moves (E.g. the hint came from a position with the same hash)
+    fn hint_first(moves: &mut [Option<BranchValue>; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)], hint: Move) {
+        let index = moves.iter().map_while(|branch_value| *branch_value).position(|branch_value| {
+            branch_value.piece_coordinates == hint.piece_coordinates && branch_value.move_coordinates == hint.move_coordinates
         });
-
-        if master_team { // Return max values for master team
-            return max;
+        if let Some(index) = index {
+            moves[0..=index].rotate_right(1);
         }
-
-        min
     }
 
     // Orders possible moves for a GameState into a vec
-    fn order_moves(game_state: GameState) -> [Option<BranchValue>; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)] {
+    // Captures are valued by static exchange evaluation, and captures that lose material are ordered after every other move
+    // The countermove (if it is a valid quiet move) is ordered ahead of all other quiet moves
+    fn order_moves(countermove: Option<Move>, game_state: GameState) -> [Option<BranchValue>; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)] {
         use crate::get_board;
         use crate::coordinates_from_usize;
         use crate::board::errors;
         use crate::piece::moves;
+        use crate::algorithm::eval::see;
 
         let mut moves_array: [Option<BranchValue>; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)] = [None; {BOARD_SIZE[0] * BOARD_SIZE[1]}.pow(2)];
         let mut moves: Vec<BranchValue> = Vec::new();
+        let mut losing_captures: Vec<BranchValue> = Vec::new();
         
         
         for x_piece in 0..BOARD_SIZE[0] {
@@ -366,35 +2327,39 @@ pub mod minimax {
                         let mut move_coordinates = coordinates_from_usize([x_move, y_move]);
                         let move_id = get_board(move_coordinates, game_state.board_info.board);
 
-                        // Get the value of the piece at move_coordinates
-                        let mut move_value = 0;
-                        if move_id != 0 {
-                            move_value = game_state.board_info.pieces[usize::try_from(move_id.abs() - 1).unwrap()].value;
-                        }
-
                         let move_board = moves::gen_move_board(piece_coordinates, move_coordinates, crate::piece::info::IDS[4], game_state.board_info);
                         if move_board.board != game_state.board_info.board { // If the move board is different to the initial board then the move is valid
-                            let enemy_moves_board = moves::gen_enemy_moves(game_state.whites_turn, move_board);
-                            let moves_board = moves::gen_all_moves(game_state.whites_turn, None, move_board);
-
-                            let mut move_points_change = move_value;
-
-                            // Assume the enemy will try to trade if the square is not defended
-                            if get_board(move_coordinates, enemy_moves_board) == 1 && get_board(move_coordinates, moves_board) == 0 {
-                                move_points_change -= piece_value;
-                            }
-
-                            if piece_coordinates == [1, 0] && move_coordinates == [1, 6] {
-                                //println!("{}", move_points_change);
-                            }
+                            let chess_move = Move {
+                                piece_coordinates,
+                                move_coordinates,
+                            };
 
-                            // Add move to moves vec
-                            moves.push(BranchValue {
+                            let mut branch_value = BranchValue {
                                 piece_coordinates: piece_coordinates,
                                 move_coordinates: move_coordinates,
-                                value: move_points_change,
+                                value: 0,
                                 heatmap_value: 0,
-                            });
+                                terminal: None,
+                            };
+
+                            // Captures are valued by the whole exchange on the move square
+                            if move_board.capture_coordinates.is_some() || (move_id != 0 && crate::piece_white(move_id) != crate::piece_white(piece_id)) {
+                                branch_value.value = i32::from(see(chess_move, game_state));
+                                if branch_value.value < 0 {
+                                    losing_captures.push(branch_value);
+                                } else {
+                                    moves.push(branch_value);
+                                }
+                                continue;
+                            }
+
+                            // Assume the enemy will try to trade if the square of a quiet move is not defended
+                            let enemy_moves_board = moves::gen_enemy_moves(game_state.whites_turn, move_board);
+                            let moves_board = moves::gen_all_moves(game_state.whites_turn, None, move_board);
+                            if get_board(move_coordinates, enemy_moves_board) == 1 && get_board(move_coordinates, moves_board) == 0 {
+                                branch_value.value -= i32::from(piece_value);
+                            }
+                            moves.push(branch_value);
                         }
                     }
                 }
@@ -403,174 +2368,827 @@ pub mod minimax {
 
         // Sort moves and return
         moves.sort_by(|a, b| b.value.cmp(&a.value));
+        losing_captures.sort_by_key(|branch_value| std::cmp::Reverse(branch_value.value));
+
+        // Move the countermove in front of the other quiet moves
+        if let Some(countermove) = countermove {
+            let countermove_index = moves.iter().position(|m| m.piece_coordinates == countermove.piece_coordinates && m.move_coordinates == countermove.move_coordinates);
+            let quiet_index = moves.iter().position(|m| m.value <= 0);
+
+            if let (Some(countermove_index), Some(quiet_index)) = (countermove_index, quiet_index) {
+                if countermove_index > quiet_index {
+                    let countermove_info = moves.remove(countermove_index);
+                    moves.insert(quiet_index, countermove_info);
+                }
+            }
+        }
         
         // Add moves vec to moves array
+        moves.append(&mut losing_captures);
         for i in 0..moves.len() {
             moves_array[i] = Some(moves[i]);
         }
         moves_array
     }
 
+    // Returns a short human readable reason for a move, e.g. "captures rook (SEE +5), gives check"
+    pub fn explain_move(chess_move: Move, game_state: GameState) -> String {
+        use crate::algorithm::eval::see;
+        use crate::get_board;
+        use crate::piece::moves::checking_moves;
+        use crate::piece::moves::legal_move_count;
+
+        const PIECE_NAMES: [&str; IDS.len()] = ["pawn", "rook", "knight", "bishop", "queen", "king"];
+
+        // Custom pieces are named by their fen id
+        let piece_name = |id: i8| -> String {
+            let index = usize::try_from(id.abs() - 1).unwrap();
+            if index < IDS.len() {
+                return String::from(PIECE_NAMES[index]);
+            }
+            game_state.board_info.pieces[index].id_fen.to_ascii_lowercase().to_string()
+        };
+
+        let board = game_state.board_info.board;
+        let mut reasons: Vec<String> = Vec::new();
+
+        if legal_move_count(game_state) == 1 {
+            reasons.push(String::from("only legal move"));
+        }
+
+        let captured_id = get_board(chess_move.move_coordinates, board);
+        if captured_id != 0 {
+            let name = piece_name(captured_id);
+            reasons.push(format!("captures {} (SEE {:+})", name, see(chess_move, game_state)));
+        }
+
+        if checking_moves(game_state).contains(&chess_move) {
+            reasons.push(String::from("gives check"));
+        }
+
+        // Moves to a better square on the piece heatmap
+        let piece_id = get_board(chess_move.piece_coordinates, board).abs();
+        if piece_id != 0 && piece_id <= IDS.len().try_into().unwrap() {
+            let heatmap = PIECE_HEATMAPS[usize::try_from(piece_id - 1).unwrap()];
+            let heatmap_val = get_board(chess_move.move_coordinates, heatmap) - get_board(chess_move.piece_coordinates, heatmap);
+            if heatmap_val > 0 {
+                let name = piece_name(piece_id);
+                reasons.push(format!("develops {} (PST {:+})", name, heatmap_val));
+            }
+        }
+
+        if reasons.is_empty() {
+            return String::from("quiet move");
+        }
+        reasons.join(", ")
+    }
+
 
 
     #[cfg(test)]
     mod tests {
         use crate::fen;
-        use crate::board::turn::PointsInfo;
-        use crate::piece::moves::BoardInfo;
         use super::*;
 
         #[test]
         fn best_move_test1() {
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+            let game_state = GameState::from_board(fen::decode("8/8/8/8/8/r2r4/3R3n/8"), true).unwrap();
 
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+            assert_eq!(Search::builder().depth(3).build().go(game_state).unwrap().move_coordinates, [7, 1]);
+        }
 
-                points_delta: 0,
+        #[test]
+        fn best_move_test2() {
+            let game_state = GameState::from_board(fen::decode("8/8/8/4p3/3b1p2/4P3/8/8"), true).unwrap();
 
-                board_info: BoardInfo {
-                    board: fen::decode("8/8/8/8/8/r2r4/3R3n/8"),
-                    turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
-                },
+            assert_eq!(Search::builder().depth(3).build().go(game_state).unwrap().move_coordinates, [3, 3]);
+        }
+
+        #[test]
+        fn best_move_test3() {
+            let game_state = GameState::from_board(fen::decode("k7/1p6/6r1/8/8/5B2/8/1Q6"), true).unwrap();
+
+            assert_eq!(Search::builder().depth(3).build().go(game_state).unwrap().move_coordinates, [1, 6]);
+        }
+
+        #[test]
+        fn search_builder_test() { // Test the search builder giving the same result as best_move_test1
+            let mut game_state = GameState::new();
+            game_state.board_info.board = fen::decode("8/8/8/8/8/r2r4/3R3n/8");
+
+            let mut search = Search::builder().depth(3).contempt(0).eval(|game_state, _| Score::from_perspective(i32::from(crate::algorithm::eval::hanging_score(game_state)) * crate::algorithm::eval::PAWN, game_state.whites_turn)).build();
+            assert_eq!(search.go(game_state).unwrap().move_coordinates, [7, 1]);
+        }
+
+        #[test]
+        fn tempo_test() { // Test the tempo going to the team to move at the end of the search, and a tempo of 0 leaving the evaluation alone
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+
+            // The default tempo is a fraction of a pawn
+            let tempo = EvalWeights::new().tempo;
+            assert!(tempo > 0 && tempo < PAWN);
+
+            // Black is to move at depth 1 and white at depth 2
+            let mut search = Search::builder().depth(1).eval(|_, _| Score::from_white_perspective(0)).build();
+            assert_eq!(search.go(game_state).unwrap().value, -tempo);
+            let mut search = Search::builder().depth(2).eval(|_, _| Score::from_white_perspective(0)).build();
+            assert_eq!(search.go(game_state).unwrap().value, tempo);
+
+            let weights = EvalWeights {
+                tempo: 0,
+                ..EvalWeights::new()
+            };
+            let mut search = Search::builder().depth(2).eval(|_, _| Score::from_white_perspective(0)).weights(weights).build();
+            assert_eq!(search.go(game_state).unwrap().value, 0);
+        }
 
-                whites_turn: true,
+        #[test]
+        fn node_limit_test() { // Test small node limits stopping the search near the limit with a legal move, and a larger limit finding a knight fork
+            let game_state = GameState::from_fen("2q3k1/8/8/3N4/8/8/8/4K3 w - - 0 1").unwrap();
+            let fork = Move {
+                piece_coordinates: [3, 4],
+                move_coordinates: [4, 6],
             };
 
-            let mut transposition_table: HashMap<u64, TranspositionInfo> = HashMap::new();
+            // Each node checks the limit before searching its moves, so the search can only go over by a few nodes per depth
+            let depth = 3;
+            for node_limit in [1, 50] {
+                let mut search = Search::builder().depth(depth).node_limit(node_limit).build();
+                let result = search.go(game_state).unwrap();
+                let result_move = Move {
+                    piece_coordinates: result.piece_coordinates,
+                    move_coordinates: result.move_coordinates,
+                };
+                assert!(crate::piece::moves::legal_moves(game_state).contains(&result_move));
+                assert!(search.stats.nodes >= node_limit);
+                assert!(search.stats.nodes <= node_limit + 2 * u64::try_from(depth).unwrap());
+            }
+
+            let mut search = Search::builder().depth(depth).node_limit(10000).build();
+            let result = search.go(game_state).unwrap();
+            assert_eq!(result.move_coordinates, fork.move_coordinates);
+            assert!(search.stats.nodes <= 10000);
+        }
+
+        #[test]
+        fn eval_cache_test() { // Test move orders reaching the same leaf sharing an evaluation, the cache being cleared by each search, and positions only differing in piece move counts being evaluated separately
+            use crate::algorithm::eval::development_score;
+            use crate::uci::apply_moves;
+
+            let game_state = GameState::from_fen("4k3/8/8/3r4/8/2N5/8/4K3 w - - 0 1").unwrap();
+
+            let mut search = Search::builder().depth(3).build();
+            search.go(game_state).unwrap();
+            assert!(search.stats.eval_cache_hits > 0);
+
+            // A search depth of 0 only evaluates game_state
+            let mut search = Search::builder().depth(0).build();
+            let first = search.go(game_state).unwrap();
+            let second = search.go(game_state).unwrap();
+            assert_eq!(search.stats.eval_cache_hits, 0);
+            assert_eq!(first, second);
+            assert_eq!(search.eval_cache.len(), 1);
+
+            // The white knights moved three times each to reach f3 and c3, which the zobrist hash can't see
+            let moved_once = apply_moves(GameState::new(), &["g1f3", "g8h6", "b1c3", "h6g8"]).unwrap();
+            let moved_thrice = apply_moves(GameState::new(), &["g1f3", "g8h6", "f3g5", "h6g8", "g5f3", "g8h6", "b1c3", "h6g8", "c3b5", "g8h6", "b5c3", "h6g8"]).unwrap();
             let bitstrings_board = crate::gen_bistrings_board();
+            let hash = |game_state: GameState| crate::gen_zobrist_board_hash(game_state.whites_turn, game_state.board_info, &bitstrings_board);
+            assert_eq!(hash(moved_once), hash(moved_thrice));
+            assert_ne!(development_score(moved_once), development_score(moved_thrice));
+            assert_ne!(eval_key(hash(moved_once), moved_once), eval_key(hash(moved_thrice), moved_thrice));
+        }
 
-            assert_eq!(best_move(true, 0, 3, 0, None, &bitstrings_board, &mut transposition_table, game_state).move_coordinates, [7, 1]);
+        #[test]
+        fn go_reuse_test() { // Test a search reused on the position after its best move giving the same result as a fresh search
+            let game_state = GameState::from_fen("4k3/8/8/3r4/8/2N5/8/4K3 w - - 0 1").unwrap();
+            let mut reused = Search::builder().depth(2).build();
+
+            // Nxd5 leaves black a rook down, the reused search must not value blacks reply with the table of whites search
+            let result = reused.go(game_state).unwrap();
+            assert_eq!(result, Search::builder().depth(2).build().go(game_state).unwrap());
+            let game_state = new_turn(result.piece_coordinates, result.move_coordinates, IDS[4], game_state).unwrap();
+            assert_eq!(reused.go(game_state).unwrap(), Search::builder().depth(2).build().go(game_state).unwrap());
         }
 
         #[test]
-        fn best_move_test2() {
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+        fn warm_start_test() { // Test move hints from a warm started game saving nodes in a search deeper than WARM_START_DEPTH, without changing its result
+            let moves = ["e1e2", "e8e7", "e2e3", "e7e6"];
+            let start = GameState::from_fen("4k3/8/8/3r4/8/2N5/8/4K3 w - - 0 1").unwrap();
+            let mut history = vec![start];
+            for i in 0..moves.len() {
+                history.push(crate::uci::apply_moves(start, &moves[0..i + 1]).unwrap());
+            }
+            let game_state = *history.last().unwrap();
 
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+            let depth = WARM_START_DEPTH + 1;
+            let mut cold = Search::builder().depth(depth).build();
+            let cold_move = cold.go(game_state).unwrap();
 
-                points_delta: 0,
+            // Only moves are kept, so the transposition table is left empty
+            let mut warm = Search::builder().depth(depth).build();
+            warm.warm_start(&history);
+            assert!(!warm.move_hints.is_empty());
+            assert!(warm.transposition_table.is_empty());
 
-                board_info: BoardInfo {
-                    board: fen::decode("8/8/8/4p3/3b1p2/4P3/8/8"),
-                    turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
-                },
+            let warm_move = warm.go(game_state).unwrap();
+            assert!(warm.stats.nodes < cold.stats.nodes);
+            assert_eq!(warm_move, cold_move);
+        }
 
-                whites_turn: true,
-            };
+        #[test]
+        fn max_depth_test() { // Test a search depth above MAX_DEPTH being clamped instead of overflowing, and the node limit stopping the deepening loop
+            let game_state = GameState::from_fen("2q3k1/8/8/3N4/8/8/8/4K3 w - - 0 1").unwrap();
 
-            let mut transposition_table: HashMap<u64, TranspositionInfo> = HashMap::new();
-            let bitstrings_board = crate::gen_bistrings_board();
+            let mut search = Search::builder().depth(usize::MAX).node_limit(1000).build();
+            assert_eq!(search.depth, MAX_DEPTH);
 
-            assert_eq!(best_move(true, 0, 3, 0, None, &bitstrings_board, &mut transposition_table, game_state).move_coordinates, [3, 3]);
+            // The limit stops the deepening loop, so the fork found by the shallow searches is returned without starting deeper ones
+            let fork = Move {
+                piece_coordinates: [3, 4],
+                move_coordinates: [4, 6],
+            };
+            let result = search.go(game_state).unwrap();
+            assert_eq!(result.piece_coordinates, fork.piece_coordinates);
+            assert_eq!(result.move_coordinates, fork.move_coordinates);
+            assert!(search.stats.nodes <= 1000 + 2);
         }
 
         #[test]
-        fn best_move_test3() {
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+        fn claim_repetition_test() { // Test a win being valued as a draw when the enemy queen has a perpetual check
+            let game_state = GameState::from_fen("7k/1Q6/1R6/8/8/8/6P1/4q2K w - - 0 1").unwrap();
+
+            // Kh2 is forced, then Qh4+ Kg1 Qe1+ repeats
+            let result = Search::builder().depth(3).claim_repetition(true).build().go(game_state).unwrap();
+            assert_eq!(result.move_coordinates, [7, 1]);
+            assert_eq!(result.terminal, Some(GameResult::Repetition));
+            assert_eq!(result.value, -material_balance(true, game_state));
+
+            // Off by default, so the repetition is only found inside the search
+            let result = Search::builder().depth(3).build().go(game_state).unwrap();
+            assert_eq!(result.terminal, None);
+            assert!(result.value > -material_balance(true, game_state));
+        }
 
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+        #[test]
+        fn best_move_stalemate_test() { // Test a forced stalemate being reported as a draw even though white is ahead
+            let game_state = GameState::from_fen("8/8/Q7/8/8/1k6/1p6/K3Q3 w - - 0 1").unwrap();
+
+            // Kb1 is the only legal move
+            let mut search = Search::builder().depth(2).build();
+            let result = search.go(game_state).unwrap();
+            assert_eq!(result.move_coordinates, [1, 0]);
+            assert_eq!(result.terminal, Some(GameResult::Stalemate));
+
+            let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+            let mut search = Search::builder().depth(2).build();
+            assert_eq!(search.go(game_state).unwrap().terminal, Some(GameResult::Checkmate(true)));
+        }
 
-                points_delta: 0,
+        #[test]
+        fn pvs_test() { // Test principal variation search finding the same moves as the full window search, with fewer nodes over a set of random positions
+            use crate::piece::moves::random_legal_move;
+            use rand::SeedableRng;
+
+            // Positions are reached by random moves from a fixed seed, so the set is the same every run
+            let mut rng = rand::rngs::StdRng::seed_from_u64(1168);
+            let fens = [
+                "4k3/8/8/3r4/8/2N5/8/4K3 w - - 0 1",
+                "4k3/8/2n5/8/8/8/1B6/R3K3 w - - 0 1",
+                "2q3k1/8/8/3N4/8/8/8/4K3 w - - 0 1",
+            ];
+
+            let mut pvs_nodes = 0;
+            let mut full_window_nodes = 0;
+            for fen in fens {
+                for _ in 0..3 {
+                    let mut game_state = GameState::from_fen(fen).unwrap();
+                    for _ in 0..6 {
+                        let chess_move = match random_legal_move(game_state, &mut rng) {
+                            Some(chess_move) => chess_move,
+                            None => break,
+                        };
+                        game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, IDS[4], game_state) {
+                            Ok(game_state_new) => game_state_new,
+                            Err(_) => break,
+                        };
+                    }
 
-                board_info: BoardInfo {
-                    board: fen::decode("k7/1p6/6r1/8/8/5B2/8/1Q6 w - - 0 1"),
-                    turns_board: [[1i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
-                },
+                    let mut full_window = Search::builder().depth(3).build();
+                    let mut pvs = Search::builder().depth(3).pvs(true).build();
+                    assert_eq!(pvs.go(game_state).unwrap(), full_window.go(game_state).unwrap());
+                    pvs_nodes += pvs.stats.nodes;
+                    full_window_nodes += full_window.stats.nodes;
+                }
+            }
+            assert!(pvs_nodes < full_window_nodes, "{} {}", pvs_nodes, full_window_nodes);
+        }
 
-                whites_turn: true,
+        #[test]
+        fn tie_break_test() { // Test an equal knight trade being picked over a check and quiet moves with the same value, and a check over quiet moves
+            let game_state = GameState::from_fen("4k3/8/2p5/3n4/8/4N3/8/R3K3 w - - 0 1").unwrap();
+            let result = Search::builder().depth(2).eval(|_, _| Score::from_white_perspective(0)).tie_break(true).build().go(game_state).unwrap();
+            assert_eq!(result.value, EvalWeights::new().tempo);
+            assert_eq!(result.piece_coordinates, [4, 2]);
+            assert_eq!(result.move_coordinates, [3, 4]);
+
+            let capture = Move {
+                piece_coordinates: [4, 2],
+                move_coordinates: [3, 4],
+            };
+            let check = Move {
+                piece_coordinates: [0, 0],
+                move_coordinates: [0, 7],
             };
+            let quiet = Move {
+                piece_coordinates: [4, 0],
+                move_coordinates: [4, 1],
+            };
+            let value = |chess_move: Move| tie_break_value(chess_move, new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, IDS[4], game_state).as_ref().ok(), &game_state);
+            assert!(value(capture) > value(check));
+            assert!(value(check) > value(quiet));
+
+            // Without the tie break the first rook move in board order is picked
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+            let result = Search::builder().depth(2).eval(|_, _| Score::from_white_perspective(0)).build().go(game_state).unwrap();
+            assert_eq!(result.move_coordinates, [0, 1]);
+            let result = Search::builder().depth(2).eval(|_, _| Score::from_white_perspective(0)).tie_break(true).build().go(game_state).unwrap();
+            assert_eq!(result.move_coordinates, [0, 7]);
+        }
 
-            let mut transposition_table: HashMap<u64, TranspositionInfo> = HashMap::new();
-            let bitstrings_board = crate::gen_bistrings_board();
+        #[test]
+        fn fortress_test() { // Test a locked position a bishop down moving towards a draw, and a tactical position being left alone
+            let game_state = GameState::from_fen("8/8/1k6/p1p1p1p1/P1P1P1P1/8/7b/1K6 w - - 0 1").unwrap();
+            assert!(fortress(game_state));
+
+            let plain = Search::builder().depth(2).build().go(game_state).unwrap();
+            let result = Search::builder().depth(2).fortress(true).build().go(game_state).unwrap();
+            assert!(result.value > plain.value);
+            assert!(result.value <= -material_balance(true, game_state));
+
+            let game_state = GameState::from_fen("2q3k1/8/8/3N4/8/8/8/2R1K3 w - - 0 1").unwrap();
+            assert!(!fortress(game_state));
+            let plain = Search::builder().depth(2).build().go(game_state).unwrap();
+            assert_eq!(Search::builder().depth(2).fortress(true).build().go(game_state).unwrap(), plain);
+        }
+
+        #[test]
+        fn kq_vs_k_test() { // Test the engine mating instead of stalemating, and mating a bare king from the center
+            let game_state = GameState::from_fen("k7/8/1K6/8/8/8/8/2Q5 w - - 0 1").unwrap();
+
+            // Qc7 stalemates, Qc8 mates
+            let result = Search::builder().depth(2).build().go(game_state).unwrap();
+            assert_eq!(result.move_coordinates, [2, 7]);
+            assert_eq!(result.terminal, Some(GameResult::Checkmate(true)));
 
-            assert_eq!(best_move(true, 0, 3, 0, None, &bitstrings_board, &mut transposition_table, game_state).move_coordinates, [1, 6]);
+            let game_state = GameState::from_fen("8/8/8/4k3/8/8/8/3QK3 w - - 0 1").unwrap();
+            assert_eq!(self_play(game_state, 2, 100).1, Some(GameResult::Checkmate(true)));
         }
 
         #[test]
-        fn order_moves_test() {
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+        fn go_explained_test() { // Test a free rook capture being explained as a capture
+            let game_state = GameState::from_fen("4k3/8/8/3r4/8/2N5/8/4K3 w - - 0 1").unwrap();
 
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+            let mut search = Search::builder().depth(2).build();
+            let (best_move, reason) = search.go_explained(game_state).unwrap();
+
+            assert_eq!(best_move.move_coordinates, [3, 4]);
+            assert_eq!(reason, "captures rook (SEE +5)");
+        }
 
-                points_delta: 0,
+        #[test]
+        fn mate_distance_test() { // Test a checkmate scoring less the more plies it takes, from both teams perspectives
+            // 1. Ra8#
+            let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+            let mate = Search::builder().depth(3).build().go(game_state).unwrap();
+            assert_eq!(mate.value, MATE);
+            assert_eq!(mate_plies(mate.value), Some(0));
+
+            // 1. Kb6 Kb8 2. Rh8#
+            let game_state = GameState::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+            let mate = Search::builder().depth(3).build().go(game_state).unwrap();
+            assert_eq!(mate.value, MATE - 2);
+            assert_eq!(mate_plies(mate.value), Some(2));
+
+            // Black is mated after its only move
+            let game_state = GameState::from_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1").unwrap();
+            let mated = Search::builder().depth(2).build().go(game_state).unwrap();
+            assert_eq!(mated.value, -(MATE - 1));
+            assert_eq!(mate_plies(mated.value), Some(1));
+
+            assert_eq!(mate_plies(5 * PAWN), None);
+        }
 
-                board_info: BoardInfo {
-                    board: fen::decode("2n5/7n/8/6R1/8/8/8/2Q3R1"),
-                    turns_board: [[1i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
-                },
+        #[test]
+        fn add_values_test() { // Test search values saturating symmetrically so they can be negated
+            let checkmate = i32::from(crate::piece::info::CHECKMATE_VALUE) * PAWN;
+            let max = i32::from(i8::MAX) * PAWN;
+            assert_eq!(add_values(checkmate, 39 * PAWN), max);
+            assert_eq!(add_values(-checkmate, -39 * PAWN), -max);
+            assert_eq!(add_values(5 * PAWN, -3 * PAWN), 2 * PAWN);
+        }
 
-                whites_turn: true,
-            };
+        #[test]
+        fn self_play_test() { // Test self play stopping on a draw and on a checkmate
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+            let (game_state_end, result) = self_play(game_state, 2, 50);
+            assert_eq!(game_state_end, game_state);
+            assert_eq!(result, Some(GameResult::InsufficientMaterial));
+
+            let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+            let (_, result) = self_play(game_state, 1, 50);
+            assert_eq!(result, Some(GameResult::Checkmate(true)));
+        }
+
+        #[test]
+        fn best_move_repetition_test() { // Test a losing team finding a move that repeats a position from its history
+            let game_state = GameState::from_fen("7k/8/8/8/8/8/8/R6K b - - 0 1").unwrap();
+
+            // Black king g8 was played before
+            let repeated = new_turn([0, 0], [1, 0], 0, game_state).unwrap();
+            let game_state = game_state.fork(repeated.position_hash());
+
+            let result = Search::builder().depth(1).build().go(game_state).unwrap();
+
+            // The draw cancels out blacks missing rook
+            assert_eq!(result.move_coordinates, [1, 0]);
+            assert_eq!(result.value, 5 * PAWN);
+        }
+
+        #[test]
+        fn best_move_hanging_test() { // Test the engine moving a hanging knight, even when the capture is past the search depth
+            let mut game_state = GameState::new();
+            game_state.board_info.board = fen::decode("4k3/8/8/3p4/4N3/8/8/4K3");
+            game_state.board_info.turns_board = fen::derive_state_boards("4k3/8/8/3p4/4N3/8/8/4K3").unwrap().turns_board;
+
+            let result = Search::builder().depth(1).build().go(game_state).unwrap();
+
+            // Get the knight out of the way of the pawn, blacks passed pawn and more active king leave white behind by less than a piece
+            assert_eq!(result.piece_coordinates, [4, 3]);
+            assert!(result.value < 0 && result.value > -3 * PAWN);
+        }
+
+        #[test]
+        fn best_move_perpetual_test() { // Test a perpetual check being used to save a lost position
+            let game_state = GameState::from_fen("1r3b1k/6p1/8/3Q4/1q6/8/PP6/K7 w - - 0 1").unwrap();
+
+            let mut search = Search::builder().depth(2).build();
+            let result = search.go(game_state).unwrap();
+
+            // The queen gives a perpetual check, and the position is valued as a draw
+            assert_eq!(result.piece_coordinates, [3, 4]);
+            assert_eq!(result.value, 7 * PAWN);
+            assert_eq!(result.terminal, Some(GameResult::Repetition));
+            assert_eq!(perpetual_check(PERPETUAL_CHECK_MOVES, &search.bitstrings_board, game_state).map(|perpetual_move| perpetual_move.piece_coordinates), Some([3, 4]));
+        }
+
+        #[test]
+        fn order_moves_test() {
+            let game_state = GameState::from_board(fen::decode("2n5/7n/8/6R1/8/8/8/2Q3R1"), true).unwrap();
 
-            let result = order_moves(game_state);
+            let result = order_moves(None, game_state);
             let best_move = BranchValue {
                 piece_coordinates: [2, 0],
                 move_coordinates: [2, 7],
                 value: 3,
                 heatmap_value: 0,
+                terminal: None,
             };
 
             assert_eq!(result[0], Some(best_move));
         }
+
+        #[test]
+        fn order_moves_see_test() { // Test a queen taking a defended pawn being ordered after a pawn taking a free pawn, and after every quiet move
+            let game_state = GameState::from_fen("4k3/8/2p5/3p1p2/4P3/8/8/3QK3 w - - 0 1").unwrap();
+            let result: Vec<BranchValue> = order_moves(None, game_state).iter().flatten().copied().collect();
+
+            let index = |piece_coordinates: [i8; 2], move_coordinates: [i8; 2]| -> usize {
+                result.iter().position(|m| m.piece_coordinates == piece_coordinates && m.move_coordinates == move_coordinates).unwrap()
+            };
+
+            let free_capture = index([4, 3], [5, 4]);
+            let losing_capture = index([3, 0], [3, 4]);
+            assert!(free_capture < 2); // exd5 also wins a pawn, the queen recaptures
+            assert_eq!(result[free_capture].value, 1);
+            assert_eq!(losing_capture, result.len() - 1);
+            assert_eq!(result[losing_capture].value, -7); // cxd5 exd5
+        }
+
+        #[test]
+        fn iter_deepening_test() { // Test one evaluation per depth, the last matching a direct search, and the principal variation of a knight fork
+            let game_state = GameState::from_fen("2q3k1/8/8/3N4/8/8/8/4K3 w - - 0 1").unwrap();
+
+            let mut search = Search::builder().build();
+            let evaluations: Vec<Evaluation> = search.iter_deepening(game_state, 3).collect();
+            assert_eq!(evaluations.iter().map(|evaluation| evaluation.depth).collect::<Vec<usize>>(), vec![1, 2, 3]);
+
+            let direct = Search::builder().depth(3).build().go(game_state).unwrap();
+            let last = evaluations.last().unwrap();
+            assert_eq!(last.best_move.piece_coordinates, direct.piece_coordinates);
+            assert_eq!(last.best_move.move_coordinates, direct.move_coordinates);
+            assert_eq!(last.best_move.value, direct.value);
+
+            // Ne7+, the king moves off g8, Nxc8, all from whites perspective
+            assert_eq!(last.pv[0].move_coordinates, [4, 6]);
+            assert_eq!(last.pv.len(), 3);
+            assert_eq!(last.pv[1].piece_coordinates, [6, 7]);
+            assert_eq!(last.pv[2].piece_coordinates, [4, 6]);
+            assert_eq!(last.pv[2].move_coordinates, [2, 7]);
+
+            // Iterators can be stopped early
+            assert_eq!(search.iter_deepening(game_state, 3).take_while(|evaluation| evaluation.depth < 2).count(), 1);
+        }
+
+        #[derive(Debug)]
+        struct ReverseOrderer;
+
+        impl MoveOrderer for ReverseOrderer {
+            fn order(&self, moves: &mut Vec<MoveInfo>, context: &OrderContext) {
+                DefaultOrderer.order(moves, context);
+                moves.reverse();
+            }
+        }
+
+        #[test]
+        fn orderer_test() { // Test the default orderer matching order_moves, and reversing it finding the same move with a different number of nodes
+            let game_state = GameState::from_fen("7k/8/8/3q4/8/8/8/3R3K w - - 0 1").unwrap();
+            let mut moves = crate::piece::moves::legal_move_infos(game_state);
+            DefaultOrderer.order(&mut moves, &OrderContext {
+                game_state,
+                countermove: None,
+                current_depth: 0,
+            });
+            let expected: Vec<[i8; 2]> = order_moves(None, game_state).iter().flatten().map(|branch_value| branch_value.move_coordinates).collect();
+            assert_eq!(moves.iter().map(|move_info| move_info.chess_move.move_coordinates).collect::<Vec<[i8; 2]>>(), expected);
+
+            let mut default_search = Search::builder().depth(3).build();
+            let mut reverse_search = Search::builder().depth(3).orderer(ReverseOrderer).build();
+            let default_result = default_search.go(game_state).unwrap();
+            let reverse_result = reverse_search.go(game_state).unwrap();
+
+            assert_eq!(default_result.move_coordinates, [3, 4]);
+            assert_eq!(reverse_result.piece_coordinates, default_result.piece_coordinates);
+            assert_eq!(reverse_result.move_coordinates, default_result.move_coordinates);
+            assert_eq!(reverse_result.value, default_result.value);
+            assert_ne!(reverse_search.stats.nodes, default_search.stats.nodes);
+        }
+
+        #[test]
+        fn stalemate_orderer_test() { // Test a stalemate being ordered first when down material and last when up material
+            use crate::piece::moves::stalemates;
+
+            let order = |game_state: GameState| -> Vec<Move> {
+                let mut moves = crate::piece::moves::legal_move_infos(game_state);
+                StalemateOrderer.order(&mut moves, &OrderContext {
+                    game_state,
+                    countermove: None,
+                    current_depth: 0,
+                });
+                moves.iter().map(|move_info| move_info.chess_move).collect()
+            };
+
+            // Black is a pawn up, but every black pawn is blocked, so g6 leaves black without a move
+            let stalemate = Move {
+                piece_coordinates: [6, 4],
+                move_coordinates: [6, 5],
+            };
+            let game_state = GameState::from_fen("7k/5K2/8/2p1p1P1/2p1p3/p1P1P3/P7/8 w - - 0 1").unwrap();
+            assert!(stalemates(stalemate, game_state));
+            assert_eq!(order(game_state).iter().filter(|chess_move| stalemates(**chess_move, game_state)).count(), 1);
+            assert_eq!(order(game_state)[0], stalemate);
+
+            let mut default_moves = crate::piece::moves::legal_move_infos(game_state);
+            DefaultOrderer.order(&mut default_moves, &OrderContext {
+                game_state,
+                countermove: None,
+                current_depth: 0,
+            });
+            assert_ne!(default_moves[0].chess_move, stalemate);
+
+            // With an extra rook white is winning, so the stalemate is tried last
+            let game_state = GameState::from_fen("7k/5K2/8/2p1p1P1/2p1p3/p1P1P3/P7/R7 w - - 0 1").unwrap();
+            assert_eq!(*order(game_state).last().unwrap(), stalemate);
+        }
+
+        #[test]
+        fn go_restricted_test() { // Test restricting the root to a pawn push instead of taking the queen, and the restricted result not being reused by an unrestricted search
+            let game_state = GameState::from_fen("4k3/8/8/3q4/8/1P6/8/3RK3 w - - 0 1").unwrap();
+            let pawn_push = Move {
+                piece_coordinates: [1, 2],
+                move_coordinates: [1, 3],
+            };
+
+            let mut search = Search::builder().depth(3).build();
+            let restricted = search.go_restricted(game_state, &[pawn_push]).unwrap();
+            assert_eq!(restricted.piece_coordinates, pawn_push.piece_coordinates);
+            assert_eq!(restricted.move_coordinates, pawn_push.move_coordinates);
+
+            // The value is what searching the reply would give
+            let game_state_new = crate::uci::apply_moves(game_state, &["b3b4"]).unwrap();
+            let reply = Search::builder().depth(2).build().go(game_state_new).unwrap();
+            assert_eq!(restricted.value, -reply.value);
+
+            let unrestricted = search.go(game_state).unwrap();
+            assert_eq!(unrestricted.move_coordinates, [3, 4]);
+            assert!(unrestricted.value > restricted.value);
+
+            // Nothing is searched when no restricted move is legal
+            let illegal = Move {
+                piece_coordinates: [1, 2],
+                move_coordinates: [1, 4],
+            };
+            assert_eq!(search.go_restricted(game_state, &[]), None);
+            assert_eq!(search.go_restricted(game_state, &[illegal]), None);
+        }
+
+        #[test]
+        fn order_moves_countermove_test() { // Test the countermove being ordered ahead of other quiet moves
+            let game_state = GameState::from_board(fen::decode("2n5/7n/8/6R1/8/8/8/2Q3R1"), true).unwrap();
+
+            let previous_move = Move {
+                piece_coordinates: [1, 0],
+                move_coordinates: [2, 2],
+            };
+
+            let countermove = Move {
+                piece_coordinates: [6, 0],
+                move_coordinates: [6, 1],
+            };
+
+            // Prime the countermove table
+            let mut countermove_table: HashMap<Move, Move> = HashMap::new();
+            countermove_table.insert(previous_move, countermove);
+
+            // The queen taking the knight is the only capture, so the countermove comes straight after it
+            let index = |stored: Option<Move>| -> Option<usize> {
+                order_moves(stored, game_state).iter().flatten().position(|branch_value| {
+                    branch_value.piece_coordinates == countermove.piece_coordinates && branch_value.move_coordinates == countermove.move_coordinates
+                })
+            };
+            let result = order_moves(countermove_table.get(&previous_move).copied(), game_state);
+            assert!(result[0].unwrap().value > 0);
+            assert_eq!(index(countermove_table.get(&previous_move).copied()), Some(1));
+            assert!(result[2].unwrap().value <= 0);
+
+            // Without the countermove it is ordered with the other quiet moves
+            assert!(index(None).unwrap() > 1);
+        }
+    }
+}
+
+// Module for counting move paths, used to check move generation and measure performance
+pub mod perft {
+    use crate::board::turn::new_turn;
+    use crate::board::turn::GameState;
+    use crate::piece::moves::is_promotion;
+    use crate::piece::moves::legal_moves;
+    use crate::piece::moves::Move;
+
+    // Returns the number of leaf nodes reachable from game_state after depth moves
+    pub fn perft(game_state: GameState, depth: usize) -> u64 {
+        perft_bounded(game_state, depth, u64::MAX).0
+    }
+
+    // Same as perft, but stops counting once max_nodes leaf nodes have been reached
+    // The returned bool is true when the limit was hit and the count is partial
+    pub fn perft_bounded(game_state: GameState, depth: usize, max_nodes: u64) -> (u64, bool) {
+        let mut nodes: u64 = 0;
+        let hit_limit = count_nodes(game_state, depth, max_nodes, &mut nodes);
+        (nodes, hit_limit)
+    }
+
+    // Same as perft, but the subtrees of the root moves are counted on separate threads
+    // Perft has no shared state, so every subtree is counted independently and the counts are added up
+    // Only built with the parallel feature, which pulls in rayon
+    #[cfg(feature = "parallel")]
+    pub fn perft_parallel(game_state: GameState, depth: usize) -> u64 {
+        use rayon::prelude::*;
+
+        if depth <= 1 {
+            return perft(game_state, depth);
+        }
+
+        legal_moves(game_state).par_iter().map(|legal_move| {
+            promotions(*legal_move, game_state).iter().map(|promotion_id| {
+                match new_turn(legal_move.piece_coordinates, legal_move.move_coordinates, *promotion_id, game_state) {
+                    Ok(game_state_new) => perft(game_state_new, depth - 1),
+                    Err(_) => 0,
+                }
+            }).sum::<u64>()
+        }).sum()
+    }
+
+    // Returns the pieces a move is counted with, every allowed promotion for a pawn reaching the last row, otherwise just the default promotion (which new_turn ignores)
+    fn promotions(chess_move: Move, game_state: GameState) -> Vec<i8> {
+        if is_promotion(chess_move, game_state) {
+            let promotion_ids = game_state.promotion_ids();
+            if !promotion_ids.is_empty() {
+                return promotion_ids;
+            }
+        }
+        vec![game_state.default_promotion()]
+    }
+
+    // Adds the leaf nodes under game_state to nodes, returns true if max_nodes was hit
+    fn count_nodes(game_state: GameState, depth: usize, max_nodes: u64, nodes: &mut u64) -> bool {
+        if depth == 0 {
+            return add_nodes(1, max_nodes, nodes);
+        }
+
+        // Every legal move is a leaf node at depth 1 (once for each promotion piece), so there is no need to make the moves
+        if depth == 1 {
+            let pawn_moves = crate::piece::moves::legal_moves_masked(game_state, Some(&[crate::piece::info::IDS[0]]));
+            let extra_promotions: usize = pawn_moves.iter().map(|pawn_move| promotions(*pawn_move, game_state).len() - 1).sum();
+            let count = crate::piece::moves::legal_move_count(game_state) + extra_promotions;
+            return add_nodes(count.try_into().unwrap(), max_nodes, nodes);
+        }
+
+        for legal_move in legal_moves(game_state) {
+            for promotion_id in promotions(legal_move, game_state) {
+                let game_state_new = new_turn(legal_move.piece_coordinates, legal_move.move_coordinates, promotion_id, game_state);
+
+                // Checkmates and stalemates are returned as errors, they have no child nodes
+                if let Ok(game_state_new) = game_state_new {
+                    if count_nodes(game_state_new, depth - 1, max_nodes, nodes) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // Adds new_nodes to nodes without going over max_nodes, returns true if max_nodes was hit
+    fn add_nodes(new_nodes: u64, max_nodes: u64, nodes: &mut u64) -> bool {
+        if *nodes + new_nodes > max_nodes {
+            *nodes = max_nodes;
+            return true;
+        }
+        *nodes += new_nodes;
+        false
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn perft_test() {
+            assert_eq!(perft(GameState::new(), 2), 400);
+        }
+
+        #[test]
+        #[cfg(feature = "parallel")]
+        fn perft_parallel_test() { // Test the parallel count matching the serial count, including a position with checkmates
+            let game_state = GameState::new();
+            let nodes = perft_parallel(game_state, 3);
+            assert_eq!(nodes, perft(game_state, 3));
+            assert_eq!(nodes, 8902);
+
+            let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+            assert_eq!(perft_parallel(game_state, 3), perft(game_state, 3));
+            assert_eq!(perft_parallel(game_state, 0), 1);
+        }
+
+        #[test]
+        fn perft_bounded_test() { // Test a tiny node limit stopping the count early
+            assert_eq!(perft_bounded(GameState::new(), 3, 10), (10, true));
+        }
+    }
+
+    // Perft counts from positions that stress castling rights and castle legality
+    // The first position is the standard castling perft position (26, 568, and 13744 nodes at depths 1 to 3)
+    // The other counts aren't published, they were checked against an independent pawnless perft written in python for these cases (kings, knights, bishops, rooks, and queens with full castling rules)
+    #[cfg(test)]
+    mod castling_tests {
+        use super::*;
+
+        const CASTLING_CASES: [(&str, usize, u64); 6] = [
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", 2, 568), // Rooks can be captured on their home squares
+            ("r3k2r/8/8/8/8/8/1b6/R3K2R b KQkq - 0 1", 2, 714), // The bishop can take the a1 rook
+            ("r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", 2, 1494), // Queens cover castling squares for both teams
+            ("4k3/8/8/2b5/8/8/8/R3K2R w KQ - 0 1", 2, 347), // Castling kingside would move into check
+            ("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1", 2, 363), // Castling kingside would move through check
+            ("1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", 2, 357), // An attacked b1 doesn't stop castling queenside
+        ];
+
+        #[test]
+        fn castling_perft_test() {
+            assert_eq!(crate::testing::run_perft_suite(&CASTLING_CASES), Ok(()));
+        }
+
+        #[test]
+        fn castling_rights_lost_perft_test() { // Test a king and a rook moving away and back losing their castle rights
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+            let game_state_new = crate::uci::apply_moves(game_state, &["e1e2", "e8e7", "e2e1", "e7e8"]).unwrap();
+            assert_eq!(game_state_new.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w - - 4 3");
+            assert_eq!(perft(game_state_new, 2), 482);
+
+            let game_state_new = crate::uci::apply_moves(game_state, &["h1h2", "a8a7", "h2h1", "a7a8"]).unwrap();
+            assert_eq!(game_state_new.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w Qk - 4 3");
+            assert_eq!(perft(game_state_new, 2), 525);
+        }
     }
-}
\ No newline at end of file
+}
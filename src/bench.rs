@@ -0,0 +1,32 @@
+use crate::board::BOARD_SIZE;
+use crate::board::turn::GameState;
+use crate::piece::moves::BoardInfo;
+use crate::algorithm::minimax::BranchValue;
+
+// Module of entry points for benchmark harnesses (benches/)
+// Functions do no I/O and return their results so the work can't be optimized away
+
+// Generates all white moves for board_info
+pub fn bench_gen_all_moves(board_info: BoardInfo) -> [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
+    crate::piece::moves::gen_all_moves(true, None, board_info)
+}
+
+// Searches game_state to depth using a fresh search, None for an illegal position
+pub fn bench_best_move(game_state: GameState, depth: usize) -> Option<BranchValue> {
+    crate::algorithm::minimax::Search::builder().depth(depth).build().go(game_state)
+}
+
+// Runs perft from the start position
+pub fn bench_perft(depth: usize) -> u64 {
+    crate::algorithm::perft::perft(GameState::new(), depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bench_perft_test() {
+        assert_eq!(bench_perft(1), 20);
+    }
+}
@@ -4,6 +4,145 @@ pub const MAX_SLIDES: usize = 8; // Maximum number of squares a piece should be
 
 // niighfg
 
+// Rule sets that change how turns are played
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Variant {
+    Standard,
+    Atomic, // Captures explode, destroying the capturing piece and all non pawn pieces around the capture square
+    Antichess, // Captures are forced, the king is a normal piece, and a team wins by losing all of its pieces or having no moves
+}
+
+// A square whose piece id changed between two boards
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SquareChange {
+    pub square: [i8; 2],
+    pub from: i8, // Piece id before
+    pub to: i8, // Piece id after
+}
+
+// Returns every square that changed between two boards, both boards have to be from the same perspective
+pub fn diff(before: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], after: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> Vec<SquareChange> {
+    let mut changes = Vec::new();
+
+    for x in 0..BOARD_SIZE[0] {
+        for y in 0..BOARD_SIZE[1] {
+            if before[x][y] != after[x][y] {
+                changes.push(SquareChange {
+                    square: crate::coordinates_from_usize([x, y]),
+                    from: before[x][y],
+                    to: after[x][y],
+                });
+            }
+        }
+    }
+    changes
+}
+
+// How far a game has progressed, judged by the non pawn material left on the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GamePhase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+pub const PHASE_MAX: i32 = 256; // Phase value with no non pawn material left
+const PHASE_WEIGHTS: [i32; 6] = [0, 2, 1, 1, 4, 0]; // Phase weight of each piece, indexed by id - 1
+const PHASE_START: i32 = 24; // Total phase weight of the starting position
+
+// Returns the game phase from 0 (all non pawn material left) to PHASE_MAX (only kings and pawns left)
+// Used to blend opening and endgame evaluations, promotions can't make the phase go below 0
+pub fn phase_value(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> i32 {
+    let mut weight = 0;
+    for id in board.iter().flatten() {
+        if let Some(piece_weight) = PHASE_WEIGHTS.get(usize::try_from(id.abs() - 1).unwrap_or(usize::MAX)) {
+            weight += piece_weight;
+        }
+    }
+
+    let weight = std::cmp::min(weight, PHASE_START);
+    (PHASE_START - weight) * PHASE_MAX / PHASE_START
+}
+
+// Returns the game phase of a board, boards from either perspective give the same phase
+pub fn game_phase(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> GamePhase {
+    let phase = phase_value(board);
+    if phase < PHASE_MAX / 4 {
+        GamePhase::Opening
+    } else if phase < PHASE_MAX * 3 / 4 {
+        GamePhase::Middlegame
+    } else {
+        GamePhase::Endgame
+    }
+}
+
+// Returns the colour swapped mirror of a position (ranks flipped and piece colours swapped, the other team is to move)
+// Because boards are stored from the perspective of the team to move, this is the same as mirroring the board files and swapping piece colours
+// Evaluations from whites perspective should be antisymmetric, eval(pos) == -eval(mirror(pos))
+pub fn mirror(game_state: turn::GameState) -> turn::GameState {
+    let mirror_coordinates = |coordinates: [i8; 2]| -> [i8; 2] {
+        [crate::coordinates_from_usize(BOARD_SIZE)[0] - 1 - coordinates[0], coordinates[1]]
+    };
+
+    let mut board = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+    let mut turns_board = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+    for x in 0..BOARD_SIZE[0] {
+        for y in 0..BOARD_SIZE[1] {
+            board[BOARD_SIZE[0] - 1 - x][y] = -game_state.board_info.board[x][y];
+            turns_board[BOARD_SIZE[0] - 1 - x][y] = game_state.board_info.turns_board[x][y];
+        }
+    }
+
+    // Captured piece ids change team along with the pieces on the board
+    let swap_points_info = |mut points_info: turn::PointsInfo| -> turn::PointsInfo {
+        for i in 0..points_info.captured_pieces.len() {
+            points_info.captured_pieces[i] *= -1;
+        }
+        points_info
+    };
+
+    let castling_rights = game_state.board_info.castling_rights;
+
+    let mut game_state_new = game_state;
+    game_state_new.white_points_info = swap_points_info(game_state.black_points_info);
+    game_state_new.black_points_info = swap_points_info(game_state.white_points_info);
+    game_state_new.board_info.board = board;
+    game_state_new.board_info.turns_board = turns_board;
+    game_state_new.board_info.castling_rights = [castling_rights[2], castling_rights[3], castling_rights[0], castling_rights[1]];
+    game_state_new.board_info.material = [game_state.board_info.material[1], game_state.board_info.material[0]];
+    game_state_new.board_info.last_turn_coordinates = mirror_coordinates(game_state.board_info.last_turn_coordinates);
+    game_state_new.board_info.capture_coordinates = game_state.board_info.capture_coordinates.map(mirror_coordinates);
+    game_state_new.whites_turn = !game_state.whites_turn;
+    game_state_new.captured = game_state.captured.map(|(id, coordinates)| (-id, mirror_coordinates(coordinates)));
+    game_state_new.board_info.en_passant = game_state.board_info.en_passant.map(mirror_coordinates);
+    game_state_new.history = turn::RepetitionHistory::new(); // Hashes of the unmirrored positions can't repeat
+    game_state_new
+}
+
+// Number of squares in a flat board
+pub const FLAT_SIZE: usize = BOARD_SIZE[0] * BOARD_SIZE[1];
+
+// Converts a board to a flat array in rank major order, a1 = 0, b1 = 1, ..., h1 = 7, a2 = 8, ..., h8 = 63
+// The board should be from whites perspective, board[x][y] is stored at index y * 8 + x
+pub fn to_flat(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> [i8; FLAT_SIZE] {
+    let mut flat = [0i8; FLAT_SIZE];
+    for x in 0..BOARD_SIZE[0] {
+        for y in 0..BOARD_SIZE[1] {
+            flat[y * BOARD_SIZE[0] + x] = board[x][y];
+        }
+    }
+    flat
+}
+
+// Converts a flat array made by to_flat back to a board
+pub fn from_flat(flat: [i8; FLAT_SIZE]) -> [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
+    let mut board = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+    for i in 0..FLAT_SIZE {
+        board[i % BOARD_SIZE[0]][i / BOARD_SIZE[0]] = flat[i];
+    }
+    board
+}
+
 pub mod errors {
     pub const CHECK_ERROR: i8 = 1;
     pub const CHECKMATE_ERROR: i8 = 2;
@@ -46,9 +185,12 @@ pub mod turn {
     use super::*;
     use crate::piece::moves::BoardInfo;
 
+    // Size of the captured pieces array, a team can capture every piece that fits on the board (E.g. the 36 white pawns of a horde position)
+    pub const MAX_CAPTURES: usize = BOARD_SIZE[0] * BOARD_SIZE[1];
+
     #[derive(Debug, Copy, Clone, PartialEq)]
     pub struct PointsInfo {
-        pub captured_pieces: [i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}], // Array of piece ids that have been captured
+        pub captured_pieces: [i8; MAX_CAPTURES], // Array of piece ids that have been captured
         pub captured_pieces_no: i8, // Number of pieces that have been captured
         pub points_total: i8, // Total points
         pub points_delta: i8, // Last points change
@@ -61,6 +203,389 @@ pub mod turn {
         pub points_delta: i8, // Points change last turn
         pub board_info: BoardInfo,
         pub whites_turn: bool,
+        pub variant: Variant,
+        pub captured: Option<(i8, [i8; 2])>, // Id and coordinates of the piece captured last turn, coordinates are from the perspective of the team that captured it
+        pub history: RepetitionHistory, // Hashes of recent positions, used to detect repetitions
+        pub halfmove_clock: u16, // Turns since the last capture or pawn move, used for the fifty move rule
+        pub fullmove_number: u16, // Starts at 1 and is incremented after blacks turn
+        pub allowed_promotions: [bool; crate::piece::info::MAX_PIECES], // Pieces a pawn can promote to, indexed by id - 1, custom pieces can be allowed too
+        pub gives_check: bool, // The last move put the team to move in check
+    }
+
+    // Pawns can promote to rooks, knights, bishops, and queens
+    pub const DEFAULT_PROMOTIONS: [bool; crate::piece::info::MAX_PIECES] = [false, true, true, true, true, false, false, false];
+
+    // How a game ended
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum GameResult {
+        Checkmate(bool), // Stores true if white won
+        Stalemate,
+        FiftyMoves,
+        Repetition,
+        InsufficientMaterial,
+        VariantWin(bool), // Won by the variants own rules (E.g. running out of pieces or moves in antichess), stores true if white won
+    }
+
+    // A piece captured by a move, coordinates are from the perspective of the team that captured it
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Capture {
+        pub id: i8,
+        pub coordinates: [i8; 2],
+    }
+
+    // Outcome of trying to make a move with GameState::try_move
+    // The GameState is boxed so the other outcomes don't take up its size
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum TurnOutcome {
+        Moved(Box<GameState>, Option<Capture>), // The game continues from the new GameState
+        GameOver(GameResult),
+        Illegal,
+    }
+
+    pub const HISTORY_SIZE: usize = 16; // Number of recent position hashes kept for repetition detection
+
+    // Ring buffer of recent position hashes
+    // Fixed size so GameState stays Copy and copying it stays cheap during a search
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct RepetitionHistory {
+        hashes: [u64; HISTORY_SIZE],
+        len: usize,
+        next: usize, // Index the next hash is written to, overwriting the oldest hash once the buffer is full
+    }
+
+    impl Default for RepetitionHistory {
+        fn default() -> Self {
+            RepetitionHistory::new()
+        }
+    }
+
+    impl RepetitionHistory {
+        pub fn new() -> Self {
+            RepetitionHistory {
+                hashes: [0u64; HISTORY_SIZE],
+                len: 0,
+                next: 0,
+            }
+        }
+
+        pub fn push(&mut self, hash: u64) {
+            self.hashes[self.next] = hash;
+            self.next = (self.next + 1) % HISTORY_SIZE;
+            if self.len < HISTORY_SIZE {
+                self.len += 1;
+            }
+        }
+
+        // Returns how many times the hash appears in the history
+        pub fn count(&self, hash: u64) -> usize {
+            self.hashes[..self.len].iter().filter(|h| **h == hash).count()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        // Removes every hash, used after an irreversible move
+        pub fn clear(&mut self) {
+            *self = RepetitionHistory::new();
+        }
+    }
+
+    impl Default for GameState {
+        fn default() -> Self {
+            GameState::new()
+        }
+    }
+
+    impl GameState {
+        // Returns the GameState for the start of a standard game
+        pub fn new() -> Self {
+            GameState::from_fen(crate::fen::START_FEN).unwrap()
+        }
+
+        // Returns a hash of the board, team to move, castling rights, and en passant square
+        // Unlike zobrist hashes it doesn't need bitstrings, so the same position always has the same hash
+        pub fn position_hash(&self) -> u64 {
+            use std::collections::hash_map::DefaultHasher;
+            use std::hash::Hash;
+            use std::hash::Hasher;
+
+            // The en passant square only makes the position different when a pawn of the team to move is beside the pawn that double moved
+            let mut pawn_id = crate::piece::info::IDS[0];
+            if !self.whites_turn {
+                pawn_id *= -1;
+            }
+            let board = self.board_info.board;
+            let en_passant = self.board_info.en_passant.filter(|square| {
+                [-1, 1].iter().any(|x| {
+                    let pawn_coordinates = [square[0] + x, square[1] - 1];
+                    crate::fits_in_board(pawn_coordinates) && crate::get_board(pawn_coordinates, board) == pawn_id
+                })
+            });
+
+            let mut hasher = DefaultHasher::new();
+            board.hash(&mut hasher);
+            self.whites_turn.hash(&mut hasher);
+            self.board_info.castling_rights.hash(&mut hasher);
+            en_passant.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        // Returns the zobrist hash of the position, the same hash the search uses for its transposition table
+        // Castling rights and a pawn that can capture en passant change the hash
+        pub fn zobrist_hash(&self, bitstrings_board: &[[std::collections::HashMap<i8, u64>; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> u64 {
+            crate::gen_zobrist_board_hash(self.whites_turn, self.board_info, bitstrings_board)
+        }
+
+        // Returns white material minus black material, counted with the piece values and not including kings
+        // Uses the running material, so promotions count even though they aren't in the captured pieces
+        pub fn material_delta(&self) -> i32 {
+            self.board_info.material(true) - self.board_info.material(false)
+        }
+
+        // Returns the most valuable piece in allowed_promotions (a queen in a standard game), or 0 if pawns can't promote
+        // Used when a move is made without choosing a promotion, E.g. by the search and perft
+        pub fn default_promotion(&self) -> i8 {
+            let pieces = self.board_info.pieces;
+            (0..crate::piece::info::MAX_PIECES)
+                .filter(|index| self.allowed_promotions[*index] && pieces[*index].id != 0)
+                .max_by_key(|index| pieces[*index].value)
+                .map_or(0, |index| pieces[index].id)
+        }
+
+        // Returns every piece in allowed_promotions, in id order
+        pub fn promotion_ids(&self) -> Vec<i8> {
+            let pieces = self.board_info.pieces;
+            (0..crate::piece::info::MAX_PIECES)
+                .filter(|index| self.allowed_promotions[*index] && pieces[*index].id != 0)
+                .map(|index| pieces[index].id)
+                .collect()
+        }
+
+        // Returns the result of the game if it has ended, otherwise None
+        // Checks checkmate, stalemate, the fifty move rule, threefold repetition, and insufficient material
+        // In antichess there is no check, a team with no pieces or no moves wins instead
+        pub fn status(&self) -> Option<GameResult> {
+            use crate::piece::moves::get_check_state;
+
+            if self.variant == Variant::Antichess {
+                if crate::piece::moves::legal_moves(*self).is_empty() {
+                    return Some(GameResult::VariantWin(self.whites_turn));
+                }
+            } else {
+                let check_state = get_check_state(self.whites_turn, true, self.board_info);
+                if check_state.mate {
+                    if check_state.check {
+                        return Some(GameResult::Checkmate(!self.whites_turn));
+                    }
+                    return Some(GameResult::Stalemate);
+                }
+            }
+
+            if self.halfmove_clock >= 100 {
+                return Some(GameResult::FiftyMoves);
+            }
+
+            // The current position is the third occurrence
+            if self.history.count(self.position_hash()) >= 2 {
+                return Some(GameResult::Repetition);
+            }
+
+            if self.variant != Variant::Antichess && insufficient_material(self.board_info.board) {
+                return Some(GameResult::InsufficientMaterial);
+            }
+            None
+        }
+
+        // Makes a move for the team to move, pawns reaching the last row promote to default_promotion
+        // Combines new_turn and status, so the move is either played, ends the game, or is illegal
+        pub fn try_move(&self, chess_move: crate::piece::moves::Move) -> TurnOutcome {
+            let game_state_new = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, self.default_promotion(), *self) {
+                Ok(game_state_new) => game_state_new,
+                Err(error) => {
+                    return match error.error_code {
+                        errors::CHECKMATE_ERROR => TurnOutcome::GameOver(GameResult::Checkmate(self.whites_turn)),
+                        errors::STALEMATE_ERROR => TurnOutcome::GameOver(GameResult::Stalemate),
+                        _ => TurnOutcome::Illegal,
+                    };
+                },
+            };
+
+            if let Some(result) = game_state_new.status() {
+                return TurnOutcome::GameOver(result);
+            }
+
+            let capture = game_state_new.captured.map(|(id, coordinates)| Capture {
+                id,
+                coordinates,
+            });
+            TurnOutcome::Moved(Box::new(game_state_new), capture)
+        }
+
+        // Returns a copy of the GameState with hash (the hash of this position) added to its repetition history
+        // Used by the search when moving to a child position
+        pub fn fork(&self, hash: u64) -> Self {
+            let mut game_state = *self;
+            game_state.history.push(hash);
+            game_state
+        }
+
+        // Creates a GameState from a fen string
+        // Castling rights and the en passant pawn are derived from the fen (see fen::derive_state_boards)
+        // Points are derived from material missing from the enemies starting army
+        pub fn from_fen(fen: &str) -> Result<Self, crate::fen::FenError> {
+            Self::from_fen_with_pieces(fen, crate::piece::info::Piece::instantiate_all())
+        }
+
+        // Same as from_fen, but decodes and plays with the given pieces (E.g. custom pieces from Piece::with_custom)
+        pub fn from_fen_with_pieces(fen: &str, pieces: [crate::piece::info::Piece; crate::piece::info::MAX_PIECES]) -> Result<Self, crate::fen::FenError> {
+            use crate::fen::FenError;
+
+            let fields: Vec<&str> = fen.split_whitespace().collect();
+            if fields.is_empty() {
+                return Err(FenError::Empty);
+            }
+
+            // Fields after the piece placement are optional
+            let side_field = crate::unwrap_def(fields.get(1).copied(), "w");
+            let halfmove_field = crate::unwrap_def(fields.get(4).copied(), "0");
+            let fullmove_field = crate::unwrap_def(fields.get(5).copied(), "1");
+
+            let halfmove_clock: u16 = halfmove_field.parse().map_err(|_| FenError::Clock)?;
+            let fullmove_number: u16 = fullmove_field.parse().map_err(|_| FenError::Clock)?;
+
+            let board = crate::fen::decode_with_pieces(fields[0], pieces);
+
+            let whites_turn = match side_field {
+                "w" => true,
+                "b" => false,
+                _ => return Err(FenError::SideToMove),
+            };
+
+            let crate::fen::StateBoards {turns_board, last_turn_coordinates, castling_rights} = crate::fen::derive_state_boards_with_pieces(fen, pieces)?;
+
+            // derive_state_boards has checked the en passant square is on a valid row, it is flipped with the board for black
+            let mut en_passant = None;
+            if let Some(en_passant_field) = fields.get(3).filter(|field| **field != "-") {
+                en_passant = Some(crate::ccn_to_cart(en_passant_field.chars().collect()).map_err(|_| FenError::EnPassant)?);
+            }
+
+            let mut game_state = GameState {
+                white_points_info: PointsInfo {
+                    captured_pieces: [0i8; MAX_CAPTURES],
+                    captured_pieces_no: 0,
+                    points_total: 0,
+                    points_delta: 0,
+                },
+
+                black_points_info: PointsInfo {
+                    captured_pieces: [0i8; MAX_CAPTURES],
+                    captured_pieces_no: 0,
+                    points_total: 0,
+                    points_delta: 0,
+                },
+
+                points_delta: 0,
+
+                board_info: BoardInfo {
+                    board,
+                    turns_board,
+                    last_turn_coordinates,
+                    capture_coordinates: None,
+                    error_code: 0,
+                    pieces,
+                    castling_rights,
+                    material: crate::piece::moves::count_material(board, pieces),
+                    en_passant,
+                },
+
+                whites_turn,
+                variant: Variant::Standard,
+                captured: None,
+                history: RepetitionHistory::new(),
+                halfmove_clock,
+                fullmove_number,
+                allowed_promotions: DEFAULT_PROMOTIONS,
+                gives_check: false,
+            };
+
+            // Each team has captured the material that is missing from the enemies starting army
+            let pieces = game_state.board_info.pieces;
+            let start_material: i8 = 8 * pieces[0].value + 2 * {pieces[1].value + pieces[2].value + pieces[3].value} + pieces[4].value;
+            let mut white_material = 0;
+            let mut black_material = 0;
+            for id in board.iter().flatten() {
+                if *id > 0 {
+                    white_material += pieces[usize::try_from(id - 1).unwrap()].value;
+                } else if *id < 0 {
+                    black_material += pieces[usize::try_from(-id - 1).unwrap()].value;
+                }
+            }
+            game_state.white_points_info.points_total = std::cmp::max(start_material - black_material, 0);
+            game_state.black_points_info.points_total = std::cmp::max(start_material - white_material, 0);
+
+            // The board is always from the perspective of the team whose turn it is
+            if !whites_turn {
+                game_state.board_info = crate::flip_board_info(game_state.board_info);
+            }
+
+            // Refuse positions where the team that just moved left its king in check
+            if !crate::piece::moves::position_is_legal(game_state) {
+                return Err(FenError::IllegalPosition);
+            }
+            game_state.gives_check = crate::piece::moves::get_check_state(whites_turn, false, game_state.board_info).check;
+
+            Ok(game_state)
+        }
+
+        // Creates a GameState from a board (from whites perspective) and the team to move, for positions built without a fen
+        // Nobody can castle and the clocks start at zero, everything else is derived the same way as from_fen
+        pub fn from_board(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], whites_turn: bool) -> Result<Self, crate::fen::FenError> {
+            let pieces = crate::piece::info::Piece::instantiate_all();
+
+            let mut side = "w";
+            if !whites_turn {
+                side = "b";
+            }
+            Self::from_fen_with_pieces(&format!("{} {} - - 0 1", crate::fen::encode(board, pieces), side), pieces)
+        }
+
+        // Returns the full six field fen of the position (placement, side to move, castling, en passant, halfmove clock, fullmove number)
+        pub fn to_fen(&self) -> String {
+            let mut board_info = self.board_info;
+            if !self.whites_turn {
+                board_info = crate::flip_board_info(board_info);
+            }
+
+            let mut side = "w";
+            if !self.whites_turn {
+                side = "b";
+            }
+
+            let mut castle_field: String = ['K', 'Q', 'k', 'q'].iter()
+                .zip(board_info.castling_rights.iter())
+                .filter(|(_, right)| **right)
+                .map(|(c, _)| *c)
+                .collect();
+            if castle_field.is_empty() {
+                castle_field = String::from("-");
+            }
+
+            let en_passant_field = match board_info.en_passant {
+                Some(target) => crate::cart_to_ccn(target).unwrap(),
+                None => String::from("-"),
+            };
+
+            format!(
+                "{} {} {} {} {} {}",
+                crate::fen::encode(board_info.board, board_info.pieces),
+                side,
+                castle_field,
+                en_passant_field,
+                self.halfmove_clock,
+                self.fullmove_number,
+            )
+        }
     }
 
     #[derive(Debug, Copy, Clone, PartialEq)]
@@ -69,6 +594,149 @@ pub mod turn {
         pub white_win: Option<bool>,
         pub error_code: i8,
         pub value: i8, // A value can be assigned when the game is over to help guide the minimax algorithm. E.g. a value of 127 for checkmate and 0 for stalemate
+        pub reason: Option<IllegalReason>, // Why the move was rejected, None when the game is over
+    }
+
+    // Rule that made a move illegal, used to give feedback on rejected moves
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum IllegalReason {
+        NoPiece, // There is no piece at the piece coordinates
+        WrongTeam, // The piece belongs to the team that isn't moving
+        InvalidPieceMove, // The piece can't move to the move coordinates
+        KingInCheck, // The move leaves the friendly king in check
+        CastleThroughCheck, // The king is in check, or passes through or lands on an attacked square while castling
+        InvalidPromotion, // The pawn can't promote to the promotion piece
+        ExplodesOwnKing, // An atomic capture would destroy the friendly king
+        CaptureRequired, // An antichess move isn't a capture while a capture is possible
+    }
+
+    // Returns true if a king move of two squares along its row is a pseudo legal castle blocked by an attacked square
+    // The castle right must still be held, the rook present and the squares between king and rook empty
+    fn castle_through_check(piece_coordinates: [i8; 2], move_coordinates: [i8; 2], board_info: BoardInfo) -> bool {
+        use crate::get_board;
+        use crate::piece::moves::{castle_right_index, is_attacked};
+
+        let id = get_board(piece_coordinates, board_info.board);
+        if id.abs() != crate::piece::info::IDS[5] || piece_coordinates[1] != move_coordinates[1] || (move_coordinates[0] - piece_coordinates[0]).abs() != 2 {
+            return false;
+        }
+
+        let direction = (move_coordinates[0] - piece_coordinates[0]).signum();
+        let rook_x = if direction > 0 { i8::try_from(BOARD_SIZE[0] - 1).unwrap() } else { 0 };
+        let rook_coordinates = [rook_x, piece_coordinates[1]];
+        let has_right = castle_right_index(crate::piece_white(id), rook_coordinates).is_some_and(|index| board_info.castling_rights[index]);
+        if !has_right || get_board(rook_coordinates, board_info.board) != id.signum() * crate::piece::info::IDS[1] {
+            return false;
+        }
+
+        let mut x = piece_coordinates[0] + direction;
+        while x != rook_x {
+            if get_board([x, piece_coordinates[1]], board_info.board) != 0 {
+                return false;
+            }
+            x += direction;
+        }
+
+        (0..3).any(|i| is_attacked([piece_coordinates[0] + direction * i, piece_coordinates[1]], !crate::piece_white(id), board_info.board))
+    }
+
+    // Returns true if neither team has enough pieces to checkmate
+    // Kings with at most one knight or bishop, or with only bishops that are all on the same square colour
+    pub fn insufficient_material(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> bool {
+        use crate::piece::info::IDS;
+
+        let mut minor_pieces = 0;
+        let mut bishop_colours = [false; 2];
+        let mut knights = false;
+
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let id = board[x][y].abs();
+                if id == 0 || id == IDS[5] {
+                    continue;
+                }
+
+                if id == IDS[2] {
+                    knights = true;
+                } else if id == IDS[3] {
+                    bishop_colours[(x + y) % 2] = true;
+                } else {
+                    return false; // Pawns, rooks, and queens can checkmate
+                }
+                minor_pieces += 1;
+            }
+        }
+
+        let both_bishop_colours = bishop_colours[0] && bishop_colours[1];
+        minor_pieces <= 1 || !(knights || both_bishop_colours)
+    }
+
+    // Returns true if a move is not a capture, or is a capture whose explosion doesn't destroy the moving teams king
+    // Kings cannot capture in atomic chess because they would explode
+    pub fn atomic_capture_safe(piece_coordinates: [i8; 2], move_coordinates: [i8; 2], board_info: BoardInfo) -> bool {
+        use crate::get_board;
+        use crate::piece::info::IDS;
+
+        let board = board_info.board;
+        let id = get_board(piece_coordinates, board);
+
+        // A pawn moving diagonally onto an empty square is capturing en passant
+        let en_passant = id.abs() == IDS[0] && move_coordinates[0] != piece_coordinates[0];
+        if get_board(move_coordinates, board) == 0 && !en_passant {
+            return true;
+        }
+
+        if id.abs() == IDS[5] {
+            return false;
+        }
+
+        match crate::find_id_in_board(IDS[5] * id.signum(), board) {
+            Some(king_coordinates) => {
+                (king_coordinates[0] - move_coordinates[0]).abs() > 1 || (king_coordinates[1] - move_coordinates[1]).abs() > 1
+            },
+            None => true,
+        }
+    }
+
+    // Removes the piece at explosion_coordinates and all non pawn pieces on the surrounding squares
+    // Returns the new board info and the ids of the removed pieces
+    pub fn atomic_explosion(explosion_coordinates: [i8; 2], mut board_info: BoardInfo) -> (BoardInfo, Vec<i8>) {
+        use crate::get_board;
+        use crate::set_board;
+        use crate::piece::info::IDS;
+
+        let mut exploded = Vec::new();
+
+        for x in -1..2 {
+            for y in -1..2 {
+                let coordinates = [explosion_coordinates[0] + x, explosion_coordinates[1] + y];
+                if !crate::fits_in_board(coordinates) {
+                    continue;
+                }
+
+                let id = get_board(coordinates, board_info.board);
+                if id == 0 || (id.abs() == IDS[0] && coordinates != explosion_coordinates) {
+                    continue;
+                }
+
+                board_info.board = set_board(coordinates, 0, board_info.board);
+                board_info.turns_board = set_board(coordinates, 0, board_info.turns_board);
+                exploded.push(id);
+            }
+        }
+
+        (board_info, exploded)
+    }
+
+    // Adds a captured piece to the captured pieces array and points total of points_info
+    fn add_captured_piece(captured_piece_id: i8, pieces: [crate::piece::info::Piece; crate::piece::info::MAX_PIECES], mut points_info: PointsInfo) -> PointsInfo {
+        let points_change = pieces[usize::try_from(captured_piece_id.abs() - 1).unwrap()].value;
+
+        points_info.captured_pieces[usize::try_from(points_info.captured_pieces_no).unwrap()] = captured_piece_id;
+        points_info.captured_pieces_no += 1;
+        points_info.points_total += points_change;
+
+        points_info
     }
 
     // Points info stored information on captured pieces, and points of the team that it belongs to
@@ -119,6 +787,35 @@ pub mod turn {
     move_coordinates: [i8; 2],
     promotion_id: i8,
     game_state: GameState) -> Result<GameState, Error> {
+        let (game_state_new, result) = play_turn(piece_coordinates, move_coordinates, promotion_id, game_state)?;
+
+        // Return an error if the move ends the game, the enemy king is checkmated (or exploded) or stalemated
+        match result {
+            None => Ok(game_state_new),
+            Some(GameResult::Stalemate) => Err(Error {
+                game_over: true,
+                white_win: None,
+                error_code: errors::STALEMATE_ERROR,
+                value: crate::piece::info::STALEMATE_VALUE,
+                reason: None,
+            }),
+            Some(_) => Err(Error {
+                game_over: true,
+                white_win: Some(game_state.whites_turn),
+                error_code: errors::CHECKMATE_ERROR,
+                value: crate::piece::info::CHECKMATE_VALUE,
+                reason: None,
+            }),
+        }
+    }
+
+    // Plays a move like new_turn, but a move that ends the game still returns the position after it
+    // The result is Checkmate when the enemy king is checkmated or exploded, or Stalemate, other draws are left to status
+    pub fn play_turn(
+    piece_coordinates: [i8; 2],
+    move_coordinates: [i8; 2],
+    promotion_id: i8,
+    game_state: GameState) -> Result<(GameState, Option<GameResult>), Error> {
         use crate::get_board;
         use crate::piece_white;
         use crate::piece::moves::gen_move_board;
@@ -126,6 +823,16 @@ pub mod turn {
         
         let board_info = game_state.board_info;
 
+        if get_board(piece_coordinates, board_info.board) == 0 {
+            return Err(Error {
+                game_over: false,
+                white_win: None,
+                error_code: errors::INVALID_MOVE_ERROR,
+                value: 0,
+                reason: Some(IllegalReason::NoPiece),
+            });
+        }
+
         // Return an error when a player tries to move a piece from the wrong team
         if piece_white(get_board(piece_coordinates, board_info.board)) != game_state.whites_turn {
             return Err(Error {
@@ -133,23 +840,134 @@ pub mod turn {
                 white_win: None,
                 error_code: errors::WRONG_TEAM_ERROR,
                 value: 0,
+                reason: Some(IllegalReason::WrongTeam),
+            });
+        }
+
+        // Promotions have to be to a piece in allowed_promotions
+        let pawn_id = get_board(piece_coordinates, board_info.board).abs() == crate::piece::info::IDS[0];
+        if pawn_id && crate::coordinates_to_usize(move_coordinates)[1] == BOARD_SIZE[1] - 1 {
+            let allowed = match usize::try_from(promotion_id - 1) {
+                Ok(index) => index < game_state.allowed_promotions.len() && game_state.allowed_promotions[index],
+                Err(_) => false,
+            };
+            if !allowed {
+                return Err(Error {
+                    game_over: false,
+                    white_win: None,
+                    error_code: errors::WRONG_PAWN_PROMOTE_ERROR,
+                    value: 0,
+                    reason: Some(IllegalReason::InvalidPromotion),
+                });
+            }
+        }
+
+        // Atomic captures cannot explode the moving teams king
+        if game_state.variant == Variant::Atomic && !atomic_capture_safe(piece_coordinates, move_coordinates, board_info) {
+            return Err(Error {
+                game_over: false,
+                white_win: None,
+                error_code: errors::INVALID_MOVE_ERROR,
+                value: 0,
+                reason: Some(IllegalReason::ExplodesOwnKing),
+            });
+        }
+
+        // Antichess captures are forced
+        let chess_move = crate::piece::moves::Move {
+            piece_coordinates,
+            move_coordinates,
+        };
+        if game_state.variant == Variant::Antichess && !crate::piece::moves::is_capture(chess_move, game_state) && crate::piece::moves::legal_moves(game_state).iter().any(|legal_move| crate::piece::moves::is_capture(*legal_move, game_state)) {
+            return Err(Error {
+                game_over: false,
+                white_win: None,
+                error_code: errors::INVALID_MOVE_ERROR,
+                value: 0,
+                reason: Some(IllegalReason::CaptureRequired),
             });
         }
 
-        // Gen move board
-        let board_info_new = gen_move_board(piece_coordinates, move_coordinates, promotion_id, board_info);
+        // Gen move board, the antichess king is a normal piece
+        let mut board_info_new = match game_state.variant {
+            Variant::Antichess => crate::piece::moves::gen_pseudo_move_board(piece_coordinates, move_coordinates, promotion_id, board_info),
+            _ => gen_move_board(piece_coordinates, move_coordinates, promotion_id, board_info),
+        };
 
         // Return error if there was an error in gen_move_board
         let error_code = board_info_new.error_code;
         if error_code != 0 {
+            let reason = match error_code {
+                errors::CHECK_ERROR => IllegalReason::KingInCheck,
+                errors::WRONG_PAWN_PROMOTE_ERROR => IllegalReason::InvalidPromotion,
+                _ if castle_through_check(piece_coordinates, move_coordinates, board_info) => IllegalReason::CastleThroughCheck,
+                _ => IllegalReason::InvalidPieceMove,
+            };
+
             return Err(Error {
                 game_over: false,
                 white_win: None,
                 error_code: error_code,
                 value: 0,
+                reason: Some(reason),
             });
         }
 
+        // The captured piece is at the move coordinates, unless it was captured en passant
+        let captured_coordinates = crate::unwrap_def(board_info_new.capture_coordinates, move_coordinates);
+        let captured_id = get_board(captured_coordinates, board_info.board);
+        let mut captured = None;
+        if captured_id != 0 && !crate::friendly_piece(captured_id, get_board(piece_coordinates, board_info.board)) {
+            captured = Some((captured_id, captured_coordinates));
+        }
+
+        // Update the running material of both teams for the capture and any promotion
+        let value = |id: i8| -> i32 {
+            i32::from(board_info.pieces[usize::try_from(id.abs() - 1).unwrap()].value)
+        };
+        let team_index = |id: i8| -> usize {
+            if crate::piece_white(id) {
+                return 0;
+            }
+            1
+        };
+        if let Some((captured_id, _)) = captured {
+            board_info_new.material[team_index(captured_id)] -= value(captured_id);
+        }
+        let moved_id = get_board(piece_coordinates, board_info.board);
+        let promoted_id = get_board(move_coordinates, board_info_new.board);
+        if promoted_id != moved_id {
+            board_info_new.material[team_index(moved_id)] += value(promoted_id) - value(moved_id);
+        }
+
+        // Atomic capture explosion
+        let mut result = None;
+        let mut exploded: Vec<i8> = Vec::new();
+        if game_state.variant == Variant::Atomic && captured.is_some() {
+            (board_info_new, exploded) = atomic_explosion(move_coordinates, board_info_new);
+
+            // Exploded rooks lose their castle rights
+            for x in -1..2 {
+                for y in -1..2 {
+                    let coordinates = [move_coordinates[0] + x, move_coordinates[1] + y];
+                    if let Some(index) = crate::piece::moves::castle_right_index(game_state.whites_turn, coordinates) {
+                        board_info_new.castling_rights[index] = false;
+                    }
+                }
+            }
+
+            // Destroying the enemy king wins the game
+            let enemy_king_id = -crate::piece::info::IDS[5] * get_board(piece_coordinates, board_info.board).signum();
+            if exploded.contains(&enemy_king_id) {
+                result = Some(GameResult::Checkmate(game_state.whites_turn));
+            }
+        }
+
+        // Exploded pieces are removed from the running material
+        for exploded_id in exploded.iter() {
+            board_info_new.material[team_index(*exploded_id)] -= value(*exploded_id);
+        }
+
         let mut game_state_new = game_state;
 
         // It will be the opposite teams move after this so flip board_info
@@ -157,26 +975,45 @@ pub mod turn {
 
         // Invert whites_turn bool to set the next turn to be the opposite team
         game_state_new.whites_turn = !game_state.whites_turn;
+        game_state_new.captured = captured;
+        game_state_new.history.push(game_state.position_hash());
+
+        // Captures and pawn moves reset the fifty move rule
+        let pawn_move = get_board(piece_coordinates, board_info.board).abs() == crate::piece::info::IDS[0];
+        game_state_new.halfmove_clock = game_state.halfmove_clock + 1;
+        if captured.is_some() || pawn_move {
+            game_state_new.halfmove_clock = 0;
+        }
 
-        // Return an error if the enemy king is checkmated or stalemated after the turn (because this signifies the end of the game)
-        let check_state = get_check_state(game_state_new.whites_turn, true, game_state_new.board_info);
+        // A pawn double move can be captured en passant on the square it passed, for the enemies next turn only
+        game_state_new.board_info.en_passant = None;
+        if pawn_move && move_coordinates[0] == piece_coordinates[0] && move_coordinates[1] - piece_coordinates[1] == 2 {
+            game_state_new.board_info.en_passant = Some(crate::flip_coordinates([move_coordinates[0], piece_coordinates[1] + 1]));
+        }
+
+        // Positions before an irreversible move (a capture, pawn move, or lost castle right) can't repeat
+        if captured.is_some() || pawn_move || board_info_new.castling_rights != board_info.castling_rights {
+            game_state_new.history.clear();
+        }
+        if !game_state.whites_turn {
+            game_state_new.fullmove_number = game_state.fullmove_number + 1;
+        }
+
+        // The game ends if the enemy king is checkmated or stalemated after the turn
+        // Antichess has no check, the end of the game is found by status, and an exploded king can't be checked
+        let mut check_state = crate::piece::moves::CheckType {
+            check: false,
+            mate: false,
+        };
+        if game_state.variant != Variant::Antichess && result.is_none() {
+            check_state = get_check_state(game_state_new.whites_turn, true, game_state_new.board_info);
+        }
+        game_state_new.gives_check = check_state.check;
         if check_state.mate {
-            
-            // Checkmate
             if check_state.check {
-                return Err(Error {
-                    game_over: true,
-                    white_win: Some(game_state.whites_turn),
-                    error_code: errors::CHECKMATE_ERROR,
-                    value: crate::piece::info::CHECKMATE_VALUE,
-                });
-            } else { // Stalemate
-                return Err(Error {
-                    game_over: true,
-                    white_win: None,
-                    error_code: errors::STALEMATE_ERROR,
-                    value: crate::piece::info::STALEMATE_VALUE,
-                });
+                result = Some(GameResult::Checkmate(game_state.whites_turn));
+            } else {
+                result = Some(GameResult::Stalemate);
             }
         }
 
@@ -188,17 +1025,39 @@ pub mod turn {
             points = game_state.black_points_info;
         }
 
-        let points_new = update_points_info(board_info, board_info_new, points);
+        let mut enemy_points;
+        if game_state.whites_turn {
+            enemy_points = game_state.black_points_info;
+        } else {
+            enemy_points = game_state.white_points_info;
+        }
+
+        let mut points_new = update_points_info(board_info, board_info_new, points);
+
+        // Exploded enemy pieces are captured by the moving team, exploded friendly pieces are captured by the enemy
+        let mut points_lost = 0;
+        for exploded_id in exploded {
+            let value = board_info.pieces[usize::try_from(exploded_id.abs() - 1).unwrap()].value;
+            if crate::piece_white(exploded_id) == game_state.whites_turn {
+                enemy_points = add_captured_piece(exploded_id, board_info.pieces, enemy_points);
+                points_lost += value;
+            } else {
+                points_new = add_captured_piece(exploded_id, board_info.pieces, points_new);
+                points_new.points_delta += value;
+            }
+        }
 
-        game_state_new.points_delta = points_new.points_delta;
+        game_state_new.points_delta = points_new.points_delta - points_lost;
         if game_state.whites_turn {
             game_state_new.white_points_info = points_new;
+            game_state_new.black_points_info = enemy_points;
         } else {
             game_state_new.black_points_info = points_new;
+            game_state_new.white_points_info = enemy_points;
         }
         
 
-        Ok(game_state_new)
+        Ok((game_state_new, result))
     }
 
     #[cfg(test)]
@@ -211,25 +1070,21 @@ pub mod turn {
         #[test]
         fn update_points_info_test1() { // Test update_points_info when a regular capture takes place
             let board_info_old = BoardInfo {
-                board: fen::decode("8/8/8/3b4/8/4N3/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0, 0],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: crate::piece::info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/3b4/8/4N3/8/8"), true).unwrap().board_info
             };
 
             let board_info_new = BoardInfo {
-                board: fen::decode("8/8/8/3N4/8/8/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [3, 4],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: crate::piece::info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/3N4/8/8/8/8"), true).unwrap().board_info
             };
 
             let mut points_info = PointsInfo {
-                captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                captured_pieces: [0i8; MAX_CAPTURES],
                 captured_pieces_no: 1,
                 points_total: 4,
                 points_delta: 0,
@@ -239,7 +1094,7 @@ pub mod turn {
             let result = update_points_info(board_info_old, board_info_new, points_info);
             
             let mut expected = PointsInfo {
-                captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                captured_pieces: [0i8; MAX_CAPTURES],
                 captured_pieces_no: 2,
                 points_total: 7,
                 points_delta: 3,
@@ -253,25 +1108,22 @@ pub mod turn {
         #[test]
         fn update_points_info_test2() { // Test update_points_info when a conditional capture takes place
             let board_info_old = BoardInfo {
-                board: fen::decode("8/8/8/4pP2/8/8/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0, 0],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: crate::piece::info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/4pP2/8/8/8/8"), true).unwrap().board_info
             };
 
             let board_info_new = BoardInfo {
-                board: fen::decode("8/8/4P3/8/8/8/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [4, 5],
                 capture_coordinates: Some([4, 4]),
-                error_code: 0,
-                pieces: crate::piece::info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/4P3/8/8/8/8/8"), true).unwrap().board_info
             };
 
             let mut points_info = PointsInfo {
-                captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                captured_pieces: [0i8; MAX_CAPTURES],
                 captured_pieces_no: 1,
                 points_total: 4,
                 points_delta: 0,
@@ -281,7 +1133,7 @@ pub mod turn {
             let result = update_points_info(board_info_old, board_info_new, points_info);
             
             let mut expected = PointsInfo {
-                captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                captured_pieces: [0i8; MAX_CAPTURES],
                 captured_pieces_no: 2,
                 points_total: 5,
                 points_delta: 1,
@@ -297,14 +1149,14 @@ pub mod turn {
         fn new_turn_tes1() { // Test a rook capturing a piece for a new move
             let game_state = GameState {
                 white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 0,
                     points_total: 11,
                     points_delta: 0,
                 },
 
                 black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 0,
                     points_total: 15,
                     points_delta: 0,
@@ -319,23 +1171,33 @@ pub mod turn {
                     capture_coordinates: None,
                     error_code: 0,
                     pieces: crate::piece::info::Piece::instantiate_all(),
+                    castling_rights: [true; 4],
+                    material: [9, 16],
+                    en_passant: None,
                 },
 
                 whites_turn: true,
+                variant: crate::board::Variant::Standard,
+                captured: None,
+                history: RepetitionHistory::new(),
+                halfmove_clock: 0,
+                fullmove_number: 1,
+                allowed_promotions: DEFAULT_PROMOTIONS,
+                gives_check: false,
             };
 
             let result = new_turn([2, 1], [2, 5], 0, game_state);
 
             let mut expected = GameState {
                 white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 1,
                     points_total: 20,
                     points_delta: 9,
                 },
 
                 black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 0,
                     points_total: 15,
                     points_delta: 0,
@@ -350,9 +1212,19 @@ pub mod turn {
                     capture_coordinates: None,
                     error_code: 0,
                     pieces: crate::piece::info::Piece::instantiate_all(),
+                    castling_rights: [true; 4],
+                    material: [9, 7],
+                    en_passant: None,
                 },
 
                 whites_turn: false,
+                variant: crate::board::Variant::Standard,
+                captured: Some((-5, [2, 5])),
+                history: RepetitionHistory::new(), // The capture clears the history
+                halfmove_clock: 0,
+                fullmove_number: 1,
+                allowed_promotions: DEFAULT_PROMOTIONS,
+                gives_check: false,
             };
             expected.white_points_info.captured_pieces[0] = -5;
 
@@ -366,34 +1238,7 @@ pub mod turn {
 
         #[test]
         fn new_turn_tes2() { // Test an error being returned for stalemate
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
-
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
-
-                points_delta: 0,
-
-                board_info: BoardInfo {
-                    board: fen::decode("k7/3Q4/8/8/8/8/8/8"),
-                    turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
-                },
-
-                whites_turn: true,
-            };
+            let game_state = GameState::from_board(fen::decode("k7/3Q4/8/8/8/8/8/8"), true).unwrap();
 
             let result = new_turn([3, 6], [2, 6], 0, game_state);
 
@@ -402,6 +1247,7 @@ pub mod turn {
                 white_win: None,
                 error_code: errors::STALEMATE_ERROR,
                 value: crate::piece::info::STALEMATE_VALUE,
+                reason: None,
             });
 
             assert_eq!(result, expected);
@@ -409,40 +1255,14 @@ pub mod turn {
 
         #[test]
         fn new_turn_test3() { // Test an invalid move error being returned
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
-
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
-
-                points_delta: 0,
-
-                board_info: BoardInfo {
-                    board: fen::decode("k7/r2r3p/8/8/8/3Q4/8/6K1"),
-                    turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
-                },
-
-                whites_turn: true,
-            };
+            let game_state = GameState::from_board(fen::decode("k7/r2r3p/8/8/8/3Q4/8/6K1"), true).unwrap();
 
             let expected = Err(Error {
                 game_over: false,
                 white_win: None,
                 error_code: errors::INVALID_MOVE_ERROR,
                 value: 0,
+                reason: Some(IllegalReason::NoPiece),
             });
 
             let result = new_turn([7, 3], [2, 6], 0, game_state);
@@ -452,34 +1272,7 @@ pub mod turn {
         #[test]
         fn new_turn_test4() { // Test use case where new_turn gets used consecutively for making turns
             use crate::flip_coordinates;
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
-
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
-
-                points_delta: 0,
-
-                board_info: BoardInfo {
-                    board: fen::decode("k7/r2r4/6p1/8/8/3Q4/8/6K1"),
-                    turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
-                },
-
-                whites_turn: true,
-            };
+            let game_state = GameState::from_board(fen::decode("k7/r2r4/6p1/8/8/3Q4/8/6K1"), true).unwrap();
 
             let turn_white = new_turn([3, 2], [3, 6], 0, game_state).unwrap();
             let turn_black = new_turn(flip_coordinates([0, 6]), flip_coordinates([3, 6]), 0, turn_white).unwrap();
@@ -491,14 +1284,14 @@ pub mod turn {
         fn new_turn_tes5() { // Test a rook capturing a piece for a new move
             let game_state = GameState {
                 white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 0,
                     points_total: 11,
                     points_delta: 0,
                 },
 
                 black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 0,
                     points_total: 15,
                     points_delta: 0,
@@ -513,23 +1306,33 @@ pub mod turn {
                     capture_coordinates: None,
                     error_code: 0,
                     pieces: crate::piece::info::Piece::instantiate_all(),
+                    castling_rights: [true; 4],
+                    material: [5, 8],
+                    en_passant: None,
                 },
 
                 whites_turn: false,
+                variant: crate::board::Variant::Standard,
+                captured: None,
+                history: RepetitionHistory::new(),
+                halfmove_clock: 0,
+                fullmove_number: 1,
+                allowed_promotions: DEFAULT_PROMOTIONS,
+                gives_check: false,
             };
 
             let result = new_turn([0, 2], [3, 2], 0, game_state);
 
             let mut expected = GameState {
                 white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 0,
                     points_total: 11,
                     points_delta: 0,
                 },
 
                 black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+                    captured_pieces: [0i8; MAX_CAPTURES],
                     captured_pieces_no: 1,
                     points_total: 20,
                     points_delta: 5,
@@ -544,9 +1347,19 @@ pub mod turn {
                     capture_coordinates: None,
                     error_code: 0,
                     pieces: crate::piece::info::Piece::instantiate_all(),
+                    castling_rights: [true; 4],
+                    material: [0, 8],
+                    en_passant: None,
                 },
 
                 whites_turn: true,
+                variant: crate::board::Variant::Standard,
+                captured: Some((2, [3, 2])),
+                history: RepetitionHistory::new(), // The capture clears the history
+                halfmove_clock: 0,
+                fullmove_number: 2,
+                allowed_promotions: DEFAULT_PROMOTIONS,
+                gives_check: false,
             };
             expected.black_points_info.captured_pieces[0] = 2;
 
@@ -559,45 +1372,591 @@ pub mod turn {
         }
 
         #[test]
-        fn new_turn_test6() { // Test a check error being returned when a king tries to move next to an enemy king
-            let game_state = GameState {
-                white_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+        fn from_fen_castle_test() { // Test the fen castle field deciding if the king can castle
+            use crate::piece::moves::legal_moves;
+            use crate::piece::moves::Move;
 
-                black_points_info: PointsInfo {
-                    captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
-                    captured_pieces_no: 0,
-                    points_total: 0,
-                    points_delta: 0,
-                },
+            let white_castle = Move {
+                piece_coordinates: [4, 0],
+                move_coordinates: [6, 0],
+            };
 
-                points_delta: 0,
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+            assert!(legal_moves(game_state).contains(&white_castle));
 
-                board_info: BoardInfo {
-                    board: fen::decode("8/8/8/3k4/8/2K5/8/8"),
-                    turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
-                    last_turn_coordinates: [0, 0],
-                    capture_coordinates: None,
-                    error_code: 0,
-                    pieces: crate::piece::info::Piece::instantiate_all(),
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+            assert!(!legal_moves(game_state).contains(&white_castle));
+
+            // Black queen side castle, the board is flipped for blacks turn and flipped back after it
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+            let game_state_new = new_turn([3, 0], [5, 0], 0, game_state).unwrap();
+            assert_eq!(game_state_new.board_info.board, fen::decode("2kr3r/8/8/8/8/8/8/R3K2R"));
+
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQk - 0 1").unwrap();
+            assert_eq!(new_turn([3, 0], [5, 0], 0, game_state).unwrap_err().error_code, errors::INVALID_MOVE_ERROR);
+        }
+
+        #[test]
+        fn from_fen_error_test() { // Test each malformed fen field returning its error
+            use crate::fen::FenError;
+
+            assert_eq!(GameState::from_fen(" "), Err(FenError::Empty));
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"), Err(FenError::SideToMove));
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w - - a 1"), Err(FenError::Clock));
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w KX - 0 1"), Err(FenError::Castling));
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w - e4 0 1"), Err(FenError::EnPassant));
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1"), Err(FenError::IllegalPosition));
+        }
+
+        #[test]
+        fn from_board_test() { // Test a programmatic board matching the same fen without castling, and searching from it
+            use crate::algorithm::minimax::Search;
+            use crate::piece::moves::legal_moves;
+
+            let board = fen::decode("r3k2r/pppq1ppp/8/3p4/8/8/PPP2PPP/R3K2R");
+            let game_state = GameState::from_board(board, false).unwrap();
+            assert_eq!(game_state, GameState::from_fen("r3k2r/pppq1ppp/8/3p4/8/8/PPP2PPP/R3K2R b - - 0 1").unwrap());
+            assert_eq!(game_state.board_info.castling_rights, [false; 4]);
+            assert_eq!(game_state.board_info.material(false), game_state.board_info.material(true) + 10);
+
+            assert!(!legal_moves(game_state).is_empty());
+            let branch = Search::builder().depth(2).build().go(game_state).unwrap();
+            assert!(legal_moves(game_state).contains(&crate::piece::moves::Move {
+                piece_coordinates: branch.piece_coordinates,
+                move_coordinates: branch.move_coordinates,
+            }));
+
+            // The team that just moved can't be left in check
+            assert!(GameState::from_board(fen::decode("4k3/8/8/8/8/8/8/4R1K1"), false).is_ok());
+            assert_eq!(GameState::from_board(fen::decode("4k3/8/8/8/8/8/8/4R1K1"), true), Err(crate::fen::FenError::IllegalPosition));
+        }
+
+        #[test]
+        fn to_fen_test() { // Test fens with castling, en passant, and clock fields surviving from_fen and to_fen
+            let fens = [
+                crate::fen::START_FEN,
+                "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1",
+                "rnbqkbnr/pppp1ppp/8/8/4Pp2/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                "8/8/8/4k3/8/8/8/3QK3 b - - 12 40",
+            ];
+            for fen in fens {
+                assert_eq!(GameState::from_fen(fen).unwrap().to_fen(), fen);
+            }
+
+            // Played moves are tracked the same as loaded fields
+            let game_state = crate::uci::apply_moves(GameState::new(), &["e2e4", "c7c5", "g1f3"]).unwrap();
+            assert_eq!(game_state.to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+            let game_state = crate::uci::apply_moves(game_state, &["c5c4", "d2d4"]).unwrap();
+            assert_eq!(game_state.to_fen(), "rnbqkbnr/pp1ppppp/8/8/2pPP3/5N2/PPP2PPP/RNBQKB1R b KQkq d3 0 3");
+        }
+
+        #[test]
+        fn from_fen_en_passant_test() { // Test the fen en passant field allowing an en passant capture
+            let game_state = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+            let game_state_new = new_turn([4, 4], [3, 5], 0, game_state).unwrap();
+
+            assert_eq!(game_state_new.points_delta, 1);
+        }
+
+        #[test]
+        fn en_passant_expires_test() { // Test a fen en passant square being honoured, expiring after one move, and being set by a double move
+            use crate::piece::moves::legal_moves;
+            use crate::piece::moves::Move;
+
+            let capture = Move {
+                piece_coordinates: [4, 4],
+                move_coordinates: [3, 5],
+            };
+            let game_state = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+            assert_eq!(game_state.board_info.en_passant, Some([3, 5]));
+            assert!(legal_moves(game_state).contains(&capture));
+
+            // Both kings move and the capture has expired
+            let game_state_new = crate::uci::apply_moves(game_state, &["e1f1", "e8f8"]).unwrap();
+            assert_eq!(game_state_new.board_info.en_passant, None);
+            assert!(!legal_moves(game_state_new).contains(&capture));
+            assert!(new_turn(capture.piece_coordinates, capture.move_coordinates, 0, game_state_new).is_err());
+
+            // The en passant square decides, not the pawn that moved last
+            let mut game_state_new = game_state;
+            game_state_new.board_info.en_passant = None;
+            assert!(!legal_moves(game_state_new).contains(&capture));
+            assert!(!crate::piece::moves::is_legal(capture, game_state_new));
+            assert!(new_turn(capture.piece_coordinates, capture.move_coordinates, 0, game_state_new).is_err());
+
+            // A black double move sets the square from whites perspective
+            let game_state = crate::uci::apply_moves(GameState::from_fen("4k3/2p5/8/3P4/8/8/8/4K3 b - - 0 1").unwrap(), &["c7c5"]).unwrap();
+            assert_eq!(game_state.board_info.en_passant, Some([2, 5]));
+            assert_eq!(game_state.to_fen(), "4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 2");
+            assert!(crate::uci::apply_moves(game_state, &["d5c6"]).is_ok());
+        }
+
+        #[test]
+        fn new_turn_captured_test() { // Test the captured piece being reported at its own square for en passant
+            let game_state = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+            let game_state_new = new_turn([4, 4], [3, 5], 0, game_state).unwrap();
+            assert_eq!(game_state_new.captured, Some((-1, [3, 4])));
+
+            let game_state = GameState::from_fen("4k3/8/8/3r4/4P3/8/8/4K3 w - - 0 1").unwrap();
+            let game_state_new = new_turn([4, 3], [3, 4], 0, game_state).unwrap();
+            assert_eq!(game_state_new.captured, Some((-2, [3, 4])));
+
+            let game_state_new = new_turn([4, 0], [4, 1], 0, game_state).unwrap();
+            assert_eq!(game_state_new.captured, None);
+        }
+
+        #[test]
+        fn repetition_history_test() { // Test the history keeping only the most recent hashes when forking
+            let mut game_state = GameState::new();
+            for hash in 0..100u64 {
+                game_state = game_state.fork(hash);
+            }
+
+            assert_eq!(game_state.history.count(99), 1);
+            assert_eq!(game_state.history.count(100 - HISTORY_SIZE as u64), 1);
+            assert_eq!(game_state.history.count(99 - HISTORY_SIZE as u64), 0);
+        }
+
+        #[test]
+        fn position_hash_en_passant_test() { // Test an en passant capture making positions different, and an en passant square no pawn can use not mattering
+            let hash = |fen: &str| GameState::from_fen(fen).unwrap().position_hash();
+            assert_ne!(hash("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1"), hash("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1"));
+            assert_eq!(hash("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1"), hash("4k3/8/8/3p4/8/8/8/4K3 w - - 0 1"));
+            assert_ne!(hash("4k3/8/8/8/3Pp3/8/8/4K3 b - d3 0 1"), hash("4k3/8/8/8/3Pp3/8/8/4K3 b - - 0 1"));
+        }
+
+        #[test]
+        fn history_reset_test() { // Test an irreversible move clearing the history so earlier positions no longer count
+            let moves = ["g1f3", "g8f6", "f3g1", "f6g8"];
+            let game_state = crate::uci::apply_moves(GameState::new(), &moves).unwrap();
+            assert_eq!(game_state.history.count(GameState::new().position_hash()), 1);
+
+            let game_state = crate::uci::apply_moves(game_state, &["e2e4"]).unwrap();
+            assert!(game_state.history.is_empty());
+            assert_eq!(game_state.history.count(GameState::new().position_hash()), 0);
+
+            // Moving the king loses the castle rights
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+            let game_state = crate::uci::apply_moves(game_state, &["e1f1", "e8d8"]).unwrap();
+            assert_eq!(game_state.history.count(GameState::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap().position_hash()), 0);
+            assert!(!game_state.history.is_empty());
+        }
+
+        #[test]
+        fn status_test() { // Test each way a game can end
+            assert_eq!(GameState::new().status(), None);
+            assert_eq!(GameState::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap().status(), Some(GameResult::Stalemate));
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap().status(), Some(GameResult::FiftyMoves));
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap().status(), None);
+            assert_eq!(GameState::from_fen("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap().status(), Some(GameResult::InsufficientMaterial));
+
+            // The start position occurs for the third time
+            let moves = ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"];
+            let game_state = crate::uci::apply_moves(GameState::new(), &moves).unwrap();
+            assert_eq!(game_state.status(), Some(GameResult::Repetition));
+        }
+
+        #[test]
+        fn illegal_reason_test() { // Test each kind of illegal move giving its reason
+            let reason = |fen: &str, piece_coordinates: [i8; 2], move_coordinates: [i8; 2], promotion_id: i8| -> Option<IllegalReason> {
+                new_turn(piece_coordinates, move_coordinates, promotion_id, GameState::from_fen(fen).unwrap()).unwrap_err().reason
+            };
+            let start = crate::fen::START_FEN;
+
+            assert_eq!(reason(start, [4, 3], [4, 4], 0), Some(IllegalReason::NoPiece));
+            assert_eq!(reason(start, [4, 6], [4, 4], 0), Some(IllegalReason::WrongTeam));
+            assert_eq!(reason(start, [1, 0], [1, 2], 0), Some(IllegalReason::InvalidPieceMove));
+            assert_eq!(reason("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1", [4, 1], [3, 2], 0), Some(IllegalReason::KingInCheck));
+            assert_eq!(reason("5rk1/8/8/8/8/8/8/R3K2R w KQ - 0 1", [4, 0], [6, 0], 0), Some(IllegalReason::CastleThroughCheck));
+            assert_eq!(reason("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", [0, 6], [0, 7], crate::piece::info::IDS[5]), Some(IllegalReason::InvalidPromotion));
+
+            // Castles that are not pseudo legal are invalid moves, even when they would pass through an attacked square
+            assert_eq!(reason("5rk1/8/8/8/8/8/8/R3K2R w - - 0 1", [4, 0], [6, 0], 0), Some(IllegalReason::InvalidPieceMove)); // No castle right
+            assert_eq!(reason("5rk1/8/8/8/8/8/8/R3K1NR w KQ - 0 1", [4, 0], [6, 0], 0), Some(IllegalReason::InvalidPieceMove)); // Path blocked
+            assert_eq!(reason("5rk1/8/8/8/8/8/8/R3K3 w KQ - 0 1", [4, 0], [6, 0], 0), Some(IllegalReason::InvalidPieceMove)); // No rook
+            assert_eq!(reason("2r3k1/8/8/8/8/8/8/R3K2R w KQ - 0 1", [4, 0], [2, 0], 0), Some(IllegalReason::CastleThroughCheck)); // Queenside
+            assert_eq!(reason("r3k2r/8/8/8/8/8/8/5RK1 b kq - 0 1", [3, 0], [1, 0], 0), Some(IllegalReason::CastleThroughCheck)); // Black, flipped board
+        }
+
+        #[test]
+        fn antichess_test() { // Test forced captures, the king moving into an attack, and winning by being stalemated or losing every piece
+            use crate::piece::moves::legal_moves;
+            use crate::piece::moves::Move;
+
+            let antichess = |fen: &str| -> GameState {
+                let mut game_state = GameState::from_fen(fen).unwrap();
+                game_state.variant = Variant::Antichess;
+                game_state
+            };
+
+            let game_state = antichess("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+            let capture = Move {
+                piece_coordinates: [4, 3],
+                move_coordinates: [3, 4],
+            };
+            assert_eq!(legal_moves(game_state), vec![capture]);
+            assert_eq!(new_turn([4, 3], [4, 4], 0, game_state).unwrap_err().reason, Some(IllegalReason::CaptureRequired));
+            assert!(new_turn([4, 3], [3, 4], 0, game_state).is_ok());
+
+            // The king can move onto an attacked square
+            let game_state = antichess("4k3/8/8/8/8/8/r7/4K3 w - - 0 1");
+            assert!(new_turn([4, 0], [3, 1], 0, game_state).is_ok());
+
+            // Black has no moves after the king move, so black wins
+            let game_state = antichess("8/8/8/8/8/p7/P7/7K w - - 0 1");
+            let king_move = Move {
+                piece_coordinates: [7, 0],
+                move_coordinates: [6, 0],
+            };
+            assert_eq!(game_state.try_move(king_move), TurnOutcome::GameOver(GameResult::VariantWin(false)));
+
+            // The forced capture takes blacks last piece, so black wins
+            let game_state = antichess("8/8/8/8/8/8/p7/R7 w - - 0 1");
+            let game_state_new = new_turn([0, 0], [0, 1], 0, game_state).unwrap();
+            assert_eq!(game_state_new.status(), Some(GameResult::VariantWin(false)));
+        }
+
+        #[test]
+        fn gives_check_test() { // Test a checking move and a quiet move setting gives_check
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+            assert!(!game_state.gives_check);
+
+            let check = new_turn([0, 0], [0, 7], 0, game_state).unwrap();
+            assert!(check.gives_check);
+
+            let quiet = new_turn([0, 0], [0, 5], 0, game_state).unwrap();
+            assert!(!quiet.gives_check);
+
+            // A position loaded in check has gives_check set
+            assert!(GameState::from_fen("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1").unwrap().gives_check);
+        }
+
+        #[test]
+        fn try_move_test() { // Test a capture, a checkmating move, and an illegal move
+            use crate::piece::moves::Move;
+
+            let game_state = GameState::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+            let capture = Move {
+                piece_coordinates: [4, 3],
+                move_coordinates: [3, 4],
+            };
+            match game_state.try_move(capture) {
+                TurnOutcome::Moved(game_state_new, captured) => {
+                    assert!(!game_state_new.whites_turn);
+                    assert_eq!(captured, Some(Capture {
+                        id: -1,
+                        coordinates: [3, 4],
+                    }));
                 },
+                other => panic!("Expected a move, got {:?}", other),
+            }
 
-                whites_turn: true,
+            let illegal = Move {
+                piece_coordinates: [4, 3],
+                move_coordinates: [4, 5],
+            };
+            assert_eq!(game_state.try_move(illegal), TurnOutcome::Illegal);
+
+            let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+            let mate = Move {
+                piece_coordinates: [0, 0],
+                move_coordinates: [0, 7],
+            };
+            assert_eq!(game_state.try_move(mate), TurnOutcome::GameOver(GameResult::Checkmate(true)));
+        }
+
+        #[test]
+        fn running_material_test() { // Test the running material matching a board scan after captures and a promotion
+            use crate::piece::moves::count_material;
+
+            let game_state = GameState::from_fen("4k3/1P6/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+            assert_eq!(game_state.board_info.material, [2, 1]);
+
+            let moves = ["e4d5", "e8e7", "b7b8q", "e7f6", "b8b4", "f6e5", "b4b1", "e5d5"];
+            let game_state = crate::uci::apply_moves(game_state, &moves).unwrap();
+
+            let board_info = game_state.board_info;
+            assert_eq!(board_info.material, count_material(board_info.board, board_info.pieces));
+            assert_eq!(board_info.material(true), 9);
+            assert_eq!(board_info.material(false), 0);
+        }
+
+        #[test]
+        fn zobrist_hash_test() { // Test equal positions reached by different move orders hashing equal, and en passant changing the hash
+            let bitstrings_board = crate::gen_bistrings_board();
+
+            let a = crate::uci::apply_moves(GameState::new(), &["g1f3", "g8f6", "b1c3"]).unwrap();
+            let b = crate::uci::apply_moves(GameState::new(), &["b1c3", "g8f6", "g1f3"]).unwrap();
+            assert_eq!(a.zobrist_hash(&bitstrings_board), b.zobrist_hash(&bitstrings_board));
+            assert_ne!(a.zobrist_hash(&bitstrings_board), GameState::new().zobrist_hash(&bitstrings_board));
+
+            let en_passant = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+            let no_en_passant = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+            assert_ne!(en_passant.zobrist_hash(&bitstrings_board), no_en_passant.zobrist_hash(&bitstrings_board));
+        }
+
+        #[test]
+        fn material_delta_test() { // Test the material delta after a few captures matching a count from scratch, from either teams turn
+            use crate::piece::moves::count_material;
+
+            let moves = ["e2e4", "d7d5", "e4d5", "d8d5", "b1c3", "d5a2", "a1a2"];
+            let game_state = crate::uci::apply_moves(GameState::new(), &moves).unwrap();
+
+            let material = count_material(game_state.board_info.board, game_state.board_info.pieces);
+            assert_eq!(game_state.material_delta(), material[0] - material[1]);
+            assert_eq!(game_state.material_delta(), 8);
+
+            let game_state = crate::uci::apply_moves(game_state, &["b8c6"]).unwrap();
+            assert_eq!(game_state.material_delta(), 8);
+            assert_eq!(GameState::new().material_delta(), 0);
+        }
+
+        #[test]
+        fn allowed_promotions_test() { // Test promoting to a piece outside the allowed promotions being an error
+            use crate::piece::info::IDS;
+
+            let mut game_state = GameState::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+            game_state.allowed_promotions = [false, false, true, false, false, false, false, false];
+
+            let result = new_turn([1, 6], [1, 7], IDS[4], game_state);
+            assert_eq!(result.unwrap_err().error_code, errors::WRONG_PAWN_PROMOTE_ERROR);
+
+            let game_state_new = new_turn([1, 6], [1, 7], IDS[2], game_state).unwrap();
+            assert_eq!(crate::flip_board(game_state_new.board_info.board)[1][7], IDS[2]);
+
+            // Moves made without choosing a promotion use the allowed knight instead of a queen
+            assert_eq!(game_state.default_promotion(), IDS[2]);
+            assert_eq!(GameState::new().default_promotion(), IDS[4]);
+            let chess_move = crate::piece::moves::Move {
+                piece_coordinates: [1, 6],
+                move_coordinates: [1, 7],
+            };
+            assert!(matches!(game_state.try_move(chess_move), TurnOutcome::GameOver(GameResult::InsufficientMaterial))); // A king and knight can't checkmate
+            assert_eq!(crate::algorithm::perft::perft(game_state, 1), 6);
+            let best_move = crate::algorithm::minimax::Search::builder().depth(1).build().go(game_state).unwrap();
+            assert_eq!((best_move.piece_coordinates, best_move.move_coordinates), ([1, 6], [1, 7]));
+        }
+
+        #[test]
+        fn from_fen_pawn_double_move_test() { // Test pawns in a loaded position double moving from their starting row only
+            use crate::piece::moves::legal_moves;
+            use crate::piece::moves::Move;
+
+            let double_move = Move {
+                piece_coordinates: [4, 1],
+                move_coordinates: [4, 3],
+            };
+
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 10 30").unwrap();
+            assert!(legal_moves(game_state).contains(&double_move));
+
+            let game_state = GameState::from_fen("4k3/8/8/8/8/4P3/8/4K3 w - - 10 30").unwrap();
+            assert!(!legal_moves(game_state).iter().any(|m| m.move_coordinates == [4, 4]));
+
+            // Black double moves in a loaded position, then white captures en passant
+            let game_state = GameState::from_fen("4k3/3p4/8/4P3/8/8/8/4K3 b - - 0 1").unwrap();
+            let game_state = new_turn([4, 1], [4, 3], 0, game_state).unwrap();
+            let game_state = new_turn([4, 4], [3, 5], 0, game_state).unwrap();
+
+            assert_eq!(game_state.points_delta, 1);
+        }
+
+        #[test]
+        fn castling_rights_test() { // Test a rook moving away and back losing its castle right
+            use crate::piece::moves::legal_moves;
+            use crate::piece::moves::Move;
+
+            let king_side_castle = Move {
+                piece_coordinates: [4, 0],
+                move_coordinates: [6, 0],
+            };
+            let queen_side_castle = Move {
+                piece_coordinates: [4, 0],
+                move_coordinates: [2, 0],
             };
 
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+            let game_state = new_turn([7, 0], [7, 1], 0, game_state).unwrap();
+            let game_state = new_turn([7, 0], [7, 1], 0, game_state).unwrap();
+            let game_state = new_turn([7, 1], [7, 0], 0, game_state).unwrap();
+            let game_state = new_turn([7, 1], [7, 0], 0, game_state).unwrap();
+
+            assert_eq!(game_state.board_info.castling_rights, [false, true, true, false]);
+            assert!(!legal_moves(game_state).contains(&king_side_castle));
+            assert!(legal_moves(game_state).contains(&queen_side_castle));
+        }
+
+        #[test]
+        fn horde_test() { // Test a horde position (all pawn white army without a king) being playable
+            use crate::algorithm::minimax::Search;
+            use crate::piece::moves::legal_moves;
+            use crate::piece::moves::Move;
+
+            let game_state = GameState::from_fen("rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1").unwrap();
+            let moves = legal_moves(game_state);
+            assert_eq!(moves.len(), 8);
+
+            let mut search = Search::builder().depth(2).build();
+            let best_move = search.go(game_state).unwrap();
+            assert!(moves.contains(&Move {
+                piece_coordinates: best_move.piece_coordinates,
+                move_coordinates: best_move.move_coordinates,
+            }));
+
+            // Black can capture more pawns than a standard army has pieces
+            let mut game_state = GameState::from_fen("k7/8/8/8/8/8/1p6/P7 b - - 0 1").unwrap();
+            game_state.black_points_info.captured_pieces_no = 35;
+            game_state.white_points_info.captured_pieces_no = 35;
+            let game_state = new_turn([6, 6], [7, 7], crate::piece::info::IDS[4], game_state).unwrap();
+            assert_eq!(game_state.black_points_info.captured_pieces_no + game_state.white_points_info.captured_pieces_no, 71);
+
+            // Black can still be checkmated by the horde
+            let game_state = GameState::from_fen("7k/5P1p/5PPP/8/8/8/8/8 w - - 0 1").unwrap();
+            let result = new_turn([6, 5], [6, 6], 0, game_state);
+            assert_eq!(result.unwrap_err(), Error {
+                game_over: true,
+                white_win: Some(true),
+                error_code: errors::CHECKMATE_ERROR,
+                value: crate::piece::info::CHECKMATE_VALUE,
+                reason: None,
+            });
+        }
+
+        #[test]
+        fn atomic_explosion_test() { // Test an atomic capture removing the capturing piece and the non pawn pieces around it
+            let mut game_state = GameState::from_fen("4k3/8/2nrb3/3pp3/2N5/8/3R4/4K3 w - - 0 1").unwrap();
+            game_state.variant = Variant::Atomic;
+
+            let game_state = new_turn([3, 1], [3, 4], 0, game_state).unwrap();
+
+            assert_eq!(crate::flip_board(game_state.board_info.board), fen::decode("4k3/8/8/4p3/8/8/8/4K3"));
+            assert_eq!(game_state.points_delta, 4);
+            assert_eq!(game_state.white_points_info.captured_pieces_no, 4);
+            assert_eq!(game_state.black_points_info.captured_pieces_no, 2);
+        }
+
+        #[test]
+        fn atomic_king_test() { // Test exploding the enemy king winning, and captures next to the friendly king being illegal
+            use crate::piece::moves::legal_moves;
+            use crate::piece::moves::Move;
+
+            let mut game_state = GameState::from_fen("4k3/3p4/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+            game_state.variant = Variant::Atomic;
+            assert_eq!(new_turn([3, 0], [3, 6], 0, game_state).unwrap_err(), Error {
+                game_over: true,
+                white_win: Some(true),
+                error_code: errors::CHECKMATE_ERROR,
+                value: crate::piece::info::CHECKMATE_VALUE,
+                reason: None,
+            });
+
+            let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/3p4/3QK3 w - - 0 1").unwrap();
+            game_state.variant = Variant::Atomic;
+            assert_eq!(new_turn([3, 0], [3, 1], 0, game_state).unwrap_err().error_code, errors::INVALID_MOVE_ERROR);
+            assert!(!legal_moves(game_state).contains(&Move {
+                piece_coordinates: [3, 0],
+                move_coordinates: [3, 1],
+            }));
+        }
+
+        #[test]
+        fn new_turn_test6() { // Test a check error being returned when a king tries to move next to an enemy king
+            let game_state = GameState::from_board(fen::decode("8/8/8/3k4/8/2K5/8/8"), true).unwrap();
+
             let expected = Err(Error {
                 game_over: false,
                 white_win: None,
                 error_code: errors::CHECK_ERROR,
                 value: 0,
+                reason: Some(IllegalReason::KingInCheck),
             });
 
             let result = new_turn([2, 2], [2, 3], 0, game_state);
             assert_eq!(result, expected);
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_test() { // Test a castle changing the king and rook squares
+        let game_state = turn::GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let game_state_new = turn::new_turn([4, 0], [6, 0], 0, game_state).unwrap();
+
+        let before = game_state.board_info.board;
+        let after = crate::flip_board(game_state_new.board_info.board);
+
+        let expected = vec![
+            SquareChange { square: [4, 0], from: 6, to: 0 },
+            SquareChange { square: [5, 0], from: 0, to: 2 },
+            SquareChange { square: [6, 0], from: 0, to: 6 },
+            SquareChange { square: [7, 0], from: 2, to: 0 },
+        ];
+        assert_eq!(diff(before, after), expected);
+    }
+
+    #[test]
+    fn game_phase_test() { // Test the start position, a position with queens traded, and bare kings
+        let start = turn::GameState::new().board_info.board;
+        assert_eq!(phase_value(start), 0);
+        assert_eq!(game_phase(start), GamePhase::Opening);
+
+        let middlegame = crate::fen::decode("r1b1k2r/pp3ppp/2n5/8/8/2N5/PP3PPP/R1B1K2R");
+        assert_eq!(phase_value(middlegame), 128);
+        assert_eq!(game_phase(middlegame), GamePhase::Middlegame);
+
+        let bare_kings = crate::fen::decode("4k3/8/8/8/8/8/8/4K3");
+        assert_eq!(phase_value(bare_kings), PHASE_MAX);
+        assert_eq!(game_phase(bare_kings), GamePhase::Endgame);
+    }
+
+    #[test]
+    fn flat_test() { // Test a1 and h8 being the first and last squares, and converting back
+        let board = crate::fen::decode("7k/8/8/8/8/8/8/R3K3");
+        let flat = to_flat(board);
+
+        assert_eq!(flat[0], 2); // a1 rook
+        assert_eq!(flat[4], 6); // e1 king
+        assert_eq!(flat[63], -6); // h8 king
+        assert_eq!(from_flat(flat), board);
+
+        let start = turn::GameState::new().board_info.board;
+        assert_eq!(from_flat(to_flat(start)), start);
+    }
+
+    #[test]
+    fn mirror_test() { // Test mirroring the start position and evaluation antisymmetry under mirroring
+        use crate::algorithm::eval;
+
+        let start = turn::GameState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let start_black = turn::GameState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        assert_eq!(mirror(start).board_info.board, start_black.board_info.board);
+        assert_eq!(mirror(start).board_info.turns_board, start_black.board_info.turns_board);
+        assert_eq!(mirror(start).board_info.castling_rights, start_black.board_info.castling_rights);
+        assert_eq!(mirror(mirror(start)), start);
+
+        // Evaluations are from the perspective of the team to move, convert them to whites perspective
+        let white_eval = |game_state: turn::GameState| -> i32 {
+            let eval = eval::material(game_state) + i32::from(eval::hanging_score(game_state));
+            if game_state.whites_turn {
+                eval
+            } else {
+                -eval
+            }
+        };
+
+        let fens = [
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            "4k3/8/8/3q4/4N3/8/8/4K3 b - - 0 1",
+            "r3k2r/pp3ppp/8/3Bn3/8/2N5/PP3PPP/R3K2R w KQkq - 0 1",
+            "6k1/5ppp/8/8/8/8/1r6/R5K1 w - - 0 1",
+        ];
+        for fen in fens {
+            let game_state = turn::GameState::from_fen(fen).unwrap();
+            assert_eq!(white_eval(game_state), -white_eval(mirror(game_state)));
+        }
+    }
+}
@@ -2,9 +2,28 @@ use crate::piece;
 use crate::board::BOARD_SIZE;
 // Module for fen related functions
 
+// Fen string for the start of a standard game
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// Why a fen string couldn't be turned into a position
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FenError {
+    Empty, // The fen has no piece placement field
+    Castling, // The castling field has a character other than KQkq
+    EnPassant, // The en passant field isn't a square on the third or sixth row
+    SideToMove, // The side to move field isn't w or b
+    Clock, // The halfmove clock or fullmove number isn't a number
+    IllegalPosition, // The team that just moved left its king in check
+}
+
 // Decode a fen string into a board array
 // Converts chars like 'P' into a piece number identifier to be used in the board array
 pub fn decode(fen: &str) ->  [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
+    decode_with_pieces(fen, piece::info::Piece::instantiate_all())
+}
+
+// Decode a fen string into a board array, using the id_fen chars of the given pieces (E.g. custom pieces from Piece::with_custom)
+pub fn decode_with_pieces(fen: &str, pieces: [piece::info::Piece; piece::info::MAX_PIECES]) ->  [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
     let fen_vec: Vec<char> = fen.chars().collect();
     let mut board = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
 
@@ -31,7 +50,7 @@ pub fn decode(fen: &str) ->  [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
             if valid_skip {
                 x = x + usize::try_from(skip_num).unwrap();
             } else { // Otherwise convert the fen_char into a piece id and put it on the board
-                board[x][y] = piece::info::id_fen_to_id(fen_char, piece::info::Piece::instantiate_all());
+                board[x][y] = piece::info::id_fen_to_id(fen_char, pieces);
                 x = x + 1;
             }
         }
@@ -52,10 +71,172 @@ pub fn decode(fen: &str) ->  [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
     board
 }
 
+// Encode a board array (from whites perspective) into the piece placement field of a fen string
+pub fn encode(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], pieces: [piece::info::Piece; piece::info::MAX_PIECES]) -> String {
+    let mut fen = String::new();
+
+    for y in (0..BOARD_SIZE[1]).rev() {
+        let mut skip = 0;
+        for column in board.iter() {
+            let id = column[y];
+            if id == 0 {
+                skip += 1;
+                continue;
+            }
+
+            if skip > 0 {
+                fen.push_str(&skip.to_string());
+                skip = 0;
+            }
+
+            // Black pieces use the lowercase id_fen
+            let id_fen = pieces[usize::try_from(id.abs() - 1).unwrap()].id_fen;
+            if id > 0 {
+                fen.push(id_fen);
+            } else {
+                fen.push(id_fen.to_ascii_lowercase());
+            }
+        }
+
+        if skip > 0 {
+            fen.push_str(&skip.to_string());
+        }
+        if y > 0 {
+            fen.push('/');
+        }
+    }
+    fen
+}
+
+// Move state of a position that the piece placement of a fen doesn't show, from whites perspective
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StateBoards {
+    pub turns_board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+    pub last_turn_coordinates: [i8; 2],
+    pub castling_rights: [bool; 4],
+}
+
+// Derives the turns board, last turn coordinates, and castling rights of a full fen string, from whites perspective
+// Pawns on their starting row haven't moved so they can double move, every other piece has moved once
+// A castle right is only kept if the king and rook are on their starting squares, the en passant pawn becomes the last piece to move
+pub fn derive_state_boards(fen: &str) -> Result<StateBoards, FenError> {
+    derive_state_boards_with_pieces(fen, piece::info::Piece::instantiate_all())
+}
+
+// Same as derive_state_boards, but decodes the piece placement with the given pieces (E.g. custom pieces from Piece::with_custom)
+pub fn derive_state_boards_with_pieces(fen: &str, pieces: [piece::info::Piece; piece::info::MAX_PIECES]) -> Result<StateBoards, FenError> {
+    use piece::info::IDS;
+
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.is_empty() {
+        return Err(FenError::Empty);
+    }
+
+    // Fields after the piece placement are optional
+    let castle_field = crate::unwrap_def(fields.get(2).copied(), "-");
+    let en_passant_field = crate::unwrap_def(fields.get(3).copied(), "-");
+
+    let board = decode_with_pieces(fields[0], pieces);
+
+    // A pawn that double moves then has the single move en passant (condition_subj_moves) expects
+    let mut turns_board = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+    for x in 0..BOARD_SIZE[0] {
+        for y in 0..BOARD_SIZE[1] {
+            let id = board[x][y];
+
+            // Pawns on their starting row have not moved
+            if (id == IDS[0] && y == 1) || (id == -IDS[0] && y == BOARD_SIZE[1] - 2) {
+                continue;
+            }
+
+            if id != 0 {
+                turns_board[x][y] = 1;
+            }
+        }
+    }
+
+    let mut castling_rights = [false; 4];
+    let y_max = BOARD_SIZE[1] - 1;
+    if castle_field != "-" {
+        for c in castle_field.chars() {
+            let (king_coordinates, rook_coordinates, team, index) = match c {
+                'K' => ([4, 0], [7, 0], 1, 0),
+                'Q' => ([4, 0], [0, 0], 1, 1),
+                'k' => ([4, y_max], [7, y_max], -1, 2),
+                'q' => ([4, y_max], [0, y_max], -1, 3),
+                _ => return Err(FenError::Castling),
+            };
+
+            if board[king_coordinates[0]][king_coordinates[1]] == IDS[5] * team && board[rook_coordinates[0]][rook_coordinates[1]] == IDS[1] * team {
+                castling_rights[index] = true;
+            }
+        }
+    }
+
+    let mut last_turn_coordinates = [0, 0];
+    if en_passant_field != "-" {
+        let chars: Vec<char> = en_passant_field.chars().collect();
+        if chars.len() != 2 {
+            return Err(FenError::EnPassant);
+        }
+        let target = crate::ccn_to_cart(chars).map_err(|_| FenError::EnPassant)?;
+
+        // The pawn is one square past the target square
+        last_turn_coordinates = match target[1] {
+            2 => [target[0], 3],
+            5 => [target[0], 4],
+            _ => return Err(FenError::EnPassant),
+        };
+    }
+
+    Ok(StateBoards {
+        turns_board,
+        last_turn_coordinates,
+        castling_rights,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn fen_encode_test() { // Test encoding being the inverse of decoding
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R";
+        assert_eq!(encode(decode(fen), piece::info::Piece::instantiate_all()), fen);
+    }
+
+    #[test]
+    fn derive_state_boards_test() { // Test a fen with Kq only keeping those castle rights, home row pawns being unmoved, and the en passant pawn
+        let StateBoards {turns_board, last_turn_coordinates, castling_rights} = derive_state_boards("r3k2r/pppppppp/8/8/4P3/8/PPPP1PPP/R3K2R b Kq e3 0 1").unwrap();
+
+        assert_eq!(castling_rights, [true, false, false, true]);
+        assert_eq!(last_turn_coordinates, [4, 3]);
+        assert_eq!(turns_board[0][1], 0);
+        assert_eq!(turns_board[0][6], 0);
+        assert_eq!(turns_board[4][3], 1);
+        assert_eq!(turns_board[0][0], 1);
+        assert_eq!(turns_board[4][4], 0);
+
+        // A right is dropped when the rook isn't on its starting square
+        assert_eq!(derive_state_boards("1r2k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap().castling_rights, [true, true, true, false]);
+        assert_eq!(derive_state_boards("4k3/8/8/8/8/8/8/4K3 w X - 0 1"), Err(FenError::Castling));
+        assert_eq!(derive_state_boards("4k3/8/8/8/8/8/8/4K3 w - e4 0 1"), Err(FenError::EnPassant));
+        assert_eq!(derive_state_boards("4k3/8/8/8/8/8/8/4K3 w - z9 0 1"), Err(FenError::EnPassant));
+        assert_eq!(derive_state_boards("4k3/8/8/8/8/8/8/4K3 w - e 0 1"), Err(FenError::EnPassant));
+        assert_eq!(derive_state_boards(""), Err(FenError::Empty));
+
+        // Only the white king side and black queen side castles are legal
+        use crate::board::turn::GameState;
+        use crate::uci::apply_moves;
+        let white = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1").unwrap();
+        assert!(apply_moves(white, &["e1g1"]).is_ok());
+        assert!(apply_moves(white, &["e1c1"]).is_err());
+        let black = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1").unwrap();
+        assert!(apply_moves(black, &["e8c8"]).is_ok());
+        assert!(apply_moves(black, &["e8g8"]).is_err());
+    }
+
     #[test]
     fn fen_decode_test() {
         let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
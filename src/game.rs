@@ -0,0 +1,569 @@
+use crate::board::turn::GameResult;
+use crate::board::turn::GameState;
+use crate::board::BOARD_SIZE;
+use crate::piece::moves::Move;
+
+// Compact storage of games, used for storing large numbers of self play games
+// Layout: start fen length (2 bytes, little endian), start fen (ascii), then 2 bytes per move
+// Each move is packed into a little endian u16, the low 6 bits are the piece square, the next 6 bits the move square (square = x + y * 8), and the top 4 bits the promotion id
+// Squares are stored from the perspective of the team making the move, the same as Move
+
+// Packs coordinates into a square index
+fn pack_square(coordinates: [i8; 2]) -> u16 {
+    let coordinates = crate::coordinates_to_usize(coordinates);
+    u16::try_from(coordinates[0] + coordinates[1] * BOARD_SIZE[0]).unwrap()
+}
+
+// Unpacks a square index into coordinates
+fn unpack_square(square: u16) -> [i8; 2] {
+    let square = usize::from(square);
+    crate::coordinates_from_usize([square % BOARD_SIZE[0], square / BOARD_SIZE[0]])
+}
+
+// Encodes a start fen and the moves played from it, each with the promotion id it was played with
+// A move packs into a u16 as the piece square, the move square, then the promotion id in the top 4 bits
+pub fn encode_moves(start: &str, moves: &[(Move, i8)]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(2 + start.len() + moves.len() * 2);
+
+    bytes.extend_from_slice(&u16::try_from(start.len()).unwrap().to_le_bytes());
+    bytes.extend_from_slice(start.as_bytes());
+
+    for (chess_move, promotion_id) in moves {
+        let promotion = u16::try_from(promotion_id.abs()).unwrap() & 0xf;
+        let packed = pack_square(chess_move.piece_coordinates) | pack_square(chess_move.move_coordinates) << 6 | promotion << 12;
+        bytes.extend_from_slice(&packed.to_le_bytes());
+    }
+    bytes
+}
+
+// Why bytes couldn't be decoded by decode_moves
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Truncated, // The bytes end inside the start fen length, the start fen, or a move
+    Utf8, // The start fen isn't valid utf-8
+    Fen(crate::fen::FenError), // The start fen isn't a valid position
+    Promotion(usize), // The move at the index has a promotion id that isn't 0 or one of the allowed promotions of the start position
+}
+
+// Decodes bytes from encode_moves into the start position and the moves played from it with their promotion ids
+// The moves are not checked for legality
+pub fn decode_moves(bytes: &[u8]) -> Result<(GameState, Vec<(Move, i8)>), DecodeError> {
+    if bytes.len() < 2 {
+        return Err(DecodeError::Truncated);
+    }
+
+    let fen_len = usize::from(u16::from_le_bytes([bytes[0], bytes[1]]));
+    let moves_start = 2 + fen_len;
+    if bytes.len() < moves_start || !(bytes.len() - moves_start).is_multiple_of(2) {
+        return Err(DecodeError::Truncated);
+    }
+
+    let fen = std::str::from_utf8(&bytes[2..moves_start]).map_err(|_| DecodeError::Utf8)?;
+    let game_state = GameState::from_fen(fen).map_err(DecodeError::Fen)?;
+    let promotion_ids = game_state.promotion_ids();
+
+    let mut moves: Vec<(Move, i8)> = Vec::with_capacity((bytes.len() - moves_start) / 2);
+    for (i, packed) in bytes[moves_start..].chunks(2).enumerate() {
+        let packed = u16::from_le_bytes([packed[0], packed[1]]);
+        let promotion_id = i8::try_from(packed >> 12).unwrap();
+        if promotion_id != 0 && !promotion_ids.contains(&promotion_id) {
+            return Err(DecodeError::Promotion(i));
+        }
+
+        let chess_move = Move {
+            piece_coordinates: unpack_square(packed & 0x3f),
+            move_coordinates: unpack_square(packed >> 6 & 0x3f),
+        };
+        moves.push((chess_move, promotion_id));
+    }
+
+    Ok((game_state, moves))
+}
+
+// Why a move in a list of standard algebraic notation (san) moves couldn't be played
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SanErrorKind {
+    Invalid, // The move couldn't be parsed
+    NoMatch, // No legal move matches the move
+    Ambiguous, // More than one legal move matches the move
+    Turn(i8), // new_turn returned the error code, E.g. CHECKMATE_ERROR when the move ends the game
+}
+
+// Error for a san move in a move list
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SanError {
+    pub index: usize, // Index of the move in the move list
+    pub kind: SanErrorKind,
+}
+
+// Parses a san move (E.g. e4, Nbd7, exd5, O-O, e8=Q+) for the team to move into a move and a promotion id
+// Check, mate, and annotation symbols are ignored, pawns promote to queens unless a promotion piece is given
+pub fn parse_san(san: &str, game_state: GameState) -> Result<(Move, i8), SanErrorKind> {
+    use crate::piece::info::IDS;
+    use crate::piece::moves::legal_moves;
+
+    let white = game_state.whites_turn;
+    let board = game_state.board_info.board;
+    let pieces = game_state.board_info.pieces;
+
+    // Coordinates from whites perspective to the perspective of the team to move, and back
+    let perspective = |coordinates: [i8; 2]| -> [i8; 2] {
+        match white {
+            true => coordinates,
+            false => crate::flip_coordinates(coordinates),
+        }
+    };
+
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+    let legal = legal_moves(game_state);
+
+    // Castles are the kings two square move
+    if san == "O-O" || san == "O-O-O" || san == "0-0" || san == "0-0-0" {
+        let mut king_id = IDS[5];
+        if !white {
+            king_id *= -1;
+        }
+        let king = perspective(crate::find_id_in_board(king_id, board).ok_or(SanErrorKind::NoMatch)?);
+        let mut direction = 1;
+        if san.len() > 3 {
+            direction = -1;
+        }
+
+        let castle = Move {
+            piece_coordinates: perspective(king),
+            move_coordinates: perspective([king[0] + 2 * direction, king[1]]),
+        };
+        if !legal.contains(&castle) {
+            return Err(SanErrorKind::NoMatch);
+        }
+        return Ok((castle, IDS[4]));
+    }
+
+    let mut chars: Vec<char> = san.chars().collect();
+
+    // Promotion piece, with or without the =
+    let mut promotion_id = IDS[4];
+    if let Some(last) = chars.last().copied() {
+        if ['Q', 'R', 'B', 'N'].contains(&last) {
+            promotion_id = crate::piece::info::id_fen_to_id(last, pieces);
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+    }
+
+    // Moving piece, pawns have no letter
+    let mut piece_id = IDS[0];
+    if let Some(first) = chars.first().copied() {
+        if ['K', 'Q', 'R', 'B', 'N'].contains(&first) {
+            piece_id = crate::piece::info::id_fen_to_id(first, pieces);
+            chars.remove(0);
+        }
+    }
+
+    if chars.len() < 2 {
+        return Err(SanErrorKind::Invalid);
+    }
+    let move_coordinates = crate::ccn_to_cart(chars[chars.len() - 2..].to_vec()).map_err(|_| SanErrorKind::Invalid)?;
+
+    // Anything left is the capture symbol and a file and / or row to tell pieces apart
+    let mut from_x: Option<i8> = None;
+    let mut from_y: Option<i8> = None;
+    for c in chars[..chars.len() - 2].iter() {
+        match c {
+            'a'..='h' => from_x = Some(*c as i8 - 'a' as i8),
+            '1'..='8' => from_y = Some(*c as i8 - '1' as i8),
+            'x' => (),
+            _ => return Err(SanErrorKind::Invalid),
+        }
+    }
+
+    let matches: Vec<Move> = legal.into_iter().filter(|legal_move| {
+        let piece_coordinates = perspective(legal_move.piece_coordinates);
+        crate::get_board(legal_move.piece_coordinates, board).abs() == piece_id
+            && perspective(legal_move.move_coordinates) == move_coordinates
+            && from_x.is_none_or(|x| piece_coordinates[0] == x)
+            && from_y.is_none_or(|y| piece_coordinates[1] == y)
+    }).collect();
+
+    match matches.len() {
+        0 => Err(SanErrorKind::NoMatch),
+        1 => Ok((matches[0], promotion_id)),
+        _ => Err(SanErrorKind::Ambiguous),
+    }
+}
+
+// Writes a legal move for the team to move as san (E.g. e4, Nbd7, exd8=N+, O-O), the inverse of parse_san
+// promotion_id is only written when a pawn reaches the last row, a move that checkmates ends in # instead of +
+// Returns None if the move or its promotion_id is illegal for the team to move
+pub fn move_to_san(chess_move: Move, promotion_id: i8, game_state: GameState) -> Option<String> {
+    use crate::board::errors;
+    use crate::board::turn::new_turn;
+    use crate::get_board;
+    use crate::piece::info::IDS;
+    use crate::piece::moves::is_capture;
+    use crate::piece::moves::legal_moves;
+
+    // Only game ending errors are legal moves, the rest reject the move (E.g. an empty square or a bad promotion)
+    let suffix = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, promotion_id, game_state) {
+        Ok(game_state_new) if game_state_new.gives_check => Some('+'),
+        Ok(_) => None,
+        Err(error) if error.error_code == errors::CHECKMATE_ERROR => Some('#'),
+        Err(error) if error.game_over => None,
+        Err(_) => return None,
+    };
+
+    let board = game_state.board_info.board;
+    let pieces = game_state.board_info.pieces;
+
+    // Coordinates from the perspective of the team to move to whites perspective
+    let perspective = |coordinates: [i8; 2]| -> [i8; 2] {
+        match game_state.whites_turn {
+            true => coordinates,
+            false => crate::flip_coordinates(coordinates),
+        }
+    };
+    let piece_coordinates = perspective(chess_move.piece_coordinates);
+    let move_coordinates = perspective(chess_move.move_coordinates);
+    let id = get_board(chess_move.piece_coordinates, board);
+
+    let mut san = String::new();
+    if id.abs() == IDS[5] && (move_coordinates[0] - piece_coordinates[0]).abs() == 2 {
+        match move_coordinates[0] > piece_coordinates[0] {
+            true => san.push_str("O-O"),
+            false => san.push_str("O-O-O"),
+        }
+    } else {
+        let capture = is_capture(chess_move, game_state);
+        let square = crate::cart_to_ccn(piece_coordinates).unwrap();
+
+        if id.abs() == IDS[0] {
+            if capture {
+                san.push_str(&square[0..1]);
+            }
+        } else {
+            san.push(pieces[usize::try_from(id.abs() - 1).unwrap()].id_fen);
+
+            // Tell apart pieces of the same type that can move to the same square, by column, then by row, then by both
+            let others: Vec<[i8; 2]> = legal_moves(game_state).into_iter()
+                .filter(|legal_move| legal_move.move_coordinates == chess_move.move_coordinates && legal_move.piece_coordinates != chess_move.piece_coordinates && get_board(legal_move.piece_coordinates, board) == id)
+                .map(|legal_move| perspective(legal_move.piece_coordinates))
+                .collect();
+            if !others.is_empty() {
+                if others.iter().all(|other| other[0] != piece_coordinates[0]) {
+                    san.push_str(&square[0..1]);
+                } else if others.iter().all(|other| other[1] != piece_coordinates[1]) {
+                    san.push_str(&square[1..2]);
+                } else {
+                    san.push_str(&square);
+                }
+            }
+        }
+
+        if capture {
+            san.push('x');
+        }
+        san.push_str(&crate::cart_to_ccn(move_coordinates).unwrap());
+
+        if id.abs() == IDS[0] && crate::coordinates_to_usize(chess_move.move_coordinates)[1] == BOARD_SIZE[1] - 1 {
+            san.push('=');
+            san.push(pieces[usize::try_from(promotion_id.abs() - 1).unwrap()].id_fen);
+        }
+    }
+
+    if let Some(suffix) = suffix {
+        san.push(suffix);
+    }
+    Some(san)
+}
+
+// Applies a list of san moves in order, returning the final position
+pub fn apply_san(mut game_state: GameState, moves: &[&str]) -> Result<GameState, SanError> {
+    use crate::board::turn::new_turn;
+
+    for (i, san) in moves.iter().enumerate() {
+        let (chess_move, promotion_id) = parse_san(san, game_state).map_err(|kind| SanError {
+            index: i,
+            kind,
+        })?;
+
+        game_state = new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, promotion_id, game_state).map_err(|error| SanError {
+            index: i,
+            kind: SanErrorKind::Turn(error.error_code),
+        })?;
+    }
+    Ok(game_state)
+}
+
+// A game being played, keeps the position before every move so moves can be taken back
+// GameState is Copy and fixed size for the search, so the move history lives here instead
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    pub game_state: GameState, // Current position
+    undo: Vec<(GameState, Move)>, // Position before each move and the move made from it, oldest first
+}
+
+impl Game {
+    pub fn new(game_state: GameState) -> Self {
+        Game {
+            game_state,
+            undo: Vec::new(),
+        }
+    }
+
+    // Makes a move (from the perspective of the team to move), the new_turn error is returned and the position is kept if the move can't be played
+    // A move that ends the game is still played and added to the history, its result is returned
+    pub fn push_move(&mut self, chess_move: Move, promotion_id: i8) -> Result<Option<GameResult>, crate::board::turn::Error> {
+        if self.result().is_some() {
+            return Err(crate::board::turn::Error {
+                game_over: true,
+                white_win: None,
+                error_code: crate::board::errors::INVALID_MOVE_ERROR,
+                value: 0,
+                reason: None,
+            });
+        }
+
+        let (game_state_new, result) = crate::board::turn::play_turn(chess_move.piece_coordinates, chess_move.move_coordinates, promotion_id, self.game_state)?;
+        self.undo.push((self.game_state, chess_move));
+        self.game_state = game_state_new;
+        Ok(result.or_else(|| self.game_state.status()))
+    }
+
+    // Returns the result of the game if the last move ended it
+    pub fn result(&self) -> Option<GameResult> {
+        self.game_state.status()
+    }
+
+    // Takes back the last move, restoring the position before it exactly (castling rights, en passant, clocks, and repetition history)
+    // Returns the move taken back, or None when no moves have been made
+    pub fn pop_move(&mut self) -> Option<Move> {
+        let (game_state, chess_move) = self.undo.pop()?;
+        self.game_state = game_state;
+        Some(chess_move)
+    }
+
+    // Returns the moves made so far, oldest first
+    pub fn moves(&self) -> Vec<Move> {
+        self.undo.iter().map(|(_, chess_move)| *chess_move).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::turn::new_turn;
+    use crate::piece::info::IDS;
+    use crate::piece::moves::legal_moves;
+
+    #[test]
+    fn pop_move_test() { // Test taking back a castle, an en passant capture, and every other move returning to the exact start
+        let start = GameState::new();
+        let mut game = Game::new(start);
+        let sans = ["e4", "Nf6", "e5", "d5", "exd6", "e6", "Nf3", "Be7", "Bc4", "O-O", "O-O"];
+
+        let mut positions = vec![start];
+        for san in sans {
+            let (chess_move, promotion_id) = parse_san(san, game.game_state).unwrap();
+            game.push_move(chess_move, promotion_id).unwrap();
+            positions.push(game.game_state);
+        }
+        assert_eq!(game.game_state, apply_san(start, &sans).unwrap());
+        assert_eq!(game.moves().len(), sans.len());
+
+        let bitstrings_board = crate::gen_bistrings_board();
+        for i in (0..sans.len()).rev() {
+            assert!(game.pop_move().is_some());
+            assert_eq!(game.game_state, positions[i]);
+            assert_eq!(crate::gen_zobrist_board_hash(game.game_state.whites_turn, game.game_state.board_info, &bitstrings_board), crate::gen_zobrist_board_hash(positions[i].whites_turn, positions[i].board_info, &bitstrings_board));
+        }
+        assert_eq!(game.game_state, start);
+        assert_eq!(game.pop_move(), None);
+
+        // An illegal move leaves the game alone
+        let illegal = Move {
+            piece_coordinates: [4, 1],
+            move_coordinates: [4, 4],
+        };
+        assert!(game.push_move(illegal, IDS[4]).is_err());
+        assert_eq!(game, Game::new(start));
+    }
+
+    #[test]
+    fn push_move_game_over_test() { // Test a checkmating move being recorded and taken back, and no moves being played after it
+        let start = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut game = Game::new(start);
+        let (mate, promotion_id) = parse_san("Ra8#", start).unwrap();
+
+        assert_eq!(game.push_move(mate, promotion_id), Ok(Some(GameResult::Checkmate(true))));
+        assert_eq!(game.result(), Some(GameResult::Checkmate(true)));
+        assert_eq!(game.moves(), vec![mate]);
+        assert_eq!(game.game_state.to_fen(), "R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 1");
+
+        let (reply, promotion_id) = parse_san("Kf1", start).unwrap();
+        assert!(game.push_move(reply, promotion_id).is_err());
+
+        assert_eq!(game.pop_move(), Some(mate));
+        assert_eq!(game.result(), None);
+        assert_eq!(game, Game::new(start));
+    }
+
+    #[test]
+    fn apply_san_test() { // Test replaying the ruy lopez, castles for both teams, a promotion, and telling knights apart
+        let game_state = apply_san(GameState::new(), &["e4", "e5", "Nf3", "Nc6", "Bb5"]).unwrap();
+        assert_eq!(game_state.to_fen(), "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3");
+
+        let game_state = apply_san(game_state, &["Nf6", "O-O", "Be7", "d4", "exd4", "Re1", "O-O"]).unwrap();
+        assert_eq!(game_state.to_fen(), "r1bq1rk1/ppppbppp/2n2n2/1B6/3pP3/5N2/PPP2PPP/RNBQR1K1 w - - 2 7");
+
+        let game_state = apply_san(GameState::from_fen("7k/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap(), &["e8=R+"]).unwrap();
+        assert_eq!(game_state.to_fen(), "4R2k/8/8/8/8/8/8/K7 b - - 0 1");
+
+        // Both knights can reach d2
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert_eq!(apply_san(game_state, &["Nd2"]).unwrap_err(), SanError {
+            index: 0,
+            kind: SanErrorKind::Ambiguous,
+        });
+        assert!(apply_san(game_state, &["Nbd2"]).is_ok());
+        assert_eq!(apply_san(game_state, &["Ke1", "Nd2"]).unwrap_err().kind, SanErrorKind::NoMatch);
+        assert_eq!(apply_san(game_state, &["N"]).unwrap_err().kind, SanErrorKind::Invalid);
+    }
+
+    #[test]
+    fn capture_promotion_test() { // Test a capturing underpromotion that gives check being generated, played, parsed, and written, for both teams
+        use crate::board::turn::new_turn;
+        use crate::piece::moves::legal_moves;
+
+        // exd8=N+ forks the king on f7
+        let game_state = GameState::from_fen("3r4/4Pk2/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let capture = Move {
+            piece_coordinates: [4, 6],
+            move_coordinates: [3, 7],
+        };
+        assert!(legal_moves(game_state).contains(&capture));
+        assert_eq!(parse_san("exd8=N+", game_state), Ok((capture, IDS[2])));
+        assert_eq!(move_to_san(capture, IDS[2], game_state).as_deref(), Some("exd8=N+"));
+        assert_eq!(move_to_san(capture, IDS[4], game_state).as_deref(), Some("exd8=Q"));
+        assert_eq!(move_to_san(capture, 0, game_state), None);
+
+        let game_state_new = new_turn(capture.piece_coordinates, capture.move_coordinates, IDS[2], game_state).unwrap();
+        assert!(game_state_new.gives_check);
+        assert_eq!(game_state_new.to_fen(), "3N4/5k2/8/8/8/8/8/4K3 b - - 0 1");
+
+        // dxe1=N+ for black, with the board flipped
+        let game_state = GameState::from_fen("7k/8/8/8/8/8/2Kp4/4R3 b - - 0 1").unwrap();
+        let (capture, promotion_id) = parse_san("dxe1=N+", game_state).unwrap();
+        assert_eq!(promotion_id, IDS[2]);
+        assert_eq!(move_to_san(capture, promotion_id, game_state).as_deref(), Some("dxe1=N+"));
+        let game_state_new = new_turn(capture.piece_coordinates, capture.move_coordinates, promotion_id, game_state).unwrap();
+        assert!(game_state_new.gives_check);
+        assert_eq!(game_state_new.to_fen(), "7k/8/8/8/8/8/2K5/4n3 w - - 0 2");
+    }
+
+    #[test]
+    fn move_to_san_test() { // Test every legal move in a few positions surviving a move_to_san parse_san roundtrip, and disambiguation and mate
+        let fens = [
+            crate::fen::START_FEN,
+            "r1bqk2r/ppppbppp/2n2n2/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 5",
+            "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1",
+            "r3k2r/1P6/8/8/8/8/6p1/R3K2R b KQkq - 0 1",
+        ];
+        for fen in fens {
+            let game_state = GameState::from_fen(fen).unwrap();
+            for chess_move in crate::piece::moves::legal_moves(game_state) {
+                assert_eq!(parse_san(&move_to_san(chess_move, IDS[4], game_state).unwrap(), game_state), Ok((chess_move, IDS[4])));
+            }
+        }
+
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        let knight = Move {
+            piece_coordinates: [1, 0],
+            move_coordinates: [3, 1],
+        };
+        assert_eq!(move_to_san(knight, IDS[4], game_state).as_deref(), Some("Nbd2"));
+
+        let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mate = Move {
+            piece_coordinates: [0, 0],
+            move_coordinates: [0, 7],
+        };
+        assert_eq!(move_to_san(mate, IDS[4], game_state).as_deref(), Some("Ra8#"));
+
+        // Moves from an empty square or with the wrong team are rejected instead of panicking
+        let empty = Move {
+            piece_coordinates: [3, 3],
+            move_coordinates: [3, 4],
+        };
+        assert_eq!(move_to_san(empty, IDS[4], game_state), None);
+        let enemy = Move {
+            piece_coordinates: [6, 7],
+            move_coordinates: [7, 7],
+        };
+        assert_eq!(move_to_san(enemy, IDS[4], game_state), None);
+    }
+
+    #[test]
+    fn encode_moves_test() { // Test a 40 move game and underpromotions surviving an encode decode roundtrip, and malformed bytes being rejected
+        let start = crate::fen::START_FEN;
+
+        let mut game_state = GameState::from_fen(start).unwrap();
+        let mut moves: Vec<(Move, i8)> = Vec::new();
+        for i in 0..80 {
+            let legal = legal_moves(game_state);
+            let chess_move = legal[i * 7 % legal.len()];
+
+            game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, IDS[4], game_state) {
+                Ok(game_state_new) => game_state_new,
+                Err(_) => break,
+            };
+            moves.push((chess_move, IDS[4]));
+        }
+        assert_eq!(moves.len(), 80);
+
+        let bytes = encode_moves(start, &moves);
+        assert_eq!(bytes.len(), 2 + start.len() + 80 * 2);
+
+        let (start_state, decoded) = decode_moves(&bytes).unwrap();
+        assert_eq!(start_state, GameState::from_fen(start).unwrap());
+        assert_eq!(decoded, moves);
+
+        // Replaying the decoded moves reaches the same position
+        let mut replay = start_state;
+        for (chess_move, promotion_id) in decoded {
+            replay = new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, promotion_id, replay).unwrap();
+        }
+        assert_eq!(replay, game_state);
+
+        assert_eq!(decode_moves(&bytes[..bytes.len() - 1]), Err(DecodeError::Truncated));
+
+        // A king (or pawn) promotion, and a start fen that isn't a position
+        let king_promotion = encode_moves(start, &[(moves[0].0, IDS[5])]);
+        assert_eq!(decode_moves(&king_promotion), Err(DecodeError::Promotion(0)));
+        let pawn_promotion = encode_moves(start, &[moves[0], (moves[1].0, IDS[0])]);
+        assert_eq!(decode_moves(&pawn_promotion), Err(DecodeError::Promotion(1)));
+        assert_eq!(decode_moves(&encode_moves("4k3/8/8/8/8/8/8/4K3 x", &[])), Err(DecodeError::Fen(crate::fen::FenError::SideToMove)));
+
+        // Underpromotions keep their promotion piece, a8=N for white then a1=R for black
+        let start = "4k3/P7/8/8/8/8/p7/4K3 w - - 0 1";
+        let mut game_state = GameState::from_fen(start).unwrap();
+        let mut moves: Vec<(Move, i8)> = Vec::new();
+        for san in ["a8=N", "a1=R+"] {
+            let (chess_move, promotion_id) = parse_san(san, game_state).unwrap();
+            game_state = new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, promotion_id, game_state).unwrap();
+            moves.push((chess_move, promotion_id));
+        }
+        assert_eq!(moves[0].1, IDS[2]);
+        assert_eq!(moves[1].1, IDS[1]);
+
+        let (start_state, decoded) = decode_moves(&encode_moves(start, &moves)).unwrap();
+        assert_eq!(decoded, moves);
+        let mut replay = start_state;
+        for (chess_move, promotion_id) in decoded {
+            replay = new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, promotion_id, replay).unwrap();
+        }
+        assert_eq!(replay, game_state);
+        assert_eq!(replay.to_fen(), "N3k3/8/8/8/8/8/8/r3K3 w - - 0 2");
+    }
+}
@@ -5,6 +5,14 @@ pub mod fen;
 pub mod piece;
 pub mod board;
 pub mod algorithm;
+pub mod bench;
+pub mod trainer;
+pub mod uci;
+pub mod testing;
+pub mod game;
+pub mod opening;
+pub mod time;
+pub mod pgn;
 use crate::board::BOARD_SIZE;
 use crate::piece::moves::BoardInfo;
 
@@ -121,7 +129,10 @@ pub fn flip_board_info(board_info: BoardInfo) -> BoardInfo {
     BoardInfo {
         board: flip_board(board_info.board),
         turns_board: flip_board(board_info.turns_board),
+        castling_rights: board_info.castling_rights,
+        material: board_info.material,
         last_turn_coordinates: flip_coordinates(board_info.last_turn_coordinates),
+        en_passant: board_info.en_passant.map(flip_coordinates),
         capture_coordinates: board_info.capture_coordinates,
         error_code: board_info.error_code,
         pieces: board_info.pieces,
@@ -129,13 +140,25 @@ pub fn flip_board_info(board_info: BoardInfo) -> BoardInfo {
 }
 
 // Convert standard [i8; 2] coordinates into [usize; 2] coordinates
+// Hot path, negative coordinates are only caught in debug builds
 pub fn coordinates_to_usize(coordinates: [i8; 2]) -> [usize; 2] {
-    [usize::try_from(coordinates[0]).unwrap(), usize::try_from(coordinates[1]).unwrap()]
+    debug_assert!(coordinates[0] >= 0 && coordinates[1] >= 0, "negative coordinates {:?}", coordinates);
+    [coordinates[0] as usize, coordinates[1] as usize]
 }
 
 // Convert [usize; 2] coordinates into standard [i8; 2] coordinates
+// Hot path, coordinates too large for an i8 are only caught in debug builds, use coordinates_from_usize_checked for untrusted input
 pub fn coordinates_from_usize(coordinates: [usize; 2]) -> [i8; 2] {
-    [coordinates[0].try_into().unwrap(), coordinates[1].try_into().unwrap()]
+    debug_assert!(coordinates[0] <= i8::MAX as usize && coordinates[1] <= i8::MAX as usize, "coordinates {:?} do not fit in an i8", coordinates);
+    [coordinates[0] as i8, coordinates[1] as i8]
+}
+
+// Convert [usize; 2] coordinates into standard [i8; 2] coordinates, returns None if the coordinates are outside of the board
+pub fn coordinates_from_usize_checked(coordinates: [usize; 2]) -> Option<[i8; 2]> {
+    if coordinates[0] >= BOARD_SIZE[0] || coordinates[1] >= BOARD_SIZE[1] {
+        return None;
+    }
+    Some([i8::try_from(coordinates[0]).ok()?, i8::try_from(coordinates[1]).ok()?])
 }
 
 // Check if a given coordinates is valid on the chess board
@@ -198,6 +221,19 @@ pub fn find_id_in_board(id: i8, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) ->
     None
 }
 
+// Returns the squares strictly between a and b along a row, column, or diagonal, ordered from a to b
+// Returns an empty vec if a and b are not aligned, or are next to each other
+pub fn between(a: [i8; 2], b: [i8; 2]) -> Vec<[i8; 2]> {
+    let delta = [b[0] - a[0], b[1] - a[1]];
+    if !(delta[0] == 0 || delta[1] == 0 || delta[0].abs() == delta[1].abs()) {
+        return Vec::new();
+    }
+
+    let step = [delta[0].signum(), delta[1].signum()];
+    let distance = std::cmp::max(delta[0].abs(), delta[1].abs());
+    (1..distance).map(|i| [a[0] + step[0] * i, a[1] + step[1] * i]).collect()
+}
+
 // Combine 2 boards into 1 board
 // values in board_a are replaced over values in board_b if there is a conflict
 pub fn combine_boards(board_a: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], board_b: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], default_value: i8) -> [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
@@ -237,6 +273,7 @@ fn gen_rand_u64() -> u64 {
 // Generate bitstrings
 fn gen_zobrist_bitstrings() -> HashMap<i8, u64> {
     use crate::piece::info::IDS;
+    use crate::piece::info::MAX_PIECES;
 
     let mut bitstrings = HashMap::new();
 
@@ -244,10 +281,10 @@ fn gen_zobrist_bitstrings() -> HashMap<i8, u64> {
     // Special states are the piece ids * 10
     let multi_state_ids = [IDS[0], IDS[1], IDS[5]];
     
-    // Generate a random u64 value for each piece id and add them to a hashmap
-    for i in 0..IDS.len() {
+    // Generate a random u64 value for each piece id (including custom piece ids) and add them to a hashmap
+    for i in 0..MAX_PIECES {
         for j in 0..2 { // Use j to get black and white piece id
-            let mut piece_id = IDS[i];
+            let mut piece_id = i8::try_from(i + 1).unwrap();
             if j == 1 {
                 piece_id *= -1;
             }
@@ -310,28 +347,22 @@ pub fn gen_zobrist_board_hash(perspective_white: bool, board_info: BoardInfo, bi
             if piece_id != 0 {
                 
 
-                // If a rook or a king is found that hasn't moved multiply it's id by 10
-                // To signify that they can still castle
-                if piece_id.abs() == IDS[1] || piece_id.abs() == IDS[5] {
-                    if get_board(coordinates, board_info.turns_board) == 0 {
+                // If a rook is found that can still castle multiply it's id by 10
+                if piece_id.abs() == IDS[1] {
+                    let castle_right = match crate::piece::moves::castle_right_index(perspective_white, coordinates) {
+                        Some(index) => board_info.castling_rights[index],
+                        None => false,
+                    };
+                    if castle_right {
                         piece_id *= 10;
                     }
                 }
 
                 // If a friendly pawn is able to en passant this turn multiply it's id by 10
-                // En passant conditions
                 if piece_id == IDS[0] * friendly_team { // piece_id at coordinates is a friendly pawn
-                    if coordinates[1] == 4 { // piece is at y = 4
-                        
-                        let coordinates_delta = coordinates[0] - board_info.last_turn_coordinates[0];
-                        if coordinates_delta.abs() == 1 { // The last piece to move is in one of the squares next to the pawn
-
-                            let enemy_pawn_id = IDS[0] * friendly_team * -1;
-                            if get_board(board_info.last_turn_coordinates, board_info.board) == enemy_pawn_id { // The last piece to move is an enemy pawn
-                                if get_board(board_info.last_turn_coordinates, board_info.turns_board) == 1 { // The enemy pawn has moved once
-                                    piece_id *= 10;
-                                }
-                            }
+                    if let Some(en_passant) = board_info.en_passant {
+                        if en_passant[1] == coordinates[1] + 1 && (en_passant[0] - coordinates[0]).abs() == 1 { // The en passant square is diagonally in front of the pawn
+                            piece_id *= 10;
                         }
                     }
                 }
@@ -346,6 +377,7 @@ pub fn gen_zobrist_board_hash(perspective_white: bool, board_info: BoardInfo, bi
 
 #[cfg(test)]
 mod tests {
+    use crate::board::turn::GameState;
     use crate::piece::info;
     use super::*;
 
@@ -404,21 +436,17 @@ mod tests {
     #[test]
     fn flip_board_info_test() {
         let board_info = BoardInfo {
-            board: fen::decode("8/8/8/3P4/8/8/8/R7"),
             turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
             last_turn_coordinates: [0, 0],
-            capture_coordinates: None,
-            error_code: 0,
-            pieces: crate::piece::info::Piece::instantiate_all(),
+            castling_rights: [true; 4],
+            ..GameState::from_board(fen::decode("8/8/8/3P4/8/8/8/R7"), true).unwrap().board_info
         };
 
         let expected = BoardInfo {
-            board: fen::decode("7R/8/8/8/4P3/8/8/8"),
             turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[0]],
             last_turn_coordinates: [7, 7],
-            capture_coordinates: None,
-            error_code: 0,
-            pieces: crate::piece::info::Piece::instantiate_all(),
+            castling_rights: [true; 4],
+            ..GameState::from_board(fen::decode("7R/8/8/8/4P3/8/8/8"), true).unwrap().board_info
         };
 
         let board_info_flipped = flip_board_info(board_info);
@@ -442,6 +470,13 @@ mod tests {
         assert_eq!(coordinates_from_usize(coordinates_usize), coordinates_i8);
     }
 
+    #[test]
+    fn coordinates_from_usize_checked_test() {
+        assert_eq!(coordinates_from_usize_checked([2, 7]), Some([2, 7]));
+        assert_eq!(coordinates_from_usize_checked([8, 0]), None);
+        assert_eq!(coordinates_from_usize_checked([0, usize::MAX]), None);
+    }
+
     #[test]
     fn fits_in_board_test() {
         assert_eq!(fits_in_board([0, -1]), false);
@@ -500,6 +535,15 @@ mod tests {
         assert_eq!(result, Some([2, 3]));
     }
 
+    #[test]
+    fn between_test() { // Test squares between aligned squares, and non aligned squares
+        assert_eq!(between([0, 0], [3, 0]), vec![[1, 0], [2, 0]]);
+        assert_eq!(between([4, 6], [4, 3]), vec![[4, 5], [4, 4]]);
+        assert_eq!(between([2, 0], [6, 4]), vec![[3, 1], [4, 2], [5, 3]]);
+        assert_eq!(between([1, 1], [2, 2]), Vec::<[i8; 2]>::new());
+        assert_eq!(between([1, 0], [2, 2]), Vec::<[i8; 2]>::new());
+    }
+
     #[test]
     fn combine_boards_test() {
         let board1 = [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 1, 0], [0, 1, 0, 1, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 1, 0, 0, 0], [0, 1, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]];
@@ -522,23 +566,11 @@ mod tests {
 
     #[test]
     fn zobrist_hash_test() {
-        let board_info1 = BoardInfo { // Board where en passant is not valid, but pawns are in the right position
-            board: fen::decode("rnbqkbnr/ppppp1pp/8/5pP1/8/8/PPPPPP1P/RNBQKBNR"),
-            turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
-            last_turn_coordinates: [0i8; 2],
-            capture_coordinates: None,
-            error_code: 0,
-            pieces: info::Piece::instantiate_all(),
-        };
+        // Board where en passant is not valid, but pawns are in the right position
+        let board_info1 = GameState::from_fen("rnbqkbnr/ppppp1pp/8/5pP1/8/8/PPPPPP1P/RNBQKBNR w KQkq - 0 3").unwrap().board_info;
 
-        let board_info2 = BoardInfo { // Board where en passant is valid, same piece positions as before, just different turns
-            board: fen::decode("rnbqkbnr/ppppp1pp/8/5pP1/8/8/PPPPPP1P/RNBQKBNR"),
-            turns_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 1, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
-            last_turn_coordinates: [5, 4],
-            capture_coordinates: None,
-            error_code: 0,
-            pieces: info::Piece::instantiate_all(),
-        };
+        // Board where en passant is valid, same piece positions as before
+        let board_info2 = GameState::from_fen("rnbqkbnr/ppppp1pp/8/5pP1/8/8/PPPPPP1P/RNBQKBNR w KQkq f6 0 3").unwrap().board_info;
 
         let bitstrings_board = gen_bistrings_board();
         let board_hash1 = gen_zobrist_board_hash(true, board_info1, &bitstrings_board);
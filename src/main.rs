@@ -1,33 +1,32 @@
 use std::io;
-use std::collections::HashMap;
 
 use chess::board::turn::GameState;
 use chess::board::turn::PointsInfo;
+use chess::board::turn::MAX_CAPTURES;
 use chess::piece::moves::BoardInfo;
 use chess::board::BOARD_SIZE;
 use chess::board::errors;
 
 fn main() {
 
-    // Initialize transposition table
-    let mut transposition_table: HashMap<u64, chess::algorithm::minimax::TranspositionInfo> = HashMap::new();
-    let bitstrings_board = chess::gen_bistrings_board();
-
     let player_white = true;
 
     let search_depth: usize = 4;
 
+    // Initialize search
+    let mut search = chess::algorithm::minimax::Search::builder().depth(search_depth).build();
+
     // Get starting GameState
     let mut game_state = GameState {
         white_points_info: PointsInfo {
-            captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+            captured_pieces: [0i8; MAX_CAPTURES],
             captured_pieces_no: 0,
             points_total: 0,
             points_delta: 0,
         },
 
         black_points_info: PointsInfo {
-            captured_pieces: [0i8; BOARD_SIZE[0] * {BOARD_SIZE[1] / 2}],
+            captured_pieces: [0i8; MAX_CAPTURES],
             captured_pieces_no: 0,
             points_total: 0,
             points_delta: 0,
@@ -42,9 +41,19 @@ fn main() {
             capture_coordinates: None,
             error_code: 0,
             pieces: chess::piece::info::Piece::instantiate_all(),
+            castling_rights: [true; 4],
+            material: chess::piece::moves::count_material(chess::fen::decode("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"), chess::piece::info::Piece::instantiate_all()),
+            en_passant: None,
         },
 
         whites_turn: true,
+        variant: chess::board::Variant::Standard,
+        captured: None,
+        history: chess::board::turn::RepetitionHistory::new(),
+        halfmove_clock: 0,
+        fullmove_number: 1,
+        allowed_promotions: chess::board::turn::DEFAULT_PROMOTIONS,
+        gives_check: false,
     };
 
     let mut game_over = false;
@@ -56,7 +65,7 @@ fn main() {
 
     // Continue to make moves untill the game is over
     while !game_over {
-        println!("{}", transposition_table.len());
+        println!("{}", search.transposition_table.len());
 
         // Initialize variables
         let mut game_state_new = Ok(game_state);
@@ -66,6 +75,7 @@ fn main() {
             white_win: None,
             error_code: 0,
             value: 0,
+            reason: None,
         };
 
         // Get the team name
@@ -92,7 +102,7 @@ fn main() {
     
                 game_state_new = chess::board::turn::new_turn(piece_coordinates, move_coordinates, chess::piece::info::IDS[4], game_state);
             } else {
-                let best_move = chess::algorithm::minimax::best_move(true, 0, search_depth, 0, None, &bitstrings_board, &mut transposition_table, game_state);
+                let best_move = search.go(game_state).unwrap();
                 game_state_new = chess::board::turn::new_turn(best_move.piece_coordinates, best_move.move_coordinates, chess::piece::info::IDS[4], game_state);
 
                 let piece_ccn = chess::cart_to_ccn(chess::flip_coordinates(best_move.piece_coordinates)).unwrap();
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::board::turn::GameState;
+use crate::board::BOARD_SIZE;
+use crate::piece::moves::Move;
+
+// Opening book keyed by zobrist hash instead of fen, so a position reached by different move orders finds the same entry
+// Rooks that can castle and pawns that can capture en passant have their own bitstrings, so book positions only match exactly
+pub struct Book {
+    bitstrings_board: [[HashMap<i8, u64>; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+    entries: HashMap<u64, Vec<(Move, u32)>>, // Book moves and their weights, moves are from the perspective of the team to move
+}
+
+impl Default for Book {
+    fn default() -> Self {
+        Book::new()
+    }
+}
+
+impl Book {
+    pub fn new() -> Self {
+        Book {
+            bitstrings_board: crate::gen_bistrings_board(),
+            entries: HashMap::new(),
+        }
+    }
+
+    // Returns the hash game_state is stored under
+    pub fn hash(&self, game_state: GameState) -> u64 {
+        crate::gen_zobrist_board_hash(game_state.whites_turn, game_state.board_info, &self.bitstrings_board)
+    }
+
+    // Adds a book move for game_state, adding the weight to the existing weight if the move is already in the book
+    pub fn add(&mut self, game_state: GameState, chess_move: Move, weight: u32) {
+        let hash = self.hash(game_state);
+        let book_moves = self.entries.entry(hash).or_default();
+
+        match book_moves.iter_mut().find(|(book_move, _)| *book_move == chess_move) {
+            Some((_, book_weight)) => *book_weight += weight,
+            None => book_moves.push((chess_move, weight)),
+        }
+    }
+
+    // Adds every move of a line of long algebraic moves played from the start position, each with a weight of 1
+    pub fn add_line(&mut self, moves: &[&str]) -> Result<(), crate::uci::MoveError> {
+        let mut game_state = GameState::new();
+        for i in 0..moves.len() {
+            let (chess_move, _) = crate::uci::parse_move(moves[i], game_state.whites_turn).map_err(|_| crate::uci::MoveError {
+                index: i,
+                error_code: crate::board::errors::INVALID_MOVE_ERROR,
+            })?;
+
+            self.add(game_state, chess_move, 1);
+            game_state = crate::uci::apply_moves(game_state, &moves[i..i + 1]).map_err(|error| crate::uci::MoveError {
+                index: i,
+                error_code: error.error_code,
+            })?;
+        }
+        Ok(())
+    }
+
+    // Returns the book moves and weights for game_state
+    pub fn moves(&self, game_state: GameState) -> &[(Move, u32)] {
+        match self.entries.get(&self.hash(game_state)) {
+            Some(book_moves) => book_moves,
+            None => &[],
+        }
+    }
+
+    // Picks a book move for game_state, moves with a higher weight are picked more often
+    // Returns None when the position isn't in the book
+    pub fn probe(&self, game_state: GameState) -> Option<Move> {
+        use rand::seq::SliceRandom;
+
+        self.moves(game_state)
+            .choose_weighted(&mut rand::thread_rng(), |(_, weight)| *weight)
+            .ok()
+            .map(|(chess_move, _)| *chess_move)
+    }
+}
+
+// Named openings and the long algebraic moves which reach them from the start position
+const OPENINGS: [(&str, &[&str]); 15] = [
+    ("King's Pawn Game", &["e2e4"]),
+    ("Queen's Pawn Game", &["d2d4"]),
+    ("English Opening", &["c2c4"]),
+    ("Open Game", &["e2e4", "e7e5"]),
+    ("Sicilian Defense", &["e2e4", "c7c5"]),
+    ("French Defense", &["e2e4", "e7e6"]),
+    ("Caro-Kann Defense", &["e2e4", "c7c6"]),
+    ("Italian Game", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4"]),
+    ("Ruy Lopez", &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"]),
+    ("Queen's Gambit", &["d2d4", "d7d5", "c2c4"]),
+    ("Queen's Gambit Accepted", &["d2d4", "d7d5", "c2c4", "d5c4"]),
+    ("Queen's Gambit Declined", &["d2d4", "d7d5", "c2c4", "e7e6"]),
+    ("Slav Defense", &["d2d4", "d7d5", "c2c4", "c7c6"]),
+    ("Nimzo-Indian Defense", &["d2d4", "g8f6", "c2c4", "e7e6", "b1c3", "f8b4"]),
+    ("King's Indian Defense", &["d2d4", "g8f6", "c2c4", "g7g6"]),
+];
+
+// Names the opening played by moves, a game played from the start position with each move from the perspective of the team making it
+// Positions are compared instead of moves, so openings reached by a different move order are found
+// The most specific (longest) opening reached is returned, None if the game doesn't reach any opening in the table
+pub fn classify(moves: &[Move]) -> Option<&'static str> {
+    use crate::board::turn::new_turn;
+
+    // Every position reached by the game, stopping at the first move that can't be played
+    let mut positions: Vec<(bool, [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]])> = Vec::new();
+    let mut game_state = GameState::new();
+    for chess_move in moves {
+        game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, crate::piece::info::IDS[4], game_state) {
+            Ok(game_state_new) => game_state_new,
+            Err(_) => break,
+        };
+        positions.push((game_state.whites_turn, game_state.board_info.board));
+    }
+
+    let mut opening: Option<(usize, &'static str)> = None;
+    for (name, line) in OPENINGS {
+        let opening_state = crate::uci::apply_moves(GameState::new(), line).unwrap();
+        if !positions.contains(&(opening_state.whites_turn, opening_state.board_info.board)) {
+            continue;
+        }
+
+        if opening.is_none() || line.len() > opening.unwrap().0 {
+            opening = Some((line.len(), name));
+        }
+    }
+    opening.map(|(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parses a line of long algebraic moves played from the start position
+    fn parse_line(moves: &[&str]) -> Vec<Move> {
+        (0..moves.len()).map(|i| crate::uci::parse_move(moves[i], i % 2 == 0).unwrap().0).collect()
+    }
+
+    #[test]
+    fn classify_test() { // Test the queens gambit, a longer line, a transposition, and an unknown opening
+        assert_eq!(classify(&parse_line(&["d2d4", "d7d5", "c2c4"])), Some("Queen's Gambit"));
+        assert_eq!(classify(&parse_line(&["d2d4", "d7d5", "c2c4", "e7e6", "b1c3"])), Some("Queen's Gambit Declined"));
+        assert_eq!(classify(&parse_line(&["c2c4", "d7d5", "d2d4"])), Some("Queen's Gambit"));
+        assert_eq!(classify(&parse_line(&["e2e4", "c7c5"])), Some("Sicilian Defense"));
+        assert_eq!(classify(&parse_line(&["g2g3"])), None);
+    }
+
+    #[test]
+    fn probe_transposition_test() { // Test two move orders reaching the same position finding the same book move
+        let mut book = Book::new();
+        book.add_line(&["g1f3", "d7d5", "d2d4", "g8f6", "c2c4"]).unwrap();
+
+        let game_state = crate::uci::apply_moves(GameState::new(), &["d2d4", "g8f6", "g1f3", "d7d5"]).unwrap();
+        let c4 = crate::uci::parse_move("c2c4", true).unwrap().0;
+        assert_eq!(book.probe(game_state), Some(c4));
+        assert_eq!(book.moves(game_state), &[(c4, 1)]);
+
+        // The same pieces with the king side castle right lost are a different position
+        let game_state = crate::uci::apply_moves(GameState::new(), &["g1f3", "d7d5", "d2d4", "g8f6", "h1g1", "b8c6", "g1h1", "c6b8"]).unwrap();
+        assert_eq!(book.probe(game_state), None);
+    }
+}
@@ -0,0 +1,147 @@
+// Tokenizing of pgn move text, the part of a pgn after the tag pairs
+
+// A token of pgn move text
+#[derive(Debug, Clone, PartialEq)]
+pub enum PgnToken {
+    MoveNumber(u16), // "1." or "1..."
+    Move(String), // San move, with any check or annotation suffix (e.g. Nf3+, e4!?)
+    Comment(String), // Text of a {...} or ; comment, trimmed
+    Nag(u8), // Numeric annotation glyph (e.g. $1)
+    Variation(String), // Move text of a (...) variation, including nested variations, tokenize it to read it
+    Result(String), // 1-0, 0-1, 1/2-1/2, or *
+}
+
+// Game results that can end pgn move text
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// Splits pgn move text into tokens
+// Variations are kept whole so the returned move tokens are the main line, comments inside variations don't end them
+// Unterminated comments and variations run to the end of the move text, stray closing brackets are ignored
+pub fn tokenize(movetext: &str) -> Vec<PgnToken> {
+    let chars: Vec<char> = movetext.chars().collect();
+    let mut tokens: Vec<PgnToken> = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() || c == ')' || c == '}' {
+            i += 1;
+            continue;
+        }
+
+        // Comments don't nest, braced comments end at the first closing brace and ; comments at the end of the line
+        if c == '{' || c == ';' {
+            let end_char = match c {
+                '{' => '}',
+                _ => '\n',
+            };
+            let end = crate::unwrap_def(chars[i + 1..].iter().position(|c| *c == end_char), chars.len() - i - 1) + i + 1;
+            tokens.push(PgnToken::Comment(chars[i + 1..end].iter().collect::<String>().trim().to_string()));
+            i = end + 1;
+            continue;
+        }
+
+        // Find the matching closing bracket, skipping brackets in comments
+        if c == '(' {
+            let mut depth = 0;
+            let mut end = i;
+            let mut comment_end: Option<char> = None;
+            while end < chars.len() {
+                match (comment_end, chars[end]) {
+                    (Some(comment_end_char), c) if c == comment_end_char => comment_end = None,
+                    (Some(_), _) => (),
+                    (None, '{') => comment_end = Some('}'),
+                    (None, ';') => comment_end = Some('\n'),
+                    (None, '(') => depth += 1,
+                    (None, ')') => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    },
+                    _ => (),
+                }
+                end += 1;
+            }
+            let end = std::cmp::min(end, chars.len());
+            tokens.push(PgnToken::Variation(chars[i + 1..end].iter().collect::<String>().trim().to_string()));
+            i = end + 1;
+            continue;
+        }
+
+        // Everything else is a word up to the next whitespace or bracket
+        let end = crate::unwrap_def(chars[i..].iter().position(|c| c.is_whitespace() || "{}();".contains(*c)), chars.len() - i) + i;
+        let word: String = chars[i..end].iter().collect();
+        i = end;
+
+        if let Some(nag) = word.strip_prefix('$') {
+            if let Ok(nag) = nag.parse() {
+                tokens.push(PgnToken::Nag(nag));
+            }
+            continue;
+        }
+
+        if RESULTS.contains(&word.as_str()) {
+            tokens.push(PgnToken::Result(word));
+            continue;
+        }
+
+        // Move numbers are digits followed by dots, a move can follow without a space (e.g. 1.e4), castles written with zeros have no dot
+        let digits = word.chars().take_while(|c| c.is_ascii_digit()).count();
+        let dots = word[digits..].chars().take_while(|c| *c == '.').count();
+        if digits > 0 && dots > 0 {
+            if let Ok(move_number) = word[..digits].parse() {
+                tokens.push(PgnToken::MoveNumber(move_number));
+            }
+            if digits + dots < word.len() {
+                tokens.push(PgnToken::Move(word[digits + dots..].to_string()));
+            }
+            continue;
+        }
+
+        tokens.push(PgnToken::Move(word));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_test() { // Test a comment, a nested variation with a bracket in a comment, and a nag being split from the main line
+        let movetext = "1. e4 {Best by test} e5 2.Nf3 $1 (2. f4 exf4 (2... d5) {a ) in a comment} 3. Nf3) 2... Nc6 3. O-O-O+ 1/2-1/2";
+        let tokens = tokenize(movetext);
+
+        assert_eq!(tokens, vec![
+            PgnToken::MoveNumber(1),
+            PgnToken::Move(String::from("e4")),
+            PgnToken::Comment(String::from("Best by test")),
+            PgnToken::Move(String::from("e5")),
+            PgnToken::MoveNumber(2),
+            PgnToken::Move(String::from("Nf3")),
+            PgnToken::Nag(1),
+            PgnToken::Variation(String::from("2. f4 exf4 (2... d5) {a ) in a comment} 3. Nf3")),
+            PgnToken::MoveNumber(2),
+            PgnToken::Move(String::from("Nc6")),
+            PgnToken::MoveNumber(3),
+            PgnToken::Move(String::from("O-O-O+")),
+            PgnToken::Result(String::from("1/2-1/2")),
+        ]);
+
+        // Variations tokenize the same way
+        let variation = match &tokens[7] {
+            PgnToken::Variation(variation) => tokenize(variation),
+            _ => panic!(),
+        };
+        assert_eq!(variation[3], PgnToken::Variation(String::from("2... d5")));
+
+        // Line comments end at the end of the line
+        assert_eq!(tokenize("e4 ; good\ne5"), vec![
+            PgnToken::Move(String::from("e4")),
+            PgnToken::Comment(String::from("good")),
+            PgnToken::Move(String::from("e5")),
+        ]);
+    }
+}
@@ -7,9 +7,23 @@ pub mod info {
     // Order                  P  R  N  B  Q  K
     pub const IDS: [i8; 6] = [1, 2, 3, 4, 5, 6];
 
+    // Maximum number of piece types, ids from IDS.len() + 1 up to MAX_PIECES are free for custom pieces
+    pub const MAX_PIECES: usize = 8;
+
     pub const CHECKMATE_VALUE: i8 = 100; // Value of a checkmate (used for ai)
     pub const STALEMATE_VALUE: i8 = -1; // Value of a stalemate (used for ai)
 
+    // Material weight of a king in scoring paths
+    // The kings Piece.value stays 0 so i8 material sums can't overflow, scores that count kings use piece_weight instead
+    pub const KING_VALUE: i32 = 1000;
+
+    // Why Piece::with_custom couldn't add a custom piece, each error stores the id of the piece
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum CustomPieceError {
+        IdOutOfRange(i8), // The id is a standard piece id, or larger than MAX_PIECES
+        DuplicateId(i8), // An earlier custom piece has the same id
+    }
+
     #[derive(Debug, Copy, Clone, PartialEq)]
     pub struct Piece {
         pub id_fen: char,
@@ -21,11 +35,13 @@ pub mod info {
 
         // Used to restrict the amount of times a piece can slide (E.g. can only slide twice). Only takes affect for a pieces first turn (moves = 0)
         pub slide_no: Option<usize>, 
+        pub slide_no_y: Option<i8>, // If set slide_no takes affect when the piece is at this y coordinate instead of on its first turn
 
         // All mdirs arrays have to be indexed like [y][x], whereas boards are indexed [x][y]
         // This is because mdirs readability is important
         pub mdirs: [[i8; 2]; 8], // Move directions array
         pub mdir_no: usize, // How many move directions there are, if a piece has speical capture directions there can be a max of 2 mdirs
+        pub mdirs_leap: Option<[[i8; 2]; 8]>, // Extra non sliding move directions, lets a sliding piece also leap (E.g. an archbishop)
 
         // Non conditional capture directions
         pub mdirs_cap: Option<[[i8; 2]; 2]>, // Move directions just for capturing a piece
@@ -39,7 +55,7 @@ pub mod info {
         // So the condition in condition_adj and direction in mdirs_cap have to correspond
         pub condition_adj: Option<[[i8; 2]; 2]>, 
         pub condition_self_y: Option<i8>, // Condition for what y coordinates the piece performing the capture has to be at
-        pub condition_subj_moves: Option<i8>, // Condition for how many moves the enemy piece found from conition_adj has to have made
+        pub condition_subj_moves: Option<i8>, // How many moves the enemy piece found from conition_adj has made, the capture itself is only allowed onto BoardInfo::en_passant
     }
 
     // All pieces use white id and id_fen by default
@@ -54,6 +70,7 @@ pub mod info {
 
                 sliding: true,
                 slide_no: Some(2),
+                slide_no_y: Some(1),
 
                 mdirs: [
                     [0, 1], // Move up
@@ -66,6 +83,7 @@ pub mod info {
                     [0, 0],
                 ],
                 mdir_no: 1,
+                mdirs_leap: None,
 
                 mdirs_cap: Some([
                     [1, 1], // Capture up right
@@ -93,6 +111,7 @@ pub mod info {
 
                 sliding: true,
                 slide_no: None,
+                slide_no_y: None,
 
                 mdirs: [
                     [1, 0], // Move right
@@ -106,6 +125,7 @@ pub mod info {
                 ],
                 mdir_no: 4,
 
+                mdirs_leap: None,
                 mdirs_cap: None,
                 condition_adj: None,
                 condition_self_y: None,
@@ -123,6 +143,7 @@ pub mod info {
 
                 sliding: false,
                 slide_no: None,
+                slide_no_y: None,
 
                 mdirs: [
                     [1, 2],
@@ -136,6 +157,7 @@ pub mod info {
                 ],
                 mdir_no: 8,
 
+                mdirs_leap: None,
                 mdirs_cap: None,
                 condition_adj: None,
                 condition_self_y: None,
@@ -152,6 +174,7 @@ pub mod info {
 
                 sliding: true,
                 slide_no: None,
+                slide_no_y: None,
 
                 mdirs: [
                     [1, 1], // Move up right
@@ -165,6 +188,7 @@ pub mod info {
                 ],
                 mdir_no: 4,
 
+                mdirs_leap: None,
                 mdirs_cap: None,
                 condition_adj: None,
                 condition_self_y: None,
@@ -181,6 +205,7 @@ pub mod info {
 
                 sliding: true,
                 slide_no: None,
+                slide_no_y: None,
 
                 mdirs: [
                     [1, 0], // Move right
@@ -194,6 +219,7 @@ pub mod info {
                 ],
                 mdir_no: 8,
 
+                mdirs_leap: None,
                 mdirs_cap: None,
                 condition_adj: None,
                 condition_self_y: None,
@@ -210,6 +236,7 @@ pub mod info {
 
                 sliding: false,
                 slide_no: None,
+                slide_no_y: None,
 
                 mdirs: [
                     [1, 0], // Move right
@@ -223,6 +250,30 @@ pub mod info {
                 ],
                 mdir_no: 8,
 
+                mdirs_leap: None,
+                mdirs_cap: None,
+                condition_adj: None,
+                condition_self_y: None,
+                condition_subj_moves: None,
+            }
+        }
+
+        // Placeholder for unused custom piece slots
+        fn empty() -> Self {
+            Piece {
+                id_fen: ' ',
+                id: 0,
+
+                value: 0,
+
+                sliding: false,
+                slide_no: None,
+                slide_no_y: None,
+
+                mdirs: [[0i8; 2]; 8],
+                mdir_no: 0,
+                mdirs_leap: None,
+
                 mdirs_cap: None,
                 condition_adj: None,
                 condition_self_y: None,
@@ -232,7 +283,8 @@ pub mod info {
 
         // Instantiates all pieces and returns them in an array
         // The array is sorted so that when indexed by an id - 1 that array element will be the corresponding piece
-        pub fn instantiate_all() -> [Piece; 6] {
+        // Slots after IDS.len() are empty until filled with with_custom
+        pub fn instantiate_all() -> [Piece; MAX_PIECES] {
             let p = Piece::pawn();
             let r = Piece::rook();
             let n = Piece::knight();
@@ -242,7 +294,7 @@ pub mod info {
 
             // Do not tamper
             let piece_array_def = [p, r, n, b, q, k];
-            let mut piece_array = piece_array_def;
+            let mut piece_array = [Piece::empty(); MAX_PIECES];
 
             // Sorts piece_vec_def to piece_vec
             // Sorts in a way that ensures when indexing piece_vec with piece id - 1 it gives the corresponding struct to that id
@@ -252,10 +304,37 @@ pub mod info {
 
             piece_array
         }
+
+        // Instantiates all pieces along with extra custom (fairy) pieces
+        // Each custom piece is stored at its id - 1, so its id has to be between IDS.len() + 1 and MAX_PIECES
+        pub fn with_custom(extra: Vec<Piece>) -> Result<[Piece; MAX_PIECES], CustomPieceError> {
+            let mut piece_array = Piece::instantiate_all();
+
+            for piece in extra {
+                let index = usize::try_from(piece.id - 1).map_err(|_| CustomPieceError::IdOutOfRange(piece.id))?;
+                if index < IDS.len() || index >= MAX_PIECES {
+                    return Err(CustomPieceError::IdOutOfRange(piece.id));
+                }
+                if piece_array[index].id != 0 {
+                    return Err(CustomPieceError::DuplicateId(piece.id));
+                }
+                piece_array[index] = piece;
+            }
+
+            Ok(piece_array)
+        }
+    }
+
+    // Returns the material weight of a piece id (from either team) for scoring, kings use KING_VALUE
+    pub fn piece_weight(id: i8, pieces: [Piece; MAX_PIECES]) -> i32 {
+        if id.abs() == IDS[5] {
+            return KING_VALUE;
+        }
+        i32::from(pieces[usize::try_from(id.abs() - 1).unwrap()].value)
     }
 
     // Convert id_fen to id
-    pub fn id_fen_to_id(mut id_fen: char, pieces: [Piece; 6]) -> i8 {
+    pub fn id_fen_to_id(mut id_fen: char, pieces: [Piece; MAX_PIECES]) -> i8 {
         //let mut id: i8 = 0;
 
         // Check if piece is white
@@ -328,10 +407,116 @@ pub mod moves {
     pub struct BoardInfo {
         pub board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], // Game board, stores piece ids in the positions they are on the board.
         pub turns_board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], // Turns board, values correspond to a piece at the same coordinates on the game board. Values represent how many times that piece has moved from its starting position.
+        pub castling_rights: [bool; 4], // White king side, white queen side, black king side, black queen side. Indexed the same way regardless of board perspective
+        pub material: [i32; 2], // Running material of white and black, indexed the same way regardless of board perspective. Updated by new_turn on captures and promotions
+        pub en_passant: Option<[i8; 2]>, // Square a pawn of the team to move can capture en passant on, from the boards perspective. Set by new_turn after a pawn double move and cleared by the next move
         pub last_turn_coordinates: [i8; 2], // Last turn coordinates, coordinates of the piece that moved last turn.
         pub capture_coordinates: Option<[i8; 2]>, // Coordinates of piece that was captured (if any)
         pub error_code: i8,
-        pub pieces: [info::Piece; 6], // Array stores piece structs, structs contain infromation such as piece ids, movement directions, and movement types.
+        pub pieces: [info::Piece; info::MAX_PIECES], // Array stores piece structs, structs contain infromation such as piece ids, movement directions, and movement types.
+    }
+
+    impl BoardInfo {
+        // Returns the running material of a team, kings are not counted
+        pub fn material(&self, white: bool) -> i32 {
+            if white {
+                return self.material[0];
+            }
+            self.material[1]
+        }
+
+        // Places the piece id on square (from the boards perspective), replacing any piece already there
+        // Used to edit positions, the result should be checked with position_is_legal before playing from it
+        // Keeps the running material up to date, loses castle rights of a replaced king or rook, and ends any en passant
+        pub fn set_piece(&mut self, square: [i8; 2], id: i8) {
+            use info::IDS;
+
+            let old_id = get_board(square, self.board);
+            let value = |id: i8| -> i32 {
+                i32::from(self.pieces[usize::try_from(id.abs() - 1).unwrap()].value)
+            };
+            if old_id != 0 {
+                self.material[usize::from(!piece_white(old_id))] -= value(old_id);
+            }
+            if id != 0 {
+                self.material[usize::from(!piece_white(id))] += value(id);
+            }
+
+            // Castle rights are indexed the same way regardless of perspective, so check the squares of both perspectives
+            let x_max = i8::try_from(BOARD_SIZE[0] - 1).unwrap();
+            let y_max = i8::try_from(BOARD_SIZE[1] - 1).unwrap();
+            for index in 0..self.castling_rights.len() {
+                let (team, y) = match index < 2 {
+                    true => (1, 0),
+                    false => (-1, y_max),
+                };
+                let rook_x = match index % 2 == 0 {
+                    true => x_max,
+                    false => 0,
+                };
+
+                for (coordinates, castle_id) in [([4, y], IDS[5] * team), ([rook_x, y], IDS[1] * team)] {
+                    if (square == coordinates || square == crate::flip_coordinates(coordinates)) && old_id == castle_id {
+                        self.castling_rights[index] = false;
+                    }
+                }
+            }
+
+            // Pawns on either starting row can still double move, every other piece has moved
+            let starting_row = square[1] == 1 || square[1] == y_max - 1;
+            let mut turns = 1;
+            if id == 0 || (id.abs() == IDS[0] && starting_row) {
+                turns = 0;
+            }
+
+            self.board = set_board(square, id, self.board);
+            self.turns_board = set_board(square, turns, self.turns_board);
+            self.last_turn_coordinates = [0, 0];
+            self.capture_coordinates = None;
+            self.en_passant = None;
+        }
+
+        // Removes the piece on square, see set_piece
+        pub fn clear(&mut self, square: [i8; 2]) {
+            self.set_piece(square, 0);
+        }
+    }
+
+    // Counts the material of white and black by scanning the board, kings are not counted
+    pub fn count_material(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], pieces: [info::Piece; info::MAX_PIECES]) -> [i32; 2] {
+        let mut material = [0i32; 2];
+        for id in board.iter().flatten() {
+            if *id == 0 {
+                continue;
+            }
+
+            let value = i32::from(pieces[usize::try_from(id.abs() - 1).unwrap()].value);
+            if piece_white(*id) {
+                material[0] += value;
+            } else {
+                material[1] += value;
+            }
+        }
+        material
+    }
+
+    // A move of the piece at piece_coordinates to move_coordinates
+    // Coordinates are from the perspective of the team making the move
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct Move {
+        pub piece_coordinates: [i8; 2],
+        pub move_coordinates: [i8; 2],
+    }
+
+    // A legal move and what kind of move it is
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct MoveInfo {
+        pub chess_move: Move,
+        pub is_capture: bool, // Includes en passant captures
+        pub is_castle: bool,
+        pub is_en_passant: bool,
+        pub promotion: Option<i8>, // Id the pawn promotes to
+        pub gives_check: bool,
     }
 
     // Generates all possible moves given a single piece, cannot generate moves for an enemy team because the pawns will move backwards
@@ -343,7 +528,6 @@ pub mod moves {
         
         let board = board_info.board;
         let turns_board = board_info.turns_board;
-        let last_turn_coordinates = board_info.last_turn_coordinates;
         let pieces = board_info.pieces;
 
         // Get piece id
@@ -368,10 +552,13 @@ pub mod moves {
         let slide_no = pieces[pieces_index].slide_no;
         let mut slide_no = unwrap_def(slide_no, MAX_SLIDES);
         
-        if get_board(piece_coordinates, turns_board) != 0 && slide_no != MAX_SLIDES {
-
-            // Piece must have 0 turns to make use of a custom slide number
-            // If it doesn't slding is disabled
+        // Piece must be on its starting row (or have 0 turns if it has no starting row) to make use of a custom slide number
+        // If it doesn't slding is disabled
+        let slide_no_valid = match pieces[pieces_index].slide_no_y {
+            Some(y) => piece_coordinates[1] == y,
+            None => get_board(piece_coordinates, turns_board) == 0,
+        };
+        if !slide_no_valid && slide_no != MAX_SLIDES {
             slides = false;
         }
 
@@ -383,7 +570,6 @@ pub mod moves {
         let mdirs_cap = pieces[pieces_index].mdirs_cap;
         let condition_adj = pieces[pieces_index].condition_adj;
         let condition_self_y = pieces[pieces_index].condition_self_y;
-        let condition_subj_moves = pieces[pieces_index].condition_subj_moves;
 
         // Check for special capture
         let mut special_capture = false;
@@ -431,7 +617,6 @@ pub mod moves {
         if conditional_capture {
             let condition_adj = unwrap_def(condition_adj, [[0i8; 2]; 2]);
             let condition_self_y = unwrap_def(condition_self_y, 0);
-            let condition_subj_moves = unwrap_def(condition_subj_moves, 0);
 
             for i in 0..2 {
 
@@ -451,18 +636,16 @@ pub mod moves {
                 if fits_in_board(capture_coordinates) && fits_in_board(condition_coordinates) { // Check both the capture and condition coordinates fit in the board
 
                     // Check conditions
-                    if condition_coordinates == last_turn_coordinates { // Piece in condition square must have moved last turn
+                    if board_info.en_passant == Some(capture_coordinates) { // The capture coordinates must be the en passant square, set by the enemies double move last turn
                         let condition_id = get_board(condition_coordinates, board);
                         if !friendly_piece(id, condition_id) && id.abs() == condition_id.abs() { // Condition square must be occupied by an enemy piece, and the piece capturing and piece being captured must be of the same type
-                            if get_board(condition_coordinates, turns_board) == condition_subj_moves { // Piece in condition square must have moved condition_subj_moves times
-                                if piece_coordinates[1] == condition_self_y { // Piece performing the special capture must be at y coordinates condition_self_y
-                                    if get_board(capture_coordinates, board) == 0 { // Square where the piece moves to must be empty
+                            if piece_coordinates[1] == condition_self_y { // Piece performing the special capture must be at y coordinates condition_self_y
+                                if get_board(capture_coordinates, board) == 0 { // Square where the piece moves to must be empty
 
-                                        // Set moves_board to 1 at the capture coordinates to indicate that the piece can move there
-                                        moves_board = set_board(capture_coordinates, 1, moves_board); 
+                                    // Set moves_board to 1 at the capture coordinates to indicate that the piece can move there
+                                    moves_board = set_board(capture_coordinates, 1, moves_board); 
 
-                                        captured_coordinates = Some(condition_coordinates);
-                                    }
+                                    captured_coordinates = Some(condition_coordinates);
                                 }
                             }
                         }
@@ -482,37 +665,72 @@ pub mod moves {
             no_block_id = no_block_id * -1;
         }
 
-        for i in 0..mdir_no {
-            let mut piece_coordinates_current = piece_coordinates;
-            for _j in 0..slide_no {
-
-                // Get the pieces coordinates after applying the x and y coordinates change from mdirs array
+        if slide_no == 1 && !special_capture { // Non sliding pieces (e.g. knights and kings) move once in each direction, onto an empty square or an enemy
+            for mdir in mdirs.iter().take(mdir_no) {
                 let move_coordinates = [
-                    piece_coordinates_current[0] + mdirs[i][0],
-                    piece_coordinates_current[1] + mdirs[i][1],
+                    piece_coordinates[0] + mdir[0],
+                    piece_coordinates[1] + mdir[1],
                 ];
 
-                if fits_in_board(move_coordinates) { // Check move coordinates fit in the board
-                    let move_coordinates_id =  get_board(move_coordinates, board);
+                if fits_in_board(move_coordinates) && !friendly_piece(id, get_board(move_coordinates, board)) {
+                    moves_board = set_board(move_coordinates, 1, moves_board);
+                }
+            }
+        } else {
+            for mdir in mdirs.iter().take(mdir_no) {
+                let mut piece_coordinates_current = piece_coordinates;
+                for _j in 0..slide_no {
+
+                    // Get the pieces coordinates after applying the x and y coordinates change from mdirs array
+                    let move_coordinates = [
+                        piece_coordinates_current[0] + mdir[0],
+                        piece_coordinates_current[1] + mdir[1],
+                    ];
 
-                    // Default move val is 1
-                    let mut move_val = 1;
-                    if special_capture {
-                        move_val = 2; // 2 When the move should not be seen as a potential capture (e.g. can't put the king in check)
-                    }
+                    if fits_in_board(move_coordinates) { // Check move coordinates fit in the board
+                        let move_coordinates_id =  get_board(move_coordinates, board);
 
-                    if move_coordinates_id == 0 || move_coordinates_id == no_block_id { // If the move_coordinates are empty they can be moved to
-                        moves_board = set_board(move_coordinates, move_val, moves_board);
-                        piece_coordinates_current = move_coordinates;
-                    } else if !friendly_piece(id, move_coordinates_id) && move_coordinates_id != 0 && !special_capture { // If the move_coordinates are an enemy they can be moved to, special captures cannot capture this way, they have to use their special capture
-                        moves_board = set_board(move_coordinates, move_val, moves_board);
-                        piece_coordinates_current = move_coordinates;
-                        break;
+                        // Default move val is 1
+                        let mut move_val = 1;
+                        if special_capture {
+                            move_val = 2; // 2 When the move should not be seen as a potential capture (e.g. can't put the king in check)
+                        }
+
+                        // moves_board can hold the moves of other pieces (gen_all_moves), a square another piece attacks stays a 1
+                        if move_val == 2 && get_board(move_coordinates, moves_board) == 1 {
+                            move_val = 1;
+                        }
+
+                        if move_coordinates_id == 0 || move_coordinates_id == no_block_id { // If the move_coordinates are empty they can be moved to
+                            moves_board = set_board(move_coordinates, move_val, moves_board);
+                            piece_coordinates_current = move_coordinates;
+                        } else if !friendly_piece(id, move_coordinates_id) && move_coordinates_id != 0 && !special_capture { // If the move_coordinates are an enemy they can be moved to, special captures cannot capture this way, they have to use their special capture
+                            moves_board = set_board(move_coordinates, move_val, moves_board);
+                            break;
+                        } else {
+                            break;
+                        }
                     } else {
                         break;
                     }
-                } else {
-                    break;
+                }
+            }
+        }
+
+        // Generate leaping moves for pieces that can both slide and leap
+        if let Some(mdirs_leap) = pieces[pieces_index].mdirs_leap {
+            for mdir_leap in mdirs_leap {
+                if mdir_leap == [0, 0] { // Unused move direction
+                    continue;
+                }
+
+                let move_coordinates = [
+                    piece_coordinates[0] + mdir_leap[0],
+                    piece_coordinates[1] + mdir_leap[1],
+                ];
+
+                if fits_in_board(move_coordinates) && !friendly_piece(id, get_board(move_coordinates, board)) {
+                    moves_board = set_board(move_coordinates, 1, moves_board);
                 }
             }
         }
@@ -524,6 +742,15 @@ pub mod moves {
         }
     }
 
+    // Returns the coordinates of the piece removed by a conditional capture (en passant) when moving to move_coordinates
+    // gen_moves finds the conditional capture for the whole piece, but it only happens when the piece moves onto the captured pieces column
+    fn move_capture_coordinates(moves: Moves, piece_coordinates: [i8; 2], move_coordinates: [i8; 2]) -> Option<[i8; 2]> {
+        match moves.capture_coordinates {
+            Some(capture_coordinates) if move_coordinates[0] == capture_coordinates[0] && move_coordinates[0] != piece_coordinates[0] => Some(capture_coordinates),
+            _ => None,
+        }
+    }
+
     // Generates all possible moves for a type of piece (white or black)
     pub fn gen_all_moves(
     gen_all_white: bool, // When true generates all white moves, generates black mvoes when false
@@ -546,6 +773,126 @@ pub mod moves {
         moves_board
     }
 
+    // Generates the squares attacked by a single piece
+    // Unlike gen_moves squares occupied by friendly pieces are included (they are defended), and pawns only attack their capture directions
+    fn gen_attacks(piece_coordinates: [i8; 2], board_info: BoardInfo) -> [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
+        use crate::board::MAX_SLIDES;
+
+        let board = board_info.board;
+        let mut attacks_board = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+
+        let id = get_board(piece_coordinates, board);
+        if id == 0 {
+            return attacks_board;
+        }
+
+        let piece = board_info.pieces[usize::try_from(id.abs() - 1).unwrap()];
+
+        // Pieces with special captures only attack in their capture directions
+        let mut mdirs = piece.mdirs;
+        let mut mdir_no = piece.mdir_no;
+        let mut slide_no = MAX_SLIDES;
+        if let Some(mdirs_cap) = piece.mdirs_cap {
+            mdirs[0] = mdirs_cap[0];
+            mdirs[1] = mdirs_cap[1];
+            mdir_no = 2;
+            slide_no = 1;
+        } else if !piece.sliding {
+            slide_no = 1;
+        }
+
+        for mdir in mdirs.iter().take(mdir_no) {
+            let mut attack_coordinates = piece_coordinates;
+            for _j in 0..slide_no {
+                attack_coordinates = [
+                    attack_coordinates[0] + mdir[0],
+                    attack_coordinates[1] + mdir[1],
+                ];
+
+                if !fits_in_board(attack_coordinates) {
+                    break;
+                }
+
+                attacks_board = set_board(attack_coordinates, 1, attacks_board);
+
+                // Any piece blocks the rest of the sightline
+                if get_board(attack_coordinates, board) != 0 {
+                    break;
+                }
+            }
+        }
+        attacks_board
+    }
+
+    // Returns the squares of the friendly pieces the piece on square defends, empty if the square is empty
+    // A friendly piece ends a sightline like any other piece but is included, so a slider defends up to and including the first friendly piece on each ray
+    // board_info has to be from the perspective of the pieces team so pawns defend their capture directions
+    pub fn defended_squares(square: [i8; 2], board_info: BoardInfo) -> Vec<[i8; 2]> {
+        let board = board_info.board;
+        let id = get_board(square, board);
+        if id == 0 {
+            return Vec::new();
+        }
+
+        let attacks_board = gen_attacks(square, board_info);
+        let mut defended = Vec::new();
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let defended_id = board[x][y];
+                if attacks_board[x][y] == 1 && defended_id != 0 && piece_white(defended_id) == piece_white(id) {
+                    defended.push(crate::coordinates_from_usize([x, y]));
+                }
+            }
+        }
+        defended
+    }
+
+    // Counts how many pieces from a team attack each square
+    // board_info must be from the perspective of the team given by white
+    pub fn attacker_count(
+    white: bool,
+    board_info: BoardInfo)
+    -> [[u8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
+        let mut attacker_count = [[0u8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let id = board_info.board[x][y];
+
+                if id != 0 && piece_white(id) == white {
+                    let attacks_board = gen_attacks(crate::coordinates_from_usize([x, y]), board_info);
+
+                    // Add the attacks from this piece to the attacker count
+                    for attack_x in 0..BOARD_SIZE[0] {
+                        for attack_y in 0..BOARD_SIZE[1] {
+                            attacker_count[attack_x][attack_y] += attacks_board[attack_x][attack_y] as u8;
+                        }
+                    }
+                }
+            }
+        }
+        attacker_count
+    }
+
+    // Counts how many pieces from the enemy team attack each square
+    // Flips boards to the enemy perspective like gen_enemy_moves
+    pub fn enemy_attacker_count(
+    caller_white: bool,
+    board_info: BoardInfo)
+    -> [[u8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
+        let enemy_count = attacker_count(!caller_white, crate::flip_board_info(board_info));
+
+        // Flip the count back to the perspective of the caller team
+        let mut attacker_count = [[0u8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+        for (x, column) in enemy_count.iter().enumerate() {
+            for (y, count) in column.iter().enumerate() {
+                let coordinates_flip = crate::coordinates_to_usize(crate::flip_coordinates(crate::coordinates_from_usize([x, y])));
+                attacker_count[coordinates_flip[0]][coordinates_flip[1]] = *count;
+            }
+        }
+        attacker_count
+    }
+
     // Generates all moves of the enemy team
     // Flip boards to enemy perspective to fix the problem where enemy pawns move backwards
     pub fn gen_enemy_moves(
@@ -559,24 +906,60 @@ pub mod moves {
         crate::flip_board(enemy_moves) // Flip enemy moves again to get back to perspective of the caller team
     }
         
+    // Returns the castling_rights index for a rook starting square
+    // rook_coordinates are from the perspective of the team stated in perspective_white
+    pub fn castle_right_index(perspective_white: bool, rook_coordinates: [i8; 2]) -> Option<usize> {
+        let mut rook_coordinates = rook_coordinates;
+        if !perspective_white {
+            rook_coordinates = crate::flip_coordinates(rook_coordinates);
+        }
+
+        let y_max = i8::try_from(BOARD_SIZE[1] - 1).unwrap();
+        let x_max = i8::try_from(BOARD_SIZE[0] - 1).unwrap();
+        if rook_coordinates == [x_max, 0] {
+            Some(0)
+        } else if rook_coordinates == [0, 0] {
+            Some(1)
+        } else if rook_coordinates == [x_max, y_max] {
+            Some(2)
+        } else if rook_coordinates == [0, y_max] {
+            Some(3)
+        } else {
+            None
+        }
+    }
+
     // Given original piece coordinates and move coordinates this function checks if the move coordinates are valid for a castle
     // Function assumes piece at piece_coordinates is a king
     // If a castle is possible a new board is returned where the king and rook pieces have castled, otherwise the original board is returned
+    // Only the kings starting and transit squares are checked for attacks
     fn castle(
     piece_coordinates: [i8; 2],
     move_coordinates: [i8; 2],
-    enemy_moves_board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
     board_info: BoardInfo)
     -> [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
 
         let mut board = board_info.board;
-        let turns_board = board_info.turns_board;
+        let castling_rights = board_info.castling_rights;
         let pieces = board_info.pieces;
 
         let id = get_board(piece_coordinates, board);
-        
-        // Check the piece being moved is a king, the king has moves 0 times, and the king is not in check
-        if get_board(piece_coordinates, turns_board) == 0 && get_board(piece_coordinates, enemy_moves_board) == 0 {
+        let white = piece_white(id);
+
+        // is_attacked only knows the standard pieces, so boards with custom pieces fall back to the enemy move board
+        let enemy_moves_board = match has_custom_pieces(board) {
+            true => Some(gen_enemy_moves(white, board_info)),
+            false => None,
+        };
+        let attacked = move |square: [i8; 2]| -> bool {
+            match enemy_moves_board {
+                Some(enemy_moves_board) => get_board(square, enemy_moves_board) == 1, // Pawn pushes (2) don't attack
+                None => is_attacked(square, !white, board),
+            }
+        };
+
+        // Check the king is not in check
+        if !attacked(piece_coordinates) {
 
             // King castle mdirs
             let king_mdir_repeats: usize = 2; // How many times to repeat king_mdirs to get to castle position
@@ -585,13 +968,6 @@ pub mod moves {
                 [-1, 0],
             ];
             
-            // Rook castle mdirs
-            // Rook mdirs do not get repeated like the kings do
-            let rook_mdirs: [[i8; 2]; 2] = [
-                [-2, 0],
-                [3, 0],
-            ];
-
             // Where the rooks have to be inorder to perform a castle
             let rook_coordinates: [[i8; 2]; 2] = [
                 [7, 0],
@@ -600,13 +976,36 @@ pub mod moves {
 
             // Repeat twice because there are 2 directions which a king can castle into
             for i in 0..2 {
-                if get_board(rook_coordinates[i], board) == pieces[1].id && get_board(rook_coordinates[i], turns_board) == 0 { // Check the rook for this castle direction is in the correct position and has moved 0 times
-
+                let rook_id = get_board(rook_coordinates[i], board);
+                let castle_right = match castle_right_index(white, rook_coordinates[i]) {
+                    Some(index) => castling_rights[index],
+                    None => false,
+                };
+                if friendly_piece(id, rook_id) && rook_id.abs() == pieces[1].id && castle_right { // Check a friendly rook for this castle direction is in the correct position and the team can still castle this way
+
+                    // The rook moves to the square the king passes over
                     let move_coordinates_rook = [
-                        rook_coordinates[i][0] + rook_mdirs[i][0],
-                        rook_coordinates[i][1] + rook_mdirs[i][1],
+                        piece_coordinates[0] + king_mdirs[i][0],
+                        piece_coordinates[1] + king_mdirs[i][1],
                     ];
 
+                    // All squares between the king and rook have to be empty
+                    let mut path_empty = true;
+                    let mut path_coordinates = move_coordinates_rook;
+                    while path_coordinates != rook_coordinates[i] {
+                        if !fits_in_board(path_coordinates) || get_board(path_coordinates, board) != 0 { // The rook has to be on the same row as the king
+                            path_empty = false;
+                            break;
+                        }
+                        path_coordinates = [
+                            path_coordinates[0] + king_mdirs[i][0],
+                            path_coordinates[1] + king_mdirs[i][1],
+                        ];
+                    }
+                    if !path_empty {
+                        continue;
+                    }
+
                     let mut piece_coordinates_current = piece_coordinates;
                     for j in 0..king_mdir_repeats {
 
@@ -616,7 +1015,7 @@ pub mod moves {
                         ];
 
                         // Ensures the king cannot into check or through an enemy sightline
-                        if get_board(move_coordinates_king, board) != 0 || get_board(move_coordinates_king, enemy_moves_board) != 0 {
+                        if get_board(move_coordinates_king, board) != 0 || attacked(move_coordinates_king) {
                             break;
                         } else if move_coordinates_king == move_coordinates && j > 0 { // A castle is valid when these conditions are met and the first if conditions are not met
                             board = move_board_value(piece_coordinates, move_coordinates_king, 0, board); // Move king to castled position
@@ -697,7 +1096,7 @@ pub mod moves {
                                     let post_move_board = crate::move_board_value(piece_coordinates, move_coordinates, 0, board_info.board);
 
                                     // Remove any force capture coordinates
-                                    let post_move_board = match piece_moves.capture_coordinates {
+                                    let post_move_board = match move_capture_coordinates(piece_moves, piece_coordinates, move_coordinates) {
                                         Some(capture_coordinates) => crate::set_board(capture_coordinates, 0, post_move_board),
                                         None => post_move_board,
                                     };
@@ -730,6 +1129,26 @@ pub mod moves {
     piece_coordinates: [i8; 2],
     move_coordinates: [i8; 2],
     promotion_id: i8, // Promotion id should always be positive
+    board_info: BoardInfo)
+    -> BoardInfo {
+        make_move_board(piece_coordinates, move_coordinates, promotion_id, true, board_info)
+    }
+
+    // Same as gen_move_board, but the king is a normal piece, it can't castle and can be left attacked (E.g. antichess)
+    pub fn gen_pseudo_move_board(
+    piece_coordinates: [i8; 2],
+    move_coordinates: [i8; 2],
+    promotion_id: i8,
+    board_info: BoardInfo)
+    -> BoardInfo {
+        make_move_board(piece_coordinates, move_coordinates, promotion_id, false, board_info)
+    }
+
+    fn make_move_board(
+    piece_coordinates: [i8; 2],
+    move_coordinates: [i8; 2],
+    promotion_id: i8,
+    king_safety: bool, // Castles are allowed and the teams king can't be left in check
     mut board_info: BoardInfo)
     -> BoardInfo {
         use crate::board::errors;
@@ -746,9 +1165,8 @@ pub mod moves {
 
         // Castle
         let mut castle_board = board;
-        if id == pieces[5].id {
-            let enemy_moves = gen_enemy_moves(piece_white, board_info);
-            castle_board = castle(piece_coordinates, move_coordinates, enemy_moves, board_info);
+        if id.abs() == pieces[5].id && king_safety {
+            castle_board = castle(piece_coordinates, move_coordinates, board_info);
         }
         if castle_board != board {
             move_valid = true;
@@ -767,7 +1185,7 @@ pub mod moves {
             board_info,
         );
         let possible_moves = moves.moves_board;
-        let capture_coordinates = moves.capture_coordinates;
+        let capture_coordinates = move_capture_coordinates(moves, piece_coordinates, move_coordinates);
 
         // If possible_moves at move_coordinates != 0 then the piece can move there
         if get_board(move_coordinates, possible_moves) != 0 && !move_valid {
@@ -812,8 +1230,7 @@ pub mod moves {
             board_info_pm.board = post_move_board;
             
             // If the king isn't in check after the move, then the move is valid
-            let king_check_state = get_check_state(piece_white, false, board_info_pm);
-            if !king_check_state.check {
+            if !king_safety || !get_check_state(piece_white, false, board_info_pm).check {
                 move_valid = true;
             } else {
                 error = errors::CHECK_ERROR;
@@ -823,6 +1240,21 @@ pub mod moves {
         }
 
         if move_valid {
+            // Moving the king loses both castle rights, moving a rook or capturing a rook loses the right for that rook
+            if id.abs() == pieces[5].id {
+                let mut team_rights = 0;
+                if !piece_white {
+                    team_rights = 2;
+                }
+                board_info_pm.castling_rights[team_rights] = false;
+                board_info_pm.castling_rights[team_rights + 1] = false;
+            }
+            for coordinates in [piece_coordinates, move_coordinates] {
+                if let Some(index) = castle_right_index(piece_white, coordinates) {
+                    board_info_pm.castling_rights[index] = false;
+                }
+            }
+
             // Move and increment piece turns
             board_info_pm.turns_board = move_board_value(piece_coordinates, move_coordinates, 0, board_info_pm.turns_board);
             let turns = get_board(move_coordinates, board_info_pm.turns_board);
@@ -830,8 +1262,9 @@ pub mod moves {
             
             board_info_pm.turns_board = set_board(move_coordinates, turns, board_info_pm.turns_board);
 
-            // Set last moved piece
+            // Set last moved piece, the move uses up any en passant square
             board_info_pm.last_turn_coordinates = move_coordinates;
+            board_info_pm.en_passant = None;
             
             return board_info_pm;
         }
@@ -840,95 +1273,746 @@ pub mod moves {
         board_info.error_code = error;
         board_info
     }
-    
-
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use crate::fen;
 
-        // gen_moves tests ---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
-        #[test]
-        fn queen_sliding_test() { // Test generating queen moves, where some directions are blocked by enemy or friendly pieces
-            let board_info = BoardInfo {
-                board: fen::decode("8/2P5/8/P3p3/8/2Q5/8/8"),
-                turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
-                last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
-            };
+    // Returns all legal moves for the team whose turn it is in game_state
+    // Pawns are assumed to promote to queens
+    pub fn legal_moves(game_state: crate::board::turn::GameState) -> Vec<Move> {
+        legal_moves_masked(game_state, None)
+    }
 
-            let moves_board = gen_moves(
-                [2, 2],
-                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
-                board_info,
-            );
+    // Same as legal_moves, but only returns moves of pieces whose ids (E.g. IDS[2] for knights, either team) are in piece_ids
+    // All pieces are included when piece_ids is None
+    pub fn legal_moves_masked(game_state: crate::board::turn::GameState, piece_ids: Option<&[i8]>) -> Vec<Move> {
+        let board_info = game_state.board_info;
+        let mut legal_moves = Vec::new();
 
-            let expected = Moves {
-                moves_board: [[1, 0, 1, 0, 0, 0, 0, 0], [0, 1, 1, 1, 0, 0, 0, 0], [1, 1, 0, 1, 1, 1, 0, 0], [0, 1, 1, 1, 0, 0, 0, 0], [1, 0, 1, 0, 1, 0, 0, 0], [0, 0, 1, 0, 0, 0, 0, 0], [0, 0, 1, 0, 0, 0, 0, 0], [0, 0, 1, 0, 0, 0, 0, 0]],
-                capture_coordinates: None,
-            };
-            assert_eq!(moves_board, expected);
-        }
+        let in_mask = |piece_id: i8| -> bool {
+            match piece_ids {
+                Some(piece_ids) => piece_ids.contains(&piece_id.abs()),
+                None => true,
+            }
+        };
 
-        #[test]
-        fn en_passant_test() { // Test en passant
-            let board_info = BoardInfo {
-                board: fen::decode("8/8/8/5pP1/8/8/8/8"),
-                turns_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0],  [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 1, 0, 0, 0], [0, 0, 0, 0, 1, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
-                last_turn_coordinates: [5, 4],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
-            };
+        // Antichess needs the moves of every piece to know if a capture is forced
+        let antichess = game_state.variant == crate::board::Variant::Antichess;
 
-            let moves_board = gen_moves(
-                [6, 4],
-                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
-                board_info,
-            );
+        for_each_legal_move(game_state, |piece_id| antichess || in_mask(piece_id), |legal_move| legal_moves.push(legal_move));
 
-            let expected = Moves {
-                moves_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 1, 0, 0], [0, 0, 0, 0, 0, 2, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
-                capture_coordinates: Some([5, 4]),
-            };
-            assert_eq!(moves_board, expected);
+        // Antichess captures are forced
+        if antichess {
+            if legal_moves.iter().any(|legal_move| is_capture(*legal_move, game_state)) {
+                legal_moves.retain(|legal_move| is_capture(*legal_move, game_state));
+            }
+            legal_moves.retain(|legal_move| in_mask(get_board(legal_move.piece_coordinates, board_info.board)));
         }
+        legal_moves
+    }
 
-        #[test]
-        fn double_move_test() { // Test pawn double move
-            let board_info = BoardInfo {
-                board: fen::decode("8/8/8/8/8/8/1P6/8"),
-                turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
-                last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
-            };
+    // Returns the number of legal moves for the team whose turn it is, the same as legal_moves(game_state).len() without building a list of moves
+    pub fn legal_move_count(game_state: crate::board::turn::GameState) -> usize {
+        let antichess = game_state.variant == crate::board::Variant::Antichess;
 
-            let moves_board = gen_moves(
-                [1, 1],
-                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
-                board_info,
-            );
+        let mut count: usize = 0;
+        let mut captures: usize = 0;
+        for_each_legal_move(game_state, |_| true, |legal_move| {
+            count += 1;
+            if antichess && is_capture(legal_move, game_state) {
+                captures += 1;
+            }
+        });
 
-            let expected = Moves {
-                moves_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 2, 2, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
-                capture_coordinates: None,
-            };
-            assert_eq!(moves_board, expected);
+        // Antichess captures are forced, so only they count when there are any
+        if captures > 0 {
+            return captures;
         }
+        count
+    }
 
-        #[test]
-        fn special_capture_test() { // Test pawn special capture direction
+    // Calls visit with every legal move of the pieces whose ids pass piece_filter, for the team whose turn it is
+    // Forced antichess captures are left to the caller
+    fn for_each_legal_move<F: Fn(i8) -> bool, V: FnMut(Move)>(game_state: crate::board::turn::GameState, piece_filter: F, mut visit: V) {
+        use crate::coordinates_from_usize;
+
+        let board_info = game_state.board_info;
+
+        for piece_x in 0..BOARD_SIZE[0] {
+            for piece_y in 0..BOARD_SIZE[1] {
+                let piece_coordinates = coordinates_from_usize([piece_x, piece_y]);
+                let piece_id = get_board(piece_coordinates, board_info.board);
+
+                // Only get moves for pieces from the team whose turn it is
+                if piece_id == 0 || piece_white(piece_id) != game_state.whites_turn {
+                    continue;
+                }
+
+                if !piece_filter(piece_id) {
+                    continue;
+                }
+
+                let piece_moves = gen_moves(piece_coordinates, [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], board_info);
+
+                for move_x in 0..BOARD_SIZE[0] {
+                    for move_y in 0..BOARD_SIZE[1] {
+                        let move_coordinates = coordinates_from_usize([move_x, move_y]);
+
+                        // Castles are not in the moves board so king moves of 2 squares along the row have to be tried as well
+                        let castle_move = piece_id.abs() == info::IDS[5] && move_coordinates[1] == piece_coordinates[1] && (move_coordinates[0] - piece_coordinates[0]).abs() == 2;
+                        if get_board(move_coordinates, piece_moves.moves_board) == 0 && !castle_move {
+                            continue;
+                        }
+
+                        // Atomic captures cannot explode the moving teams king
+                        if game_state.variant == crate::board::Variant::Atomic && !crate::board::turn::atomic_capture_safe(piece_coordinates, move_coordinates, board_info) {
+                            continue;
+                        }
+
+                        // gen_move_board checks the move doesn't leave the king in check
+                        let board_info_new = match game_state.variant {
+                            crate::board::Variant::Antichess => gen_pseudo_move_board(piece_coordinates, move_coordinates, info::IDS[4], board_info),
+                            _ => gen_move_board(piece_coordinates, move_coordinates, info::IDS[4], board_info),
+                        };
+                        if board_info_new.error_code == 0 {
+                            visit(Move {
+                                piece_coordinates,
+                                move_coordinates,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns a uniformly random legal move for the team whose turn it is, or None when the team has no legal moves
+    // Pawns are assumed to promote to queens, the same as legal_moves
+    pub fn random_legal_move<R: rand::Rng>(game_state: crate::board::turn::GameState, rng: &mut R) -> Option<Move> {
+        use rand::seq::SliceRandom;
+
+        legal_moves(game_state).choose(rng).copied()
+    }
+
+    // Returns the legal moves for the team whose turn it is which pass a filter
+    // The filter is given each move and the board after it is played, still from the perspective of the team making the move
+    pub fn legal_moves_where<F: Fn(Move, BoardInfo) -> bool>(game_state: crate::board::turn::GameState, filter: F) -> Vec<Move> {
+        legal_moves(game_state)
+            .into_iter()
+            .filter(|legal_move| filter(*legal_move, gen_move_board(legal_move.piece_coordinates, legal_move.move_coordinates, info::IDS[4], game_state.board_info)))
+            .collect()
+    }
+
+    // Returns false if the king of the team that just moved is attacked, which makes the position illegal
+    pub fn position_is_legal(game_state: crate::board::turn::GameState) -> bool {
+        let mut enemy_king_id = info::IDS[5];
+        if game_state.whites_turn {
+            enemy_king_id *= -1;
+        }
+
+        let enemy_king_coordinates = match crate::find_id_in_board(enemy_king_id, game_state.board_info.board) {
+            Some(coordinates) => coordinates,
+            None => return true,
+        };
+
+        let moves_board = gen_all_moves(game_state.whites_turn, None, game_state.board_info);
+        get_board(enemy_king_coordinates, moves_board) == 0
+    }
+
+    // Returns the squares of the enemy pieces giving check to the king of the team whose turn it is, from the perspective of the team to move
+    // A double check returns both pieces, the list is empty when there is no check (always in antichess) or no king
+    pub fn checkers(game_state: crate::board::turn::GameState) -> Vec<[i8; 2]> {
+        let mut king_id = info::IDS[5];
+        if !game_state.whites_turn {
+            king_id *= -1;
+        }
+
+        let mut checkers = Vec::new();
+        if game_state.variant == crate::board::Variant::Antichess {
+            return checkers;
+        }
+        let king_coordinates = match crate::find_id_in_board(king_id, game_state.board_info.board) {
+            Some(coordinates) => crate::flip_coordinates(coordinates),
+            None => return checkers,
+        };
+
+        // Enemy pieces attack from the enemy perspective so pawns capture the right way
+        let board_info = crate::flip_board_info(game_state.board_info);
+        for x in 0..BOARD_SIZE[0] {
+            for y in 0..BOARD_SIZE[1] {
+                let id = board_info.board[x][y];
+                if id == 0 || piece_white(id) == game_state.whites_turn {
+                    continue;
+                }
+
+                let piece_coordinates = crate::coordinates_from_usize([x, y]);
+                if get_board(king_coordinates, gen_attacks(piece_coordinates, board_info)) == 1 {
+                    checkers.push(crate::flip_coordinates(piece_coordinates));
+                }
+            }
+        }
+        checkers
+    }
+
+    // Returns all legal moves which put the enemy king in check
+    pub fn checking_moves(game_state: crate::board::turn::GameState) -> Vec<Move> {
+        let mut checking_moves = Vec::new();
+
+        for legal_move in legal_moves(game_state) {
+            let board_info_new = gen_move_board(legal_move.piece_coordinates, legal_move.move_coordinates, info::IDS[4], game_state.board_info);
+
+            // Flip board_info to get the enemy perspective for get_check_state
+            if get_check_state(!game_state.whites_turn, false, crate::flip_board_info(board_info_new)).check {
+                checking_moves.push(legal_move);
+            }
+        }
+        checking_moves
+    }
+
+    // Returns true if chess_move leaves the enemy with no legal moves while not in check, without the rest of new_turn
+    // Illegal moves never stalemate
+    pub fn stalemates(chess_move: Move, game_state: crate::board::turn::GameState) -> bool {
+        let board_info_new = gen_move_board(chess_move.piece_coordinates, chess_move.move_coordinates, info::IDS[4], game_state.board_info);
+        if board_info_new.error_code != 0 {
+            return false;
+        }
+
+        let check_state = get_check_state(!game_state.whites_turn, true, crate::flip_board_info(board_info_new));
+        check_state.mate && !check_state.check
+    }
+
+    // Returns true if chess_move captures a piece, including en passant, without making the move
+    // The move is assumed to be pseudo legal for the team whose turn it is
+    // Returns true if chess_move is a pawn moving to the last row, where it has to promote
+    pub fn is_promotion(chess_move: Move, game_state: crate::board::turn::GameState) -> bool {
+        let id = get_board(chess_move.piece_coordinates, game_state.board_info.board);
+        id.abs() == info::IDS[0] && crate::coordinates_to_usize(chess_move.move_coordinates)[1] == BOARD_SIZE[1] - 1
+    }
+
+    pub fn is_capture(chess_move: Move, game_state: crate::board::turn::GameState) -> bool {
+        let board_info = game_state.board_info;
+        let id = get_board(chess_move.piece_coordinates, board_info.board);
+        let target_id = get_board(chess_move.move_coordinates, board_info.board);
+        if id == 0 {
+            return false;
+        }
+        if target_id != 0 {
+            return piece_white(target_id) != piece_white(id);
+        }
+
+        // En passant, a pawn moving diagonally onto the en passant square
+        id.abs() == info::IDS[0]
+            && chess_move.move_coordinates[0] != chess_move.piece_coordinates[0]
+            && board_info.en_passant == Some(chess_move.move_coordinates)
+    }
+
+    // Returns true if the position is quiet enough for a static evaluation to be trusted
+    // The team to move isn't in check, has no captures winning material by static exchange, and neither team has a pawn one move from promoting
+    pub fn is_quiet(game_state: crate::board::turn::GameState) -> bool {
+        use crate::algorithm::eval::see;
+
+        let board_info = game_state.board_info;
+        if get_check_state(game_state.whites_turn, false, board_info).check {
+            return false;
+        }
+
+        // The team to move promotes on the last row, the enemy on the first
+        let last_row = BOARD_SIZE[1] - 1;
+        for x in 0..BOARD_SIZE[0] {
+            for (y, team_to_move) in [(last_row - 1, true), (1, false)] {
+                let id = board_info.board[x][y];
+                if id.abs() == info::IDS[0] && (piece_white(id) == game_state.whites_turn) == team_to_move {
+                    return false;
+                }
+            }
+        }
+
+        !legal_moves(game_state).iter().any(|legal_move| is_capture(*legal_move, game_state) && see(*legal_move, game_state) > 0)
+    }
+
+    // Returns the legal destination squares of the piece on square as a grid, for highlighting moves in a ui
+    // square and the grid are from the perspective of the team to move, castles mark the kings destination and en passant the square the pawn moves to
+    pub fn move_overlay(square: [i8; 2], game_state: crate::board::turn::GameState) -> [[bool; BOARD_SIZE[0]]; BOARD_SIZE[1]] {
+        let mut overlay = [[false; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+        for legal_move in legal_moves(game_state) {
+            if legal_move.piece_coordinates == square {
+                let move_coordinates = crate::coordinates_to_usize(legal_move.move_coordinates);
+                overlay[move_coordinates[0]][move_coordinates[1]] = true;
+            }
+        }
+        overlay
+    }
+
+    // Returns which castles are legal right now, indexed like castling_rights (white king side, white queen side, black king side, black queen side)
+    // A castle needs the right, empty squares between the king and rook, and a king that isn't in check and doesn't pass through or into check
+    // The team that isn't moving is checked as if it were its turn
+    pub fn castling_available(game_state: crate::board::turn::GameState) -> [bool; 4] {
+        let mut available = [false; 4];
+
+        let mut enemy_state = game_state;
+        enemy_state.board_info = crate::flip_board_info(game_state.board_info);
+        enemy_state.board_info.last_turn_coordinates = [0, 0];
+        enemy_state.board_info.en_passant = None;
+        enemy_state.whites_turn = !game_state.whites_turn;
+
+        for state in [game_state, enemy_state] {
+            let white = state.whites_turn;
+            let mut king_id = info::IDS[5];
+            if !white {
+                king_id *= -1;
+            }
+            let king = match crate::find_id_in_board(king_id, state.board_info.board) {
+                Some(king) => king,
+                None => continue,
+            };
+
+            for direction in [1, -1] {
+                let castle = Move {
+                    piece_coordinates: king,
+                    move_coordinates: [king[0] + 2 * direction, king[1]],
+                };
+                if !is_legal(castle, state) {
+                    continue;
+                }
+
+                // The king side is to the right from whites perspective and to the left from blacks
+                let mut index = usize::from((direction == 1) != white);
+                if !white {
+                    index += 2;
+                }
+                available[index] = true;
+            }
+        }
+        available
+    }
+
+    // Returns every legal capture (including en passant) for the team to move paired with its static exchange evaluation
+    // Sorted from the best exchange to the worst, so captures that lose material are last
+    pub fn captures_by_see(game_state: crate::board::turn::GameState) -> Vec<(Move, i32)> {
+        use crate::algorithm::eval::see;
+
+        let mut captures: Vec<(Move, i32)> = legal_moves(game_state).into_iter()
+            .filter(|legal_move| is_capture(*legal_move, game_state))
+            .map(|legal_move| (legal_move, i32::from(see(legal_move, game_state))))
+            .collect();
+        captures.sort_by_key(|(_, see_value)| std::cmp::Reverse(*see_value));
+        captures
+    }
+
+    // Returns all legal moves for the team whose turn it is with captures, castles, en passant, promotions, and checks flagged
+    // Pawns reaching the last row have a move for each allowed promotion piece
+    pub fn legal_move_infos(game_state: crate::board::turn::GameState) -> Vec<MoveInfo> {
+        let board_info = game_state.board_info;
+        let board = board_info.board;
+        let promotion_ids = [info::IDS[4], info::IDS[1], info::IDS[3], info::IDS[2]];
+
+        let mut move_infos = Vec::new();
+        for legal_move in legal_moves(game_state) {
+            let id = get_board(legal_move.piece_coordinates, board);
+            let target_id = get_board(legal_move.move_coordinates, board);
+
+            let mut promotions: Vec<Option<i8>> = vec![None];
+            if id.abs() == info::IDS[0] && crate::coordinates_to_usize(legal_move.move_coordinates)[1] == BOARD_SIZE[1] - 1 {
+                promotions = promotion_ids.iter()
+                    .filter(|promotion_id| game_state.allowed_promotions[usize::try_from(**promotion_id - 1).unwrap()])
+                    .map(|promotion_id| Some(*promotion_id))
+                    .collect();
+            }
+
+            for promotion in promotions {
+                let board_info_new = gen_move_board(legal_move.piece_coordinates, legal_move.move_coordinates, unwrap_def(promotion, info::IDS[4]), board_info);
+                let is_en_passant = board_info_new.capture_coordinates.is_some();
+
+                move_infos.push(MoveInfo {
+                    chess_move: legal_move,
+                    is_capture: is_en_passant || (target_id != 0 && piece_white(target_id) != piece_white(id)),
+                    is_castle: id.abs() == info::IDS[5] && (legal_move.move_coordinates[0] - legal_move.piece_coordinates[0]).abs() == 2,
+                    is_en_passant,
+                    promotion,
+                    gives_check: get_check_state(!game_state.whites_turn, false, crate::flip_board_info(board_info_new)).check,
+                });
+            }
+        }
+        move_infos
+    }
+
+    // Converts a move to a long algebraic string (e.g. e2e4, e7e8q)
+    // The move has to be from the perspective of the team stated in perspective_white, the string is always from whites perspective
+    pub fn move_to_uci(chess_move: Move, perspective_white: bool, promotion_id: Option<i8>, pieces: [info::Piece; info::MAX_PIECES]) -> String {
+        let mut piece_coordinates = chess_move.piece_coordinates;
+        let mut move_coordinates = chess_move.move_coordinates;
+        if !perspective_white {
+            piece_coordinates = crate::flip_coordinates(piece_coordinates);
+            move_coordinates = crate::flip_coordinates(move_coordinates);
+        }
+
+        let mut uci = crate::cart_to_ccn(piece_coordinates).unwrap();
+        uci.push_str(&crate::cart_to_ccn(move_coordinates).unwrap());
+        if let Some(promotion_id) = promotion_id {
+            uci.push(pieces[usize::try_from(promotion_id.abs() - 1).unwrap()].id_fen.to_ascii_lowercase());
+        }
+        uci
+    }
+
+    // Returns all legal moves as long algebraic strings, which is the move format used by UCI
+    // Castles are written as the kings two square move, and pawns reaching the last row are written once for each promotion piece
+    pub fn legal_moves_uci(game_state: crate::board::turn::GameState) -> Vec<String> {
+        let board = game_state.board_info.board;
+        let pieces = game_state.board_info.pieces;
+        let promotion_ids = [info::IDS[4], info::IDS[1], info::IDS[3], info::IDS[2]];
+
+        let mut legal_moves_uci = Vec::new();
+        for legal_move in legal_moves(game_state) {
+            let id = get_board(legal_move.piece_coordinates, board);
+            let promotion = id.abs() == info::IDS[0] && crate::coordinates_to_usize(legal_move.move_coordinates)[1] == BOARD_SIZE[1] - 1;
+
+            if promotion {
+                for promotion_id in promotion_ids {
+                    if !game_state.allowed_promotions[usize::try_from(promotion_id - 1).unwrap()] {
+                        continue;
+                    }
+                    legal_moves_uci.push(move_to_uci(legal_move, game_state.whites_turn, Some(promotion_id), pieces));
+                }
+            } else {
+                legal_moves_uci.push(move_to_uci(legal_move, game_state.whites_turn, None, pieces));
+            }
+        }
+        legal_moves_uci
+    }
+
+    // Why a coordinate move string couldn't be turned into a move
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum ParseError {
+        Invalid, // The string isn't two squares with an optional promotion piece
+        Illegal, // The move isn't legal for the team to move
+    }
+
+    // Parses a move written as two squares for the team to move into a move and a promotion id, for simple front ends
+    // Accepts "e2 e4", "e2-e4", and "e2e4" with an optional promotion piece in either case (e.g. "e7-e8q", "e7 e8=Q")
+    // Castles are written as the kings two square move, pawns promote to queens unless a promotion piece is given
+    pub fn parse_coordinate_move(s: &str, game_state: crate::board::turn::GameState) -> Result<(Move, i8), ParseError> {
+        let uci: String = s.trim().chars()
+            .filter(|c| !c.is_whitespace() && *c != '-' && *c != '=')
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let (chess_move, promotion_id) = crate::uci::parse_move(&uci, game_state.whites_turn)?;
+
+        if !legal_moves(game_state).contains(&chess_move) {
+            return Err(ParseError::Illegal);
+        }
+        Ok((chess_move, promotion_id))
+    }
+
+    // Returns true if the board has any custom pieces (ids after IDS.len())
+    fn has_custom_pieces(board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> bool {
+        board.iter().flatten().any(|id| usize::try_from(id.abs()).unwrap() > info::IDS.len())
+    }
+
+    // Returns true if the square is attacked by the team given by by_white
+    // board has to be from the perspective of the other (defending) team, so attacking pawns move down the board
+    // Looks outwards from the square and returns on the first attacker instead of generating every enemy move, only the standard pieces are checked
+    pub fn is_attacked(square: [i8; 2], by_white: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> bool {
+        use crate::board::MAX_SLIDES;
+        use info::IDS;
+
+        let mut enemy_team = -1;
+        if by_white {
+            enemy_team = 1;
+        }
+
+        // Sliding pieces and kings
+        let directions: [[i8; 2]; 8] = [[1, 0], [-1, 0], [0, 1], [0, -1], [1, 1], [1, -1], [-1, 1], [-1, -1]];
+        for (i, direction) in directions.iter().enumerate() {
+            let mut slider_id = IDS[1];
+            if i >= 4 {
+                slider_id = IDS[3];
+            }
+
+            let mut coordinates = square;
+            for distance in 1..MAX_SLIDES + 1 {
+                coordinates = [coordinates[0] + direction[0], coordinates[1] + direction[1]];
+                if !fits_in_board(coordinates) {
+                    break;
+                }
+
+                let id = get_board(coordinates, board) * enemy_team;
+                if id == slider_id || id == IDS[4] || (id == IDS[5] && distance == 1) {
+                    return true;
+                } else if id != 0 {
+                    break;
+                }
+            }
+        }
+
+        // Knights
+        let knight_mdirs: [[i8; 2]; 8] = [[1, 2], [2, 1], [1, -2], [-1, 2], [2, -1], [-2, 1], [-2, -1], [-1, -2]];
+        for mdir in knight_mdirs {
+            let coordinates = [square[0] + mdir[0], square[1] + mdir[1]];
+            if fits_in_board(coordinates) && get_board(coordinates, board) * enemy_team == IDS[2] {
+                return true;
+            }
+        }
+
+        // Enemy pawns move down the board so they attack from the row above
+        for x in [-1, 1] {
+            let coordinates = [square[0] + x, square[1] + 1];
+            if fits_in_board(coordinates) && get_board(coordinates, board) * enemy_team == IDS[0] {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // Returns the direction from the king to the enemy piece pinning the piece at piece_coordinates, if it is pinned
+    fn pin_direction(piece_coordinates: [i8; 2], king_coordinates: [i8; 2], white: bool, board: [[i8; BOARD_SIZE[0]]; BOARD_SIZE[1]]) -> Option<[i8; 2]> {
+        use info::IDS;
+
+        let delta = [piece_coordinates[0] - king_coordinates[0], piece_coordinates[1] - king_coordinates[1]];
+        if delta == [0, 0] || (delta[0] != 0 && delta[1] != 0 && delta[0].abs() != delta[1].abs()) {
+            return None;
+        }
+        let direction = [delta[0].signum(), delta[1].signum()];
+
+        let mut slider_id = IDS[1];
+        if direction[0] != 0 && direction[1] != 0 {
+            slider_id = IDS[3];
+        }
+
+        let mut enemy_team = -1;
+        if !white {
+            enemy_team = 1;
+        }
+
+        // Squares between the king and the piece have to be empty, and the first piece past the piece has to be an enemy slider
+        let mut coordinates = king_coordinates;
+        let mut past_piece = false;
+        loop {
+            coordinates = [coordinates[0] + direction[0], coordinates[1] + direction[1]];
+            if !fits_in_board(coordinates) {
+                return None;
+            }
+
+            if coordinates == piece_coordinates {
+                past_piece = true;
+                continue;
+            }
+
+            let id = get_board(coordinates, board);
+            if id == 0 {
+                continue;
+            }
+            if past_piece && (id * enemy_team == slider_id || id * enemy_team == IDS[4]) {
+                return Some(direction);
+            }
+            return None;
+        }
+    }
+
+    // Returns true if new_turn would accept the move (including moves that end the game)
+    // Unpinned pieces are legal without building a post move board, other moves only check the king square once
+    pub fn is_legal(chess_move: Move, game_state: crate::board::turn::GameState) -> bool {
+        let board_info = game_state.board_info;
+        let board = board_info.board;
+        let piece_coordinates = chess_move.piece_coordinates;
+        let move_coordinates = chess_move.move_coordinates;
+
+        if !fits_in_board(piece_coordinates) || !fits_in_board(move_coordinates) {
+            return false;
+        }
+
+        let id = get_board(piece_coordinates, board);
+        if id == 0 || piece_white(id) != game_state.whites_turn {
+            return false;
+        }
+
+        if game_state.variant == crate::board::Variant::Atomic && !crate::board::turn::atomic_capture_safe(piece_coordinates, move_coordinates, board_info) {
+            return false;
+        }
+
+        // The antichess king is a normal piece and captures are forced, so the move has to be one of the legal moves
+        if game_state.variant == crate::board::Variant::Antichess {
+            return legal_moves(game_state).contains(&chess_move);
+        }
+
+        // Castles, and boards with custom pieces (is_attacked only knows the standard pieces) are validated by gen_move_board
+        if has_custom_pieces(board) || (id.abs() == info::IDS[5] && move_coordinates[1] == piece_coordinates[1] && (move_coordinates[0] - piece_coordinates[0]).abs() == 2) {
+            let board_info_new = gen_move_board(piece_coordinates, move_coordinates, info::IDS[4], board_info);
+            return board_info_new.error_code == 0;
+        }
+
+        let moves = gen_moves(piece_coordinates, [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], board_info);
+        if get_board(move_coordinates, moves.moves_board) == 0 {
+            return false;
+        }
+
+        let mut king_id = info::IDS[5];
+        if !game_state.whites_turn {
+            king_id *= -1;
+        }
+        let king_coordinates = match crate::find_id_in_board(king_id, board) {
+            Some(coordinates) => coordinates,
+            None => return true,
+        };
+
+        let capture_coordinates = move_capture_coordinates(moves, piece_coordinates, move_coordinates);
+
+        // An unpinned piece can't expose the king, unless the king is already in check or en passant removes a second piece from the row
+        if id != king_id && capture_coordinates.is_none() && !is_attacked(king_coordinates, !game_state.whites_turn, board) {
+            return match pin_direction(piece_coordinates, king_coordinates, game_state.whites_turn, board) {
+                Some(direction) => {
+                    // A pinned piece can only move along the pin
+                    let delta = [move_coordinates[0] - king_coordinates[0], move_coordinates[1] - king_coordinates[1]];
+                    delta[0] * direction[1] - delta[1] * direction[0] == 0
+                },
+                None => true,
+            };
+        }
+
+        // Check the king square on the post move board
+        let mut post_move_board = move_board_value(piece_coordinates, move_coordinates, 0, board);
+        if let Some(capture_coordinates) = capture_coordinates {
+            post_move_board = set_board(capture_coordinates, 0, post_move_board);
+        }
+
+        let mut king_coordinates = king_coordinates;
+        if id == king_id {
+            king_coordinates = move_coordinates;
+        }
+        !is_attacked(king_coordinates, !game_state.whites_turn, post_move_board)
+    }
+    
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::board::turn::GameState;
+        use crate::fen;
+
+        // gen_moves tests ---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+        #[test]
+        fn queen_sliding_test() { // Test generating queen moves, where some directions are blocked by enemy or friendly pieces
             let board_info = BoardInfo {
-                board: fen::decode("8/8/8/8/8/1pp5/2P5/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/2P5/8/P3p3/8/2Q5/8/8"), true).unwrap().board_info
+            };
+
+            let moves_board = gen_moves(
+                [2, 2],
+                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                board_info,
+            );
+
+            let expected = Moves {
+                moves_board: [[1, 0, 1, 0, 0, 0, 0, 0], [0, 1, 1, 1, 0, 0, 0, 0], [1, 1, 0, 1, 1, 1, 0, 0], [0, 1, 1, 1, 0, 0, 0, 0], [1, 0, 1, 0, 1, 0, 0, 0], [0, 0, 1, 0, 0, 0, 0, 0], [0, 0, 1, 0, 0, 0, 0, 0], [0, 0, 1, 0, 0, 0, 0, 0]],
                 capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+            };
+            assert_eq!(moves_board, expected);
+        }
+
+        #[test]
+        fn en_passant_test() { // Test en passant
+            let board_info = BoardInfo {
+                turns_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0],  [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 1, 0, 0, 0], [0, 0, 0, 0, 1, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
+                last_turn_coordinates: [5, 4],
+                castling_rights: [true; 4],
+                en_passant: Some([5, 5]),
+                ..GameState::from_board(fen::decode("8/8/8/5pP1/8/8/8/8"), true).unwrap().board_info
+            };
+
+            let moves_board = gen_moves(
+                [6, 4],
+                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                board_info,
+            );
+
+            let expected = Moves {
+                moves_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 1, 0, 0], [0, 0, 0, 0, 0, 2, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
+                capture_coordinates: Some([5, 4]),
+            };
+            assert_eq!(moves_board, expected);
+
+            // Without an en passant square the same pawns only allow the push, whatever moved last
+            let board_info = BoardInfo {
+                en_passant: None,
+                ..board_info
+            };
+            let moves_board = gen_moves([6, 4], [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]], board_info);
+            assert_eq!(moves_board.capture_coordinates, None);
+            assert_eq!(get_board([5, 5], moves_board.moves_board), 0);
+        }
+
+        #[test]
+        fn en_passant_push_test() { // Test a pawn that could capture en passant pushing forward instead, leaving the enemy pawn on the board
+            use crate::board::turn::new_turn;
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+            let push = new_turn([4, 4], [4, 5], 0, game_state).unwrap();
+            assert_eq!(push.to_fen(), "4k3/8/4P3/3p4/8/8/8/4K3 b - - 0 2");
+
+            let capture = new_turn([4, 4], [3, 5], 0, game_state).unwrap();
+            assert_eq!(capture.to_fen(), "4k3/8/3P4/8/8/8/8/4K3 b - - 0 2");
+        }
+
+        #[test]
+        fn en_passant_pin_test() { // Test en passant being illegal when removing both pawns from the row exposes the king
+            use crate::board::turn::new_turn;
+            use crate::board::turn::GameState;
+            use crate::board::turn::IllegalReason;
+
+            let en_passant = Move {
+                piece_coordinates: [1, 4],
+                move_coordinates: [2, 5],
+            };
+
+            let game_state = GameState::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").unwrap();
+            assert!(!legal_moves(game_state).contains(&en_passant));
+            assert_eq!(new_turn(en_passant.piece_coordinates, en_passant.move_coordinates, 0, game_state).unwrap_err().reason, Some(IllegalReason::KingInCheck));
+            assert_eq!(crate::algorithm::perft::perft(game_state, 1), 4);
+
+            // Without the rook the capture is legal
+            let game_state = GameState::from_fen("8/8/8/KPp5/8/8/8/7k w - c6 0 1").unwrap();
+            assert!(legal_moves(game_state).contains(&en_passant));
+
+            // The same pin for black, by a queen
+            let game_state = GameState::from_fen("8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1").unwrap();
+            let en_passant = Move {
+                piece_coordinates: [3, 4],
+                move_coordinates: [4, 5],
+            };
+            assert!(is_capture(en_passant, game_state));
+            assert!(!legal_moves(game_state).contains(&en_passant));
+        }
+
+        #[test]
+        fn double_move_test() { // Test pawn double move
+            let board_info = BoardInfo {
+                turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                last_turn_coordinates: [0i8; 2],
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/8/8/8/1P6/8"), true).unwrap().board_info
+            };
+
+            let moves_board = gen_moves(
+                [1, 1],
+                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                board_info,
+            );
+
+            let expected = Moves {
+                moves_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 2, 2, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
+                capture_coordinates: None,
+            };
+            assert_eq!(moves_board, expected);
+        }
+
+        #[test]
+        fn special_capture_test() { // Test pawn special capture direction
+            let board_info = BoardInfo {
+                turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                last_turn_coordinates: [0i8; 2],
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/8/8/1pp5/2P5/8"), true).unwrap().board_info
             };
 
             let moves_board = gen_moves(
@@ -943,17 +2027,35 @@ pub mod moves {
             };
             assert_eq!(moves_board, expected);
         }
+
+        #[test]
+        fn knight_moves_test() { // Test a knight in the corner jumping over pieces, capturing an enemy, and not taking a friendly piece
+            let board_info = BoardInfo {
+                turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                last_turn_coordinates: [0i8; 2],
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/8/8/1p6/P1P5/N7"), true).unwrap().board_info
+            };
+
+            let moves_board = gen_moves(
+                [0, 0],
+                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                board_info,
+            );
+
+            let mut expected = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+            expected[1][2] = 1;
+            assert_eq!(moves_board.moves_board, expected);
+        }
         // gen_moves tests ---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
 
         #[test]
         fn gen_all_moves_test() { // Test generating all moves for white pieces on a board
             let board_info = BoardInfo {
-                board: fen::decode("8/3b3r/5p2/b1p1p3/3p4/8/2Q2P2/R7"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/3b3r/5p2/b1p1p3/3p4/8/2Q2P2/R7"), true).unwrap().board_info
             };
 
             let moves_board = gen_all_moves(
@@ -969,12 +2071,10 @@ pub mod moves {
         #[test]
         fn gen_enemy_moves_test() { // Test generating all enemy moves
             let board_info = BoardInfo {
-                board: fen::decode("8/p1q3r1/8/4P3/8/2N5/8/6P1"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/p1q3r1/8/4P3/8/2N5/8/6P1"), true).unwrap().board_info
             };
 
             let moves_board = gen_enemy_moves(
@@ -982,27 +2082,79 @@ pub mod moves {
                 board_info,
             );
 
-            let expected = [[0, 0, 0, 0, 2, 2, 0, 0], [0, 0, 0, 0, 0, 1, 1, 1], [0, 0, 1, 1, 1, 1, 0, 1], [0, 0, 0, 0, 0, 1, 1, 1], [0, 0, 0, 0, 1, 0, 1, 0], [0, 0, 0, 0, 0, 0, 1, 0], [1, 1, 1, 1, 1, 1, 0, 1], [0, 0, 0, 0, 0, 0, 1, 0]];
+            // a5 is a pawn push and attacked by the queen, a6 is only a pawn push
+            let expected = [[0, 0, 0, 0, 1, 2, 0, 0], [0, 0, 0, 0, 0, 1, 1, 1], [0, 0, 1, 1, 1, 1, 0, 1], [0, 0, 0, 0, 0, 1, 1, 1], [0, 0, 0, 0, 1, 0, 1, 0], [0, 0, 0, 0, 0, 0, 1, 0], [1, 1, 1, 1, 1, 1, 0, 1], [0, 0, 0, 0, 0, 0, 1, 0]];
             assert_eq!(moves_board, expected);
         }
 
+        #[test]
+        fn gen_enemy_moves_push_test() { // Test a pawn push square that a rook attacks staying attacked, whichever piece is generated last
+            let board_info = BoardInfo {
+                turns_board: [[1i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                last_turn_coordinates: [0i8; 2],
+                castling_rights: [false; 4],
+                ..GameState::from_board(fen::decode("4k3/8/8/8/8/4p3/7r/K7"), true).unwrap().board_info
+            };
+
+            let moves_board = gen_enemy_moves(true, board_info);
+            assert_eq!(get_board([4, 1], moves_board), 1); // e2 is attacked by the rook and a pawn push
+            assert_eq!(get_board([3, 1], moves_board), 1);
+
+            let board_info = BoardInfo {
+                board: fen::decode("4k3/8/8/8/8/4p3/8/K7"),
+                ..board_info
+            };
+            assert_eq!(get_board([4, 1], gen_enemy_moves(true, board_info)), 2); // e2 is only a pawn push
+        }
+
+        #[test]
+        fn attacker_count_test() { // Test counting attackers, where a square is attacked by two rooks
+            let board_info = BoardInfo {
+                turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                last_turn_coordinates: [0i8; 2],
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/8/8/8/4P3/R6R"), true).unwrap().board_info
+            };
+
+            let result = attacker_count(true, board_info);
+
+            assert_eq!(result[3][0], 2); // d1 is attacked by both rooks
+            assert_eq!(result[7][0], 1); // h1 is defended by the a1 rook
+            assert_eq!(result[5][2], 1); // f3 is attacked by the pawn
+            assert_eq!(result[4][2], 0); // e3 is not attacked, pawns only attack diagonally
+            assert_eq!(result[3][3], 0);
+        }
+
+        #[test]
+        fn enemy_attacker_count_test() { // Test counting attackers of the enemy team, with pawns attacking down the board
+            let board_info = BoardInfo {
+                turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
+                last_turn_coordinates: [0i8; 2],
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/3p4/8/8/8/8"), true).unwrap().board_info
+            };
+
+            let result = enemy_attacker_count(true, board_info);
+
+            assert_eq!(result[2][3], 1);
+            assert_eq!(result[4][3], 1);
+            assert_eq!(result[2][5], 0);
+        }
+
         // castle tests ------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
         #[test]
         fn left_castle_test() { // Test king trying to castle left with no obstacles
             let board = fen::decode("8/8/8/8/8/8/8/R3K2R");
             let board_info = BoardInfo {
-                board: board,
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(board, true).unwrap().board_info
             };
 
             let result = castle(
                 [4, 0],
                 [2, 0],
-                [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 board_info,
             );
 
@@ -1013,37 +2165,71 @@ pub mod moves {
 
         #[test]
         fn block_castle_test() { // Test king trying to castle through an obstacle
-            let board = fen::decode("8/8/8/8/8/8/8/R3K2R");
+            let board = fen::decode("5r2/8/8/8/8/8/8/R3K2R");
             let board_info = BoardInfo {
-                board: board,
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(board, true).unwrap().board_info
             };
 
             let result = castle(
                 [4, 0],
                 [6, 0],
-                [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [1, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
                 board_info,
             );
 
             assert_eq!(result, board);
         }
+
+        #[test]
+        fn castle_path_test() { // Test a piece next to the rook blocking the long castle, even though the king doesn't pass over it
+            use crate::board::turn::GameState;
+
+            let board_info = GameState::from_fen("4k3/8/8/8/8/8/8/RN2K2R w KQ - 0 1").unwrap().board_info;
+            assert_eq!(castle([4, 0], [2, 0], board_info), board_info.board);
+            assert_eq!(castle([4, 0], [6, 0], board_info), fen::decode("4k3/8/8/8/8/8/8/RN3RK1"));
+        }
+
+        #[test]
+        fn castle_enemy_rook_test() { // Test an unmoved enemy rook in the corner not letting the king castle with it
+            use crate::board::turn::GameState;
+
+            let mut board_info = GameState::from_fen("4k3/8/8/8/8/8/8/r3K2R w K - 0 1").unwrap().board_info;
+            board_info.turns_board = [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]];
+            board_info.castling_rights = [true; 4];
+            assert_eq!(castle([4, 0], [2, 0], board_info), board_info.board);
+        }
+
+        #[test]
+        fn black_castle_test() { // Test black castling both ways on its flipped board
+            use crate::board::turn::new_turn;
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/4K3 b kq - 0 1").unwrap();
+            let short = new_turn([3, 0], [1, 0], 0, game_state).unwrap();
+            assert_eq!(short.to_fen(), "r4rk1/8/8/8/8/8/8/4K3 w - - 1 2");
+            let long = new_turn([3, 0], [5, 0], 0, game_state).unwrap();
+            assert_eq!(long.to_fen(), "2kr3r/8/8/8/8/8/8/4K3 w - - 1 2");
+        }
         // castle tests ------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
         
         // get_check_state tests ---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
         #[test]
         fn get_check_state_test1() { // Test check mate 
+            // The checkmated king isn't the team to move, so the position can't come from from_board
+            let board = fen::decode("k7/1Q6/6r1/8/8/5B2/8/8");
+            let pieces = info::Piece::instantiate_all();
             let board_info = BoardInfo {
-                board: fen::decode("k7/1Q6/6r1/8/8/5B2/8/8"),
+                board,
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
                 capture_coordinates: None,
                 error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                pieces,
+                castling_rights: [true; 4],
+                material: count_material(board, pieces),
+                en_passant: None,
             };
 
             let result = get_check_state(false, true, board_info);
@@ -1059,12 +2245,10 @@ pub mod moves {
         #[test]
         fn get_check_state_test2() { // Test stale mate
             let board_info = BoardInfo {
-                board: fen::decode("k7/2Q5/8/8/8/8/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("k7/2Q5/8/8/8/8/8/8"), true).unwrap().board_info
             };
 
             let result = get_check_state(false, true, board_info);
@@ -1080,12 +2264,10 @@ pub mod moves {
         #[test]
         fn get_check_state_test3() { // Test check mate being blocked by friendly rook
             let board_info = BoardInfo {
-                board: fen::decode("K7/1q5R/8/8/8/5b2/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("K7/1q5R/8/8/8/5b2/8/8"), true).unwrap().board_info
             };
 
             let result = get_check_state(true, true, board_info);
@@ -1103,12 +2285,11 @@ pub mod moves {
         #[test]
         fn gen_move_board_test1() { // Test an invalid move (blocked by check)
             let board_info = BoardInfo {
-                board: fen::decode("8/8/6p1/3b4/8/8/6K1/1Q6"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
                 error_code: 1,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/6p1/3b4/8/8/6K1/1Q6"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1126,12 +2307,10 @@ pub mod moves {
         #[test]
         fn gen_move_board_test2() { // Test a valid move
             let board_info = BoardInfo {
-                board: fen::decode("8/8/6p1/8/8/8/6K1/1Q6"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/6p1/8/8/8/6K1/1Q6"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1148,12 +2327,11 @@ pub mod moves {
         #[test]
         fn gen_move_board_test3() { // Test an invalid move (piece trying to move that puts the king in check)
             let board_info = BoardInfo {
-                board: fen::decode("3RK3/B6B/2P1PN1Q/P2P4/2p3p1/6q1/p2n3p/1rk2r1b"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
                 error_code: 1,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("3RK3/B6B/2P1PN1Q/P2P4/2p3p1/6q1/p2n3p/1rk2r1b"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1169,12 +2347,11 @@ pub mod moves {
         #[test]
         fn gen_move_board_test4() { // Test completely invalid move where a pawn tries to move to the other side of the board
             let board_info = BoardInfo {
-                board: fen::decode("3RK3/B6B/2P1PN1Q/P2P4/2p3p1/6q1/p2n3p/1rk2r1b"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
                 error_code: 4,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("3RK3/B6B/2P1PN1Q/P2P4/2p3p1/6q1/p2n3p/1rk2r1b"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1190,12 +2367,11 @@ pub mod moves {
         #[test]
         fn gen_move_board_test5() { // Test king trying to move into check (where an enemy pawn puts the king in check)
             let board_info = BoardInfo {
-                board: fen::decode("8/8/8/8/8/8/1p6/1K6"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
                 error_code: 1,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/8/8/8/1p6/1K6"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1211,12 +2387,11 @@ pub mod moves {
         #[test]
         fn gen_move_board_test6() { // Test pawn trying to capture king by moving forwards instead of diagonal
             let board_info = BoardInfo {
-                board: fen::decode("6K1/6p1/8/8/8/8/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0i8; 2],
-                capture_coordinates: None,
                 error_code: 4,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("6K1/6p1/8/8/8/8/8/8"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1232,12 +2407,10 @@ pub mod moves {
         #[test]
         fn gen_move_board_test7() { // Test king castle
             let board_info = BoardInfo {
-                board: fen::decode("8/8/8/8/8/8/8/R3K2R"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0, 0],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/8/8/8/8/8/8/R3K2R"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1248,12 +2421,10 @@ pub mod moves {
             );
 
             let expected =  BoardInfo {
-                board: fen::decode("8/8/8/8/8/8/8/R4RK1"),
                 turns_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [1, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
                 last_turn_coordinates: [6, 0],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [false, false, true, true],
+                ..GameState::from_board(fen::decode("8/8/8/8/8/8/8/R4RK1"), true).unwrap().board_info
             };
 
             assert_eq!(result, expected);
@@ -1262,12 +2433,11 @@ pub mod moves {
         #[test]
         fn gen_move_board_test8() { // Test turns_board and last_turns_coordinates being updated in gen_move_board (with en passant)
             let board_info = BoardInfo {
-                board: fen::decode("8/8/8/4pP2/8/8/8/8"),
                 turns_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 1, 0, 0, 0], [0, 0, 0, 0, 3, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
                 last_turn_coordinates: [4, 4],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                en_passant: Some([4, 5]),
+                ..GameState::from_board(fen::decode("8/8/8/4pP2/8/8/8/8"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1278,12 +2448,12 @@ pub mod moves {
             );
 
             let expected =  BoardInfo {
-                board: fen::decode("8/8/4P3/8/8/8/8/8"),
                 turns_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 4, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
                 last_turn_coordinates: [4, 5],
                 capture_coordinates: Some([4, 4]),
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                material: board_info.material, // new_turn updates the running material, not gen_move_board
+                ..GameState::from_board(fen::decode("8/8/4P3/8/8/8/8/8"), true).unwrap().board_info
             };
 
             assert_eq!(result, expected);
@@ -1292,12 +2462,10 @@ pub mod moves {
         #[test]
         fn gen_move_board_test9() { // Test pawn promoting to queen
             let board_info = BoardInfo {
-                board: fen::decode("8/2p5/8/8/8/8/8/8"),
                 turns_board: [[0i8; BOARD_SIZE[0]]; BOARD_SIZE[1]],
                 last_turn_coordinates: [0, 0],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                ..GameState::from_board(fen::decode("8/2p5/8/8/8/8/8/8"), true).unwrap().board_info
             };
 
             let result = gen_move_board(
@@ -1308,16 +2476,500 @@ pub mod moves {
             );
 
             let expected =  BoardInfo {
-                board: fen::decode("2q5/8/8/8/8/8/8/8"),
                 turns_board: [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 1], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]],
                 last_turn_coordinates: [2, 7],
-                capture_coordinates: None,
-                error_code: 0,
-                pieces: info::Piece::instantiate_all(),
+                castling_rights: [true; 4],
+                material: board_info.material, // new_turn updates the running material, not gen_move_board
+                ..GameState::from_board(fen::decode("2q5/8/8/8/8/8/8/8"), true).unwrap().board_info
             };
 
             assert_eq!(result, expected);
         }
         // gen_move_board tests --------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+
+        // is_legal tests --------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+        #[test]
+        fn is_legal_test() { // Test is_legal agreeing with new_turn for every move of the team to move
+            use crate::board::turn::GameState;
+            use crate::board::turn::new_turn;
+            use crate::coordinates_from_usize;
+
+            use crate::board::Variant;
+
+            let positions = [
+                (crate::fen::START_FEN, Variant::Standard),
+                ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", Variant::Standard), // Castles, pins and captures
+                ("4k3/8/8/8/1b6/8/3N4/4K3 w - - 0 1", Variant::Standard), // Knight pinned by a bishop
+                ("4k3/8/8/8/8/8/4r3/R3K3 w Q - 0 1", Variant::Standard), // King in check from an adjacent rook
+                ("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1", Variant::Standard), // En passant exposing the king along the row
+                ("4k3/8/8/8/8/8/8/4K2R b K - 0 1", Variant::Standard), // Black to move
+                ("4k3/8/8/8/8/8/4r3/R3K3 w Q - 0 1", Variant::Antichess), // The king can capture or walk into the rook, other moves are rejected
+                ("4k3/8/8/8/8/8/8/R3p1N1 w - - 0 1", Variant::Antichess), // Only the rook capture is allowed
+                (crate::fen::START_FEN, Variant::Antichess), // No captures, so every piece move is allowed
+            ];
+
+            for (fen, variant) in positions {
+                let mut game_state = GameState::from_fen(fen).unwrap();
+                game_state.variant = variant;
+
+                for piece_x in 0..BOARD_SIZE[0] {
+                    for piece_y in 0..BOARD_SIZE[1] {
+                        let piece_coordinates = coordinates_from_usize([piece_x, piece_y]);
+                        let piece_id = get_board(piece_coordinates, game_state.board_info.board);
+                        if piece_id == 0 || piece_white(piece_id) != game_state.whites_turn {
+                            continue;
+                        }
+
+                        for move_x in 0..BOARD_SIZE[0] {
+                            for move_y in 0..BOARD_SIZE[1] {
+                                let chess_move = Move {
+                                    piece_coordinates,
+                                    move_coordinates: coordinates_from_usize([move_x, move_y]),
+                                };
+
+                                let accepted = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, info::IDS[4], game_state) {
+                                    Ok(_) => true,
+                                    Err(error) => error.game_over,
+                                };
+                                assert_eq!(is_legal(chess_move, game_state), accepted, "{} {:?}", fen, chess_move);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // is_legal tests --------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+
+        #[test]
+        fn set_piece_test() { // Test editing a position keeping the material, king position, and castle rights consistent
+            let mut board_info = crate::board::turn::GameState::new().board_info;
+
+            // Replace the white queen with a knight and remove a black rook
+            board_info.set_piece([3, 0], info::IDS[2]);
+            board_info.clear([0, 7]);
+            assert_eq!(board_info.material, count_material(board_info.board, board_info.pieces));
+            assert_eq!(board_info.material, [33, 34]);
+            assert_eq!(board_info.castling_rights, [true, true, true, false]);
+
+            // Move the white king to d4
+            board_info.clear([4, 0]);
+            board_info.set_piece([3, 3], info::IDS[5]);
+            assert_eq!(crate::find_id_in_board(info::IDS[5], board_info.board), Some([3, 3]));
+            assert_eq!(board_info.castling_rights, [false, false, true, false]);
+            assert_eq!(board_info.material, count_material(board_info.board, board_info.pieces));
+
+            // A black queen checking the white king on whites turn is legal, on blacks turn it isn't
+            board_info.set_piece([3, 5], -info::IDS[4]);
+            let mut game_state = crate::board::turn::GameState::new();
+            game_state.board_info = board_info;
+            assert!(crate::piece::moves::position_is_legal(game_state));
+            game_state.whites_turn = false;
+            game_state.board_info = crate::flip_board_info(board_info);
+            assert!(!crate::piece::moves::position_is_legal(game_state));
+        }
+
+        #[test]
+        fn legal_moves_where_test() { // Test filtering out moves onto squares attacked by an enemy pawn
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("4k3/8/8/8/3p4/8/8/1N2K3 w - - 0 1").unwrap();
+            let safe_moves = legal_moves_where(game_state, |chess_move, board_info| !is_attacked(chess_move.move_coordinates, false, board_info.board));
+
+            let knight_c3 = Move {
+                piece_coordinates: [1, 0],
+                move_coordinates: [2, 2],
+            };
+            let mut expected = legal_moves(game_state);
+            expected.retain(|chess_move| *chess_move != knight_c3);
+            assert_eq!(safe_moves, expected);
+            assert_eq!(safe_moves.len(), legal_moves(game_state).len() - 1);
+        }
+
+        #[test]
+        fn random_legal_move_test() { // Test a random game only playing legal moves until the game ends
+            use crate::board::turn::new_turn;
+            use crate::board::turn::GameState;
+            use rand::SeedableRng;
+
+            let mut rng = rand::rngs::StdRng::seed_from_u64(1158);
+            let mut game_state = GameState::new();
+            let mut game_over = false;
+
+            for _ in 0..1000 {
+                if game_state.status().is_some() {
+                    game_over = true;
+                    break;
+                }
+
+                let chess_move = random_legal_move(game_state, &mut rng).unwrap();
+                game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, info::IDS[4], game_state) {
+                    Ok(game_state_new) => game_state_new,
+                    Err(error) => {
+                        assert!(error.game_over, "{:?} was rejected with error code {}", chess_move, error.error_code);
+                        game_over = true;
+                        break;
+                    },
+                };
+            }
+            assert!(game_over);
+
+            let game_state = GameState::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+            assert_eq!(random_legal_move(game_state, &mut rng), None);
+        }
+
+        #[test]
+        fn legal_moves_masked_test() { // Test a knight mask only returning the legal knight moves, and a forced antichess capture by an unmasked piece
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::new();
+            let knight_moves: Vec<Move> = legal_moves(game_state).into_iter()
+                .filter(|legal_move| get_board(legal_move.piece_coordinates, game_state.board_info.board).abs() == info::IDS[2])
+                .collect();
+            assert_eq!(knight_moves.len(), 4);
+            assert_eq!(legal_moves_masked(game_state, Some(&[info::IDS[2]])), knight_moves);
+            assert_eq!(legal_moves_masked(game_state, Some(&[info::IDS[0], info::IDS[2]])).len(), 20);
+            assert_eq!(legal_moves_masked(game_state, None), legal_moves(game_state));
+
+            // The pinned knight has no legal moves
+            let game_state = GameState::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+            assert!(legal_moves_masked(game_state, Some(&[info::IDS[2]])).is_empty());
+
+            // The rook has to capture, so the knight can't move
+            let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3p1N1 w - - 0 1").unwrap();
+            game_state.variant = crate::board::Variant::Antichess;
+            assert!(legal_moves_masked(game_state, Some(&[info::IDS[2]])).is_empty());
+        }
+
+        #[test]
+        fn legal_move_count_test() { // Test the count matching legal_moves for normal, castling, en passant, promotion, checkmate and forced antichess capture positions
+            use crate::board::turn::GameState;
+
+            let fens = [
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+                "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+                "8/P3k3/8/8/8/8/4K2p/8 w - - 0 1",
+                "4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1",
+                "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            ];
+            for fen in fens {
+                let game_state = GameState::from_fen(fen).unwrap();
+                assert_eq!(legal_move_count(game_state), legal_moves(game_state).len(), "{}", fen);
+            }
+
+            // Only the forced capture counts in antichess
+            let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3p1N1 w - - 0 1").unwrap();
+            game_state.variant = crate::board::Variant::Antichess;
+            assert_eq!(legal_move_count(game_state), 1);
+            assert_eq!(legal_move_count(game_state), legal_moves(game_state).len());
+        }
+
+        #[test]
+        fn move_overlay_test() { // Test a king showing both castles, a pinned knight showing nothing, a pinned rook along the pin, and en passant
+            use crate::board::turn::GameState;
+
+            let count = |overlay: [[bool; BOARD_SIZE[0]]; BOARD_SIZE[1]]| overlay.iter().flatten().filter(|square| **square).count();
+
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+            let overlay = move_overlay([4, 0], game_state);
+            assert!(overlay[6][0] && overlay[2][0]);
+            assert_eq!(count(overlay), 7);
+
+            let game_state = GameState::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+            assert_eq!(count(move_overlay([4, 1], game_state)), 0);
+            assert_eq!(count(move_overlay([4, 0], game_state)), 4);
+
+            let game_state = GameState::from_fen("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1").unwrap();
+            let overlay = move_overlay([4, 1], game_state);
+            assert_eq!(count(overlay), 6);
+            assert!(overlay[4][7]);
+
+            let game_state = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+            let overlay = move_overlay([4, 4], game_state);
+            assert!(overlay[3][5] && overlay[4][5]);
+            assert_eq!(count(overlay), 2);
+
+            // Empty squares and enemy pieces have no moves
+            assert_eq!(count(move_overlay([0, 0], game_state)), 0);
+            assert_eq!(count(move_overlay([3, 4], game_state)), 0);
+        }
+
+        #[test]
+        fn defended_squares_test() { // Test a pawn defending its diagonals, and a rook defending the first friendly piece on each ray but not enemies or pieces behind
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("4k3/8/8/8/8/2N1B3/3P4/4K3 w - - 0 1").unwrap();
+            assert_eq!(defended_squares([3, 1], game_state.board_info), vec![[2, 2], [4, 2]]);
+
+            let game_state = GameState::from_fen("4k3/8/B7/8/N7/8/8/R1BQKn2 w - - 0 1").unwrap();
+            assert_eq!(defended_squares([0, 0], game_state.board_info), vec![[0, 3], [2, 0]]);
+            assert_eq!(defended_squares([4, 0], game_state.board_info), vec![[3, 0]]);
+            assert!(defended_squares([1, 0], game_state.board_info).is_empty());
+        }
+
+        #[test]
+        fn checkers_test() { // Test a discovered double check, a pawn check from blacks perspective, and no check
+            use crate::board::turn::GameState;
+
+            // The knight on d6 and the rook on e1 both check the black king, squares are from blacks perspective
+            let game_state = GameState::from_fen("4k3/8/3N4/8/8/8/8/4RK2 b - - 0 1").unwrap();
+            let mut squares = checkers(game_state);
+            squares.sort();
+            assert_eq!(squares, vec![[3, 7], [4, 2]]);
+
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1").unwrap();
+            assert_eq!(checkers(game_state), vec![[3, 1]]);
+
+            assert!(checkers(GameState::new()).is_empty());
+        }
+
+        #[test]
+        fn castling_available_test() { // Test a blocked castle, castling through check, the team not to move, and a king in check
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/RN2K2R w KQ - 0 1").unwrap();
+            assert_eq!(castling_available(game_state), [true, false, false, false]);
+
+            let game_state = GameState::from_fen("4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+            assert_eq!(castling_available(game_state), [false, true, false, false]);
+
+            // Black castles are found on either teams turn
+            for side in ["w", "b"] {
+                let game_state = GameState::from_fen(&format!("r3k1nr/8/8/8/8/8/8/4K3 {} kq - 0 1", side)).unwrap();
+                assert_eq!(castling_available(game_state), [false, false, false, true]);
+            }
+
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2r w KQkq - 0 1").unwrap();
+            assert_eq!(castling_available(game_state), [false, false, true, true]);
+        }
+
+        #[test]
+        fn parse_coordinate_move_test() { // Test the accepted separators, a promotion suffix, and castling only parsing when it's legal
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::new();
+            let e4 = Move {
+                piece_coordinates: [4, 1],
+                move_coordinates: [4, 3],
+            };
+            for s in ["e2e4", "e2-e4", "e2 e4", " E2 - E4 "] {
+                assert_eq!(parse_coordinate_move(s, game_state), Ok((e4, info::IDS[4])));
+            }
+            assert_eq!(parse_coordinate_move("e2-e5", game_state), Err(ParseError::Illegal));
+            assert_eq!(parse_coordinate_move("e2", game_state), Err(ParseError::Invalid));
+            assert_eq!(parse_coordinate_move("e2-e4x", game_state), Err(ParseError::Invalid));
+
+            let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+            let castle = Move {
+                piece_coordinates: [4, 0],
+                move_coordinates: [6, 0],
+            };
+            assert_eq!(parse_coordinate_move("e1-g1", game_state), Ok((castle, info::IDS[4])));
+            assert_eq!(parse_coordinate_move("e1-g1", GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1").unwrap()), Err(ParseError::Illegal));
+            assert_eq!(parse_coordinate_move("e1-g1", GameState::from_fen("r3k2r/8/8/8/8/8/6r1/R3K2R w KQkq - 0 1").unwrap()), Err(ParseError::Illegal));
+            let game_state = crate::board::turn::new_turn(castle.piece_coordinates, castle.move_coordinates, info::IDS[4], game_state).unwrap();
+
+            // Black castles from its own perspective too
+            assert_eq!(parse_coordinate_move("e8 c8", game_state), Ok((Move {
+                piece_coordinates: [3, 0],
+                move_coordinates: [5, 0],
+            }, info::IDS[4])));
+
+            let game_state = GameState::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+            assert_eq!(parse_coordinate_move("b7-b8=N", game_state).unwrap().1, info::IDS[2]);
+        }
+
+        #[test]
+        fn captures_by_see_test() { // Test a queen taking a free knight being ordered before the queen taking a defended pawn
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("4k3/8/2p5/3p3n/8/8/8/3QK3 w - - 0 1").unwrap();
+            let free_knight = Move {
+                piece_coordinates: [3, 0],
+                move_coordinates: [7, 4],
+            };
+            let defended_pawn = Move {
+                piece_coordinates: [3, 0],
+                move_coordinates: [3, 4],
+            };
+            assert_eq!(captures_by_see(game_state), vec![(free_knight, 3), (defended_pawn, -8)]);
+
+            assert!(captures_by_see(GameState::new()).is_empty());
+        }
+
+        #[test]
+        fn is_quiet_test() { // Test a locked pawn position being quiet, and a winning capture, a check, and a pawn about to promote not being quiet
+            use crate::board::turn::GameState;
+
+            assert!(is_quiet(GameState::from_fen("8/8/1k6/p1p1p1p1/P1P1P1P1/8/7b/1K6 w - - 0 1").unwrap()));
+            assert!(!is_quiet(GameState::from_fen("2q3k1/8/8/3N4/8/8/8/2R1K3 w - - 0 1").unwrap()));
+            assert!(!is_quiet(GameState::from_fen("6k1/8/8/8/8/8/8/R3K2q w - - 0 1").unwrap()));
+            assert!(!is_quiet(GameState::from_fen("6k1/8/8/8/8/8/p7/4K3 w - - 0 1").unwrap()));
+            assert!(!is_quiet(GameState::from_fen("6k1/8/8/8/8/8/p7/4K3 b - - 0 1").unwrap()));
+
+            // A defended piece can be captured, but not for a gain
+            assert!(is_quiet(GameState::from_fen("6k1/8/2p5/3p4/8/8/8/3Q1K2 w - - 0 1").unwrap()));
+        }
+
+        #[test]
+        fn is_capture_test() { // Test a pawn push, a pawn capture, and an en passant capture
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("4k3/8/8/3pP3/8/5p2/6P1/4K3 w - d6 0 1").unwrap();
+            let is_capture_move = |piece_coordinates: [i8; 2], move_coordinates: [i8; 2]| -> bool {
+                is_capture(Move {
+                    piece_coordinates,
+                    move_coordinates,
+                }, game_state)
+            };
+
+            assert!(!is_capture_move([6, 1], [6, 2]));
+            assert!(is_capture_move([6, 1], [5, 2]));
+            assert!(is_capture_move([4, 4], [3, 5]));
+            assert!(!is_capture_move([4, 4], [4, 5]));
+
+            // Every legal move agrees with legal_move_infos
+            for move_info in legal_move_infos(game_state) {
+                assert_eq!(is_capture(move_info.chess_move, game_state), move_info.is_capture);
+            }
+
+            // Without the en passant field the pawn didn't just double move
+            let game_state = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+            assert!(!is_capture(Move {
+                piece_coordinates: [4, 4],
+                move_coordinates: [3, 5],
+            }, game_state));
+        }
+
+        #[test]
+        fn legal_move_infos_test() { // Test flags for an en passant capture, a castle, promotions, a checking capture, and a quiet move
+            let game_state = crate::board::turn::GameState::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+            let move_infos = legal_move_infos(game_state);
+
+            let find = |piece_coordinates: [i8; 2], move_coordinates: [i8; 2], promotion: Option<i8>| -> MoveInfo {
+                *move_infos.iter().find(|move_info| {
+                    move_info.chess_move.piece_coordinates == piece_coordinates && move_info.chess_move.move_coordinates == move_coordinates && move_info.promotion == promotion
+                }).unwrap()
+            };
+
+            let en_passant = find([4, 4], [3, 5], None);
+            assert!(en_passant.is_capture && en_passant.is_en_passant && !en_passant.gives_check);
+
+            let castle = find([4, 0], [6, 0], None);
+            assert!(castle.is_castle && !castle.is_capture);
+
+            assert!(find([1, 6], [1, 7], Some(info::IDS[4])).gives_check);
+            assert!(!find([1, 6], [1, 7], Some(info::IDS[2])).gives_check);
+            assert!(find([1, 6], [0, 7], Some(info::IDS[1])).is_capture);
+
+            let rook_capture = find([0, 0], [0, 7], None);
+            assert!(rook_capture.is_capture && rook_capture.gives_check && !rook_capture.is_en_passant);
+
+            let quiet = find([4, 0], [3, 0], None);
+            assert_eq!(quiet, MoveInfo {
+                chess_move: quiet.chess_move,
+                is_capture: false,
+                is_castle: false,
+                is_en_passant: false,
+                promotion: None,
+                gives_check: false,
+            });
+        }
+
+        #[test]
+        fn legal_moves_uci_test() { // Test long algebraic moves for the start position, castles, and promotions
+            use crate::board::turn::GameState;
+
+            let moves = legal_moves_uci(GameState::new());
+            assert_eq!(moves.len(), 20);
+            assert!(moves.contains(&String::from("e2e4")));
+            assert!(moves.contains(&String::from("g1f3")));
+
+            let moves = legal_moves_uci(GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap());
+            assert!(moves.contains(&String::from("e8g8")));
+            assert!(moves.contains(&String::from("e8c8")));
+
+            let moves = legal_moves_uci(GameState::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+            assert!(moves.contains(&String::from("b7b8q")));
+            assert!(moves.contains(&String::from("b7b8n")));
+            assert!(!moves.contains(&String::from("b7b8")));
+        }
+
+        #[test]
+        fn position_is_legal_test() { // Test a position where the team that just moved left its king in check
+            use crate::board::turn::GameState;
+
+            assert_eq!(GameState::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1"), Err(crate::fen::FenError::IllegalPosition));
+
+            let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/4RK2 b - - 0 1").unwrap();
+            assert!(position_is_legal(game_state));
+
+            let mut illegal = game_state;
+            illegal.whites_turn = true;
+            illegal.board_info = crate::flip_board_info(game_state.board_info);
+            assert!(!position_is_legal(illegal));
+
+            let mut search = crate::algorithm::minimax::Search::builder().depth(2).build();
+            assert_eq!(search.go(illegal), None);
+            assert_eq!(search.iter_deepening(illegal, 2).count(), 0);
+        }
+
+        #[test]
+        fn is_attacked_test() { // Test is_attacked agreeing with the enemy move board on squares without pawn only attacks or defended pieces
+            use crate::board::turn::GameState;
+
+            let game_state = GameState::from_fen("r3k2r/ppp2ppp/2n5/3qp3/4P3/2N2N2/PPP2PPP/R2QK2R w KQkq - 0 1").unwrap();
+            let enemy_moves = gen_enemy_moves(true, game_state.board_info);
+
+            let squares: [[i8; 2]; 12] = [[4, 3], [2, 2], [5, 2], [3, 0], [4, 0], [0, 1], [3, 3], [1, 3], [3, 5], [7, 4], [6, 3], [0, 3]];
+            let mut attacked_no = 0;
+            for square in squares {
+                let attacked = is_attacked(square, false, game_state.board_info.board);
+                assert_eq!(attacked, get_board(square, enemy_moves) == 1, "square {:?}", square);
+                if attacked {
+                    attacked_no += 1;
+                }
+            }
+            assert!(attacked_no > 0 && attacked_no < squares.len());
+        }
+
+        #[test]
+        fn custom_piece_test() { // Test an archbishop (bishop + knight) registered as a custom piece
+            use crate::board::turn::GameState;
+
+            let archbishop = info::Piece {
+                id_fen: 'A',
+                id: 7,
+                value: 7,
+                sliding: true,
+                slide_no: None,
+                slide_no_y: None,
+                mdirs: [[1, 1], [1, -1], [-1, 1], [-1, -1], [0, 0], [0, 0], [0, 0], [0, 0]],
+                mdir_no: 4,
+                mdirs_leap: Some([[1, 2], [2, 1], [1, -2], [-1, 2], [2, -1], [-2, 1], [-2, -1], [-1, -2]]),
+                mdirs_cap: None,
+                condition_adj: None,
+                condition_self_y: None,
+                condition_subj_moves: None,
+            };
+            let pieces = info::Piece::with_custom(vec![archbishop]).unwrap();
+            assert_eq!(info::Piece::with_custom(vec![info::Piece { id: 3, ..archbishop }]), Err(info::CustomPieceError::IdOutOfRange(3)));
+            assert_eq!(info::Piece::with_custom(vec![info::Piece { id: 9, ..archbishop }]), Err(info::CustomPieceError::IdOutOfRange(9)));
+            assert_eq!(info::Piece::with_custom(vec![archbishop, archbishop]), Err(info::CustomPieceError::DuplicateId(7)));
+
+            // 13 bishop moves and 8 knight moves from d4
+            let game_state = GameState::from_fen_with_pieces("4k3/8/8/8/3A4/8/8/4K3 w - - 0 1", pieces).unwrap();
+            assert_eq!(game_state.board_info.board[3][3], 7);
+            let archbishop_moves = legal_moves(game_state).iter().filter(|m| m.piece_coordinates == [3, 3]).count();
+            assert_eq!(archbishop_moves, 21);
+
+            // A black archbishop on f3 checks the king with its knight move and covers d1 and e2 with its bishop move
+            let game_state = GameState::from_fen_with_pieces("4k3/8/8/8/8/5a2/8/4K3 w - - 0 1", pieces).unwrap();
+            assert_eq!(game_state.black_points_info.points_total - game_state.white_points_info.points_total, 7);
+            assert_eq!(legal_moves(game_state).len(), 2);
+            assert!(is_legal(Move { piece_coordinates: [4, 0], move_coordinates: [5, 0] }, game_state));
+            assert!(!is_legal(Move { piece_coordinates: [4, 0], move_coordinates: [3, 1] }, game_state));
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,199 @@
+use crate::board::turn::GameState;
+
+// Invariant checks that can be reused by tests in other crates
+
+// Panics if the evaluation of a position is not antisymmetric under mirroring
+// From whites perspective eval(pos) == -eval(mirror(pos))
+pub fn assert_eval_symmetric(game_state: GameState) {
+    use crate::algorithm::eval::evaluate;
+    use crate::algorithm::eval::EvalWeights;
+    use crate::board::mirror;
+
+    let weights = EvalWeights::new();
+    let eval = evaluate(game_state, &weights).white_perspective();
+    let eval_mirror = evaluate(mirror(game_state), &weights).white_perspective();
+    assert_eq!(eval, -eval_mirror, "evaluation is not symmetric under mirroring, board: {:?}", game_state.board_info.board);
+}
+
+// A perft suite case whose node count didn't match
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PerftFailure {
+    pub index: usize, // Index of the case in the suite
+    pub expected: u64,
+    pub nodes: Option<u64>, // None if the fen couldn't be decoded
+}
+
+// Runs perft for each (fen, depth, expected node count) case, returning every case that failed
+pub fn run_perft_suite(cases: &[(&str, usize, u64)]) -> Result<(), Vec<PerftFailure>> {
+    use crate::algorithm::perft::perft;
+
+    let mut failures: Vec<PerftFailure> = Vec::new();
+    for (i, (fen, depth, expected)) in cases.iter().enumerate() {
+        let nodes = match GameState::from_fen(fen) {
+            Ok(game_state) => Some(perft(game_state, *depth)),
+            Err(_) => None,
+        };
+
+        if nodes != Some(*expected) {
+            failures.push(PerftFailure {
+                index: i,
+                expected: *expected,
+                nodes,
+            });
+        }
+    }
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(failures)
+}
+
+// A position that broke a move generation invariant in fuzz_moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzFailure {
+    pub fen: String, // Piece placement and team to move of the failing position
+    pub reason: String,
+}
+
+pub const FUZZ_MAX_MOVES: usize = 60; // Longest random game played for each fuzzed position
+
+// Plays positions random games from the start position, checking move generation invariants for every position reached
+// Checks that legal_moves (counting a pawn reaching the last row once per promotion piece) agrees with perft(1), that new_turn accepts every legal move, and that every position after a move is legal
+// The same seed always plays the same games, so a failure can be reproduced
+pub fn fuzz_moves(seed: u64, positions: usize) -> Result<(), FuzzFailure> {
+    use crate::algorithm::perft::perft;
+    use crate::board::errors;
+    use crate::board::turn::new_turn;
+    use crate::piece::moves::is_promotion;
+    use crate::piece::moves::legal_moves;
+    use crate::piece::moves::position_is_legal;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+    for _ in 0..positions {
+        let mut game_state = GameState::new();
+        let game_length = rng.gen_range(0..FUZZ_MAX_MOVES);
+
+        for _ in 0..game_length {
+            let failure = |reason: String| -> FuzzFailure {
+                let mut board = game_state.board_info.board;
+                let mut side = "w";
+                if !game_state.whites_turn {
+                    board = crate::flip_board(board);
+                    side = "b";
+                }
+
+                FuzzFailure {
+                    fen: format!("{} {}", crate::fen::encode(board, game_state.board_info.pieces), side),
+                    reason,
+                }
+            };
+
+            let moves = legal_moves(game_state);
+            let promotions = game_state.promotion_ids().len().max(1);
+            let move_count: usize = moves.iter().map(|chess_move| match is_promotion(*chess_move, game_state) {
+                true => promotions,
+                false => 1,
+            }).sum();
+            let nodes = perft(game_state, 1);
+            if u64::try_from(move_count).unwrap() != nodes {
+                return Err(failure(format!("{} legal moves but perft(1) is {}", move_count, nodes)));
+            }
+
+            let mut next_game_state = None;
+            let next_move = match moves.is_empty() {
+                true => None,
+                false => Some(rng.gen_range(0..moves.len())),
+            };
+            for (i, chess_move) in moves.iter().enumerate() {
+                match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, game_state.default_promotion(), game_state) {
+                    Ok(game_state_new) => {
+                        if !position_is_legal(game_state_new) {
+                            return Err(failure(format!("{:?} leaves the king in check", chess_move)));
+                        }
+                        if Some(i) == next_move {
+                            next_game_state = Some(game_state_new);
+                        }
+                    },
+                    Err(error) => {
+                        if error.error_code != errors::CHECKMATE_ERROR && error.error_code != errors::STALEMATE_ERROR {
+                            return Err(failure(format!("{:?} was rejected by new_turn with error code {}", chess_move, error.error_code)));
+                        }
+                    },
+                }
+            }
+
+            // The game ended (or the random move ended it), start the next position
+            game_state = match next_game_state {
+                Some(game_state_new) => game_state_new,
+                None => break,
+            };
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+
+    #[test]
+    fn assert_eval_symmetric_test() { // Test evaluation symmetry for a handful of positions and random games played from them
+        use crate::board::turn::new_turn;
+        use crate::piece::moves::legal_moves;
+        use rand::SeedableRng;
+
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            "4k3/8/8/3q4/4N3/8/8/4K3 b - - 0 1",
+            "r3k2r/pp3ppp/8/3Bn3/8/2N5/PP3PPP/R3K2R w KQkq - 0 1",
+        ];
+
+        // Seeded so a failing game can be replayed
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7253);
+        for fen in fens {
+            let mut game_state = GameState::from_fen(fen).unwrap();
+            assert_eval_symmetric(game_state);
+
+            for _ in 0..10 {
+                let chess_move = match legal_moves(game_state).choose(&mut rng) {
+                    Some(chess_move) => *chess_move,
+                    None => break,
+                };
+
+                game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, game_state.default_promotion(), game_state) {
+                    Ok(game_state_new) => game_state_new,
+                    Err(_) => break,
+                };
+                assert_eval_symmetric(game_state);
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz_moves_test() { // Test random games from a fixed seed keeping every move generation invariant
+        assert_eq!(fuzz_moves(7253, 2), Ok(()));
+    }
+
+    #[test]
+    fn run_perft_suite_test() { // Test the standard perft positions 2 to 5 (Kiwipete, an endgame with pinned en passant captures, and two positions with underpromotions) against their known node counts
+        let cases = [
+            ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 2, 2039),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3, 2812),
+            ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 2, 264),
+            ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 3, 9467),
+            ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 2, 1486),
+            ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 3, 62379),
+        ];
+        assert_eq!(run_perft_suite(&cases), Ok(()));
+
+        let failures = run_perft_suite(&[("8/8/8/8/8/8/8/8 x", 1, 1), (crate::fen::START_FEN, 1, 21)]).unwrap_err();
+        assert_eq!(failures[0].nodes, None);
+        assert_eq!(failures[1].nodes, Some(20));
+    }
+}
@@ -0,0 +1,39 @@
+// Time management for games played under a clock
+
+pub const DEFAULT_MOVES_TO_GO: u64 = 30; // Moves the remaining time is spread over when the clock doesn't say
+pub const MOVE_OVERHEAD_MS: u64 = 50; // Time kept back every move for communication delays
+pub const MIN_MOVE_MS: u64 = 10; // Shortest time spent on a move, if there is that much time left
+
+// Returns how many milliseconds to spend on this move, as in uci "go wtime btime winc binc movestogo"
+// The remaining time is spread over the moves to go, and most of the increment is used as it's given back after the move
+// Never returns more than remaining_ms
+pub fn allocate(remaining_ms: u64, increment_ms: u64, moves_to_go: Option<u32>) -> u64 {
+    let moves_to_go = match moves_to_go {
+        Some(moves) => std::cmp::max(u64::from(moves), 1),
+        None => DEFAULT_MOVES_TO_GO,
+    };
+
+    let allocation = remaining_ms / moves_to_go + increment_ms * 3 / 4;
+
+    // Keep the overhead on the clock, but always think for a little while
+    let allocation = std::cmp::min(allocation, remaining_ms.saturating_sub(MOVE_OVERHEAD_MS));
+    std::cmp::min(std::cmp::max(allocation, MIN_MOVE_MS), remaining_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_test() { // Test normal clocks, a low clock, and a clock with moves to go
+        assert_eq!(allocate(60000, 0, None), 2000);
+        assert_eq!(allocate(60000, 1000, None), 2750);
+        assert_eq!(allocate(60000, 0, Some(1)), 60000 - MOVE_OVERHEAD_MS);
+
+        // Low on time, the increment can't be spent before it's given
+        assert!(allocate(100, 0, None) <= 10);
+        assert!(allocate(100, 5000, None) <= 100);
+        assert_eq!(allocate(5, 0, None), 5);
+        assert_eq!(allocate(0, 0, Some(0)), 0);
+    }
+}
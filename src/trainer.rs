@@ -0,0 +1,318 @@
+use crate::board::turn::new_turn;
+use crate::board::turn::GameState;
+use crate::piece::moves::Move;
+
+// A move that wins material or forces a checkmate
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tactic {
+    pub tactic_move: Move,
+    pub gain: i8, // Material won by the team making the move, compared to the current position
+    pub mate: bool, // The move forces a checkmate
+}
+
+// Weights of the material only searches, their evaluation is always 0, so only the tempo has to be left out
+fn material_only_weights() -> crate::algorithm::eval::EvalWeights {
+    crate::algorithm::eval::EvalWeights {
+        tempo: 0,
+        ..crate::algorithm::eval::EvalWeights::new()
+    }
+}
+
+// Finds every move for the team to move that wins material or forces a checkmate within depth moves
+// Each candidate move is followed by a material only search of depth moves, so a fork is found with a depth of 2 (enemy reply, capture)
+// Moves that lose the moved piece in a static exchange are not counted as winning material
+pub fn find_tactics(game_state: GameState, depth: usize) -> Vec<Tactic> {
+    use crate::algorithm::eval::see;
+    use crate::algorithm::eval::Score;
+    use crate::algorithm::eval::PAWN;
+    use crate::algorithm::minimax::mate_plies;
+    use crate::algorithm::minimax::Search;
+    use crate::board::errors;
+    use crate::piece::info::CHECKMATE_VALUE;
+    use crate::piece::moves::legal_moves;
+
+    let mut tactics: Vec<Tactic> = Vec::new();
+
+    for chess_move in legal_moves(game_state) {
+        let game_state_new = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, crate::piece::info::IDS[4], game_state) {
+            Ok(game_state_new) => game_state_new,
+            Err(error) => {
+                if error.error_code == errors::CHECKMATE_ERROR {
+                    tactics.push(Tactic {
+                        tactic_move: chess_move,
+                        gain: CHECKMATE_VALUE,
+                        mate: true,
+                    });
+                }
+                continue;
+            },
+        };
+
+        // Search from the enemies perspective, the enemies material change is the teams loss
+        let mut search = Search::builder().depth(depth).eval(|_, _| Score::from_white_perspective(0)).weights(material_only_weights()).build();
+        let reply = search.go(game_state_new).unwrap(); // new_turn only returns legal positions
+
+        if reply.value < 0 && mate_plies(reply.value).is_some() {
+            tactics.push(Tactic {
+                tactic_move: chess_move,
+                gain: CHECKMATE_VALUE,
+                mate: true,
+            });
+            continue;
+        }
+
+        let reply_value = i8::try_from(reply.value / PAWN).unwrap(); // Search values are in centipawns
+        let gain = game_state_new.points_delta - reply_value;
+        if gain > 0 && see(chess_move, game_state) >= 0 {
+            tactics.push(Tactic {
+                tactic_move: chess_move,
+                gain,
+                mate: false,
+            });
+        }
+    }
+
+    tactics
+}
+
+// Returns how many turns it takes the team to move to checkmate the enemy, if it can force a checkmate within turns turns
+// Uses the mate distance of a material only search, which is pruned so it only has to find the shortest checkmate
+fn mate_in(game_state: GameState, turns: usize) -> Option<usize> {
+    use crate::algorithm::eval::Score;
+    use crate::algorithm::minimax::mate_plies;
+    use crate::algorithm::minimax::Search;
+
+    if turns == 0 {
+        return None;
+    }
+
+    // The last turn only needs the checkmating move, not the enemies reply
+    let search = Search::builder().depth(turns * 2 - 1).eval(|_, _| Score::from_white_perspective(0)).weights(material_only_weights()).build().go(game_state)?;
+    if search.value <= 0 {
+        return None;
+    }
+    mate_plies(search.value).map(|plies| plies / 2 + 1)
+}
+
+// Returns the toughest defence for a losing team to move, the move which delays the enemies checkmate the longest
+// A move which escapes every checkmate within depth turns (of the enemy) is preferred, when the team isn't being mated the best searched move is returned
+// Returns None when the team to move has no legal moves, or the position is illegal
+pub fn analyze_defense(game_state: GameState, depth: usize) -> Option<Move> {
+    use crate::algorithm::minimax::Search;
+    use crate::board::errors;
+    use crate::piece::moves::legal_moves;
+    use crate::piece::moves::position_is_legal;
+
+    if !position_is_legal(game_state) {
+        return None;
+    }
+
+    let mut defense: Option<(Option<usize>, Move)> = None;
+    for chess_move in legal_moves(game_state) {
+        let mate = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, crate::piece::info::IDS[4], game_state) {
+            Ok(game_state_new) => mate_in(game_state_new, depth),
+            Err(error) => match error.error_code {
+                errors::CHECKMATE_ERROR => return Some(chess_move), // Checkmating the enemy beats any defence
+                errors::STALEMATE_ERROR => None, // A stalemate escapes the checkmate
+                _ => continue,
+            },
+        };
+
+        defense = match defense {
+            None => Some((mate, chess_move)),
+            Some((longest, defense_move)) => {
+                // No checkmate (None) is longer than any checkmate
+                let longer = match (mate, longest) {
+                    (None, Some(_)) => true,
+                    (Some(mate), Some(longest)) => mate > longest,
+                    _ => false,
+                };
+
+                if longer {
+                    Some((mate, chess_move))
+                } else {
+                    Some((longest, defense_move))
+                }
+            },
+        };
+    }
+
+    let (mate, defense_move) = defense?;
+    if mate.is_some() {
+        return Some(defense_move);
+    }
+
+    // Not being mated within depth, play the best move instead
+    let best_move = Search::builder().depth(depth).build().go(game_state)?;
+    Some(Move {
+        piece_coordinates: best_move.piece_coordinates,
+        move_coordinates: best_move.move_coordinates,
+    })
+}
+
+// Smallest material gain that counts as winning a puzzle, a minor piece
+pub const DECISIVE_GAIN: i8 = 3;
+
+// What a puzzle solution achieves against the engines defence
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SolutionResult {
+    Mate, // The solution checkmates, or leaves a checkmate the enemy can't avoid
+    Gain(i8), // The solution wins at least DECISIVE_GAIN material, stores the material won
+    Fails, // The solution doesn't mate or win enough material (E.g. it stalemates)
+    Illegal(usize), // The move at the index isn't legal
+}
+
+// Plays the moves of a puzzle solution for the team to move, with the engine replying to each with analyze_defense
+// Moves are from the perspective of the team to move (the solver), after the last move the enemy gets a material only search like find_tactics
+// so a solution can stop once the win is forced, E.g. the key move of a mate in 2
+pub fn verify_solution(mut game_state: GameState, solution: &[Move]) -> SolutionResult {
+    use crate::algorithm::eval::Score;
+    use crate::algorithm::eval::PAWN;
+    use crate::algorithm::minimax::mate_plies;
+    use crate::algorithm::minimax::Search;
+    use crate::board::errors;
+
+    if solution.is_empty() {
+        return SolutionResult::Fails;
+    }
+
+    let mut gain: i8 = 0;
+    for i in 0..solution.len() {
+        let chess_move = solution[i];
+        game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, crate::piece::info::IDS[4], game_state) {
+            Ok(game_state_new) => game_state_new,
+            Err(error) => {
+                return match error.error_code {
+                    errors::CHECKMATE_ERROR => SolutionResult::Mate,
+                    errors::STALEMATE_ERROR => SolutionResult::Fails,
+                    _ => SolutionResult::Illegal(i),
+                };
+            },
+        };
+        gain += game_state.points_delta;
+
+        if i == solution.len() - 1 {
+            break;
+        }
+
+        // The enemy defends against the moves the solver has left
+        let defense = match analyze_defense(game_state, std::cmp::max(solution.len() - i - 1, 1)) {
+            Some(defense) => defense,
+            None => return SolutionResult::Fails,
+        };
+        game_state = match new_turn(defense.piece_coordinates, defense.move_coordinates, crate::piece::info::IDS[4], game_state) {
+            Ok(game_state_new) => game_state_new,
+            Err(_) => return SolutionResult::Fails, // The defence ended the game
+        };
+        gain -= game_state.points_delta;
+    }
+
+    // Search from the enemies perspective, the enemies material change is the solvers loss
+    let reply = Search::builder().depth(2).eval(|_, _| Score::from_white_perspective(0)).weights(material_only_weights()).build().go(game_state).unwrap(); // new_turn only returns legal positions
+    if reply.value < 0 && mate_plies(reply.value).is_some() {
+        return SolutionResult::Mate;
+    }
+
+    let reply_value = i8::try_from(reply.value / PAWN).unwrap();
+
+    let gain = gain - reply_value;
+    if gain >= DECISIVE_GAIN {
+        return SolutionResult::Gain(gain);
+    }
+    SolutionResult::Fails
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_tactics_fork_test() { // Test a knight forking the king and queen
+        let game_state = GameState::from_fen("2q3k1/8/8/3N4/8/8/8/4K3 w - - 0 1").unwrap();
+        let tactics = find_tactics(game_state, 2);
+
+        let fork = Tactic {
+            tactic_move: Move {
+                piece_coordinates: [3, 4],
+                move_coordinates: [4, 6],
+            },
+            gain: 9,
+            mate: false,
+        };
+        assert!(tactics.contains(&fork));
+    }
+
+    #[test]
+    fn find_tactics_mate_test() { // Test a back rank mate being found
+        let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let tactics = find_tactics(game_state, 1);
+
+        let mate = Tactic {
+            tactic_move: Move {
+                piece_coordinates: [0, 0],
+                move_coordinates: [0, 7],
+            },
+            gain: crate::piece::info::CHECKMATE_VALUE,
+            mate: true,
+        };
+        assert_eq!(tactics, vec![mate]);
+    }
+
+    #[test]
+    fn verify_solution_test() { // Test a mate in 2 with the full line, the key move alone, a stalemating move, an illegal move, and a fork
+        let game_state = GameState::from_fen("7k/8/5K2/8/8/8/8/6R1 w - - 0 1").unwrap();
+
+        // 1. Kf7 Kh7 2. Rh1#
+        let key = Move {
+            piece_coordinates: [5, 5],
+            move_coordinates: [5, 6],
+        };
+        let mate = Move {
+            piece_coordinates: [6, 0],
+            move_coordinates: [7, 0],
+        };
+        assert_eq!(verify_solution(game_state, &[key, mate]), SolutionResult::Mate);
+        assert_eq!(verify_solution(game_state, &[key]), SolutionResult::Mate);
+
+        // 1. Rg7 is stalemate, 1. Kg7 walks next to the enemy king, and the king has left f6 after the key move
+        let stalemate = Move {
+            piece_coordinates: [6, 0],
+            move_coordinates: [6, 6],
+        };
+        let illegal = Move {
+            piece_coordinates: [5, 5],
+            move_coordinates: [6, 6],
+        };
+        assert_eq!(verify_solution(game_state, &[stalemate]), SolutionResult::Fails);
+        assert_eq!(verify_solution(game_state, &[illegal]), SolutionResult::Illegal(0));
+        assert_eq!(verify_solution(game_state, &[key, illegal]), SolutionResult::Illegal(1));
+        assert_eq!(verify_solution(game_state, &[mate]), SolutionResult::Fails);
+
+        let game_state = GameState::from_fen("2q3k1/8/8/3N4/8/8/8/4K3 w - - 0 1").unwrap();
+        let fork = Move {
+            piece_coordinates: [3, 4],
+            move_coordinates: [4, 6],
+        };
+        assert_eq!(verify_solution(game_state, &[fork]), SolutionResult::Gain(9));
+    }
+
+    #[test]
+    fn analyze_defense_test() { // Test the king walking to the corner where checkmate takes longer, and a checkmate played as the defence
+        let game_state = GameState::from_fen("6k1/Q7/4K3/8/8/8/8/8 b - - 0 1").unwrap();
+
+        // Kf8 is mated in 1 (Qf7), Kh8 is mated in 2
+        let defense = Move {
+            piece_coordinates: [1, 0],
+            move_coordinates: [0, 0],
+        };
+        assert_eq!(analyze_defense(game_state, 2), Some(defense));
+
+        // Rb1 checkmates instead of defending
+        let game_state = GameState::from_fen("1r4k1/Q7/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+        let mate = Move {
+            piece_coordinates: [6, 0],
+            move_coordinates: [6, 7],
+        };
+        assert_eq!(analyze_defense(game_state, 1), Some(mate));
+    }
+}
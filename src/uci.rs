@@ -0,0 +1,107 @@
+use crate::board::turn::new_turn;
+use crate::board::turn::GameState;
+use crate::piece::moves::Move;
+use crate::piece::moves::ParseError;
+
+// Error for a move in a move list that couldn't be parsed or played
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MoveError {
+    pub index: usize, // Index of the move in the move list
+    pub error_code: i8, // Error code from crate::board::errors
+}
+
+// Parses a long algebraic move (e.g. e2e4, e7e8q) into a move and a promotion id
+// The returned move is from the perspective of the team stated in perspective_white
+// The move is only parsed, ParseError::Illegal is left to the caller
+pub fn parse_move(uci: &str, perspective_white: bool) -> Result<(Move, i8), ParseError> {
+    use crate::piece::info::IDS;
+    use crate::piece::info::Piece;
+
+    let chars: Vec<char> = uci.chars().collect();
+    if chars.len() != 4 && chars.len() != 5 {
+        return Err(ParseError::Invalid);
+    }
+
+    let mut piece_coordinates = crate::ccn_to_cart(chars[0..2].to_vec()).map_err(|_| ParseError::Invalid)?;
+    let mut move_coordinates = crate::ccn_to_cart(chars[2..4].to_vec()).map_err(|_| ParseError::Invalid)?;
+    if !perspective_white {
+        piece_coordinates = crate::flip_coordinates(piece_coordinates);
+        move_coordinates = crate::flip_coordinates(move_coordinates);
+    }
+
+    // Pawns promote to queens unless a promotion piece is given
+    let mut promotion_id = IDS[4];
+    if chars.len() == 5 {
+        if !['q', 'r', 'b', 'n'].contains(&chars[4]) {
+            return Err(ParseError::Invalid);
+        }
+        promotion_id = crate::piece::info::id_fen_to_id(chars[4].to_ascii_uppercase(), Piece::instantiate_all());
+    }
+
+    let chess_move = Move {
+        piece_coordinates,
+        move_coordinates,
+    };
+    Ok((chess_move, promotion_id))
+}
+
+// Applies a list of long algebraic moves, as sent by "position ... moves ..."
+// Castles are given as the kings two square move
+pub fn apply_moves(mut game_state: GameState, moves: &[&str]) -> Result<GameState, MoveError> {
+    use crate::board::errors;
+
+    for (i, uci_move) in moves.iter().enumerate() {
+        let (chess_move, promotion_id) = match parse_move(uci_move, game_state.whites_turn) {
+            Ok(parsed) => parsed,
+            Err(_) => return Err(MoveError {
+                index: i,
+                error_code: errors::INVALID_MOVE_ERROR,
+            }),
+        };
+
+        game_state = match new_turn(chess_move.piece_coordinates, chess_move.move_coordinates, promotion_id, game_state) {
+            Ok(game_state_new) => game_state_new,
+            Err(error) => return Err(MoveError {
+                index: i,
+                error_code: error.error_code,
+            }),
+        };
+    }
+    Ok(game_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen;
+
+    #[test]
+    fn apply_moves_test() { // Test replaying a short opening line with a castle
+        let moves = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"];
+        let game_state = apply_moves(GameState::new(), &moves).unwrap();
+
+        assert!(!game_state.whites_turn);
+        assert_eq!(crate::flip_board(game_state.board_info.board), fen::decode("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1"));
+    }
+
+    #[test]
+    fn apply_moves_error_test() { // Test an illegal move reporting its index
+        let moves = ["e2e4", "e7e5", "e4e5"];
+        let result = apply_moves(GameState::new(), &moves);
+
+        assert_eq!(result.unwrap_err().index, 2);
+        assert_eq!(apply_moves(GameState::new(), &["e2"]).unwrap_err().index, 0);
+    }
+
+    #[test]
+    fn parse_move_test() { // Test a promotion from blacks perspective, and malformed moves being invalid
+        let (chess_move, promotion_id) = parse_move("a2a1n", false).unwrap();
+        assert_eq!(chess_move.piece_coordinates, [7, 6]);
+        assert_eq!(chess_move.move_coordinates, [7, 7]);
+        assert_eq!(promotion_id, crate::piece::info::IDS[2]);
+
+        assert_eq!(parse_move("e2", true), Err(ParseError::Invalid));
+        assert_eq!(parse_move("e2i4", true), Err(ParseError::Invalid));
+        assert_eq!(parse_move("e7e8k", true), Err(ParseError::Invalid));
+    }
+}